@@ -1,14 +1,22 @@
+use crate::gems;
 use crate::object::*;
-use petgraph::algo::dominators;
-use petgraph::graph::NodeIndex;
+use crate::units::Units;
+use petgraph::algo::{dominators, kosaraju_scc};
+use petgraph::graph::{Edge, NodeIndex};
 use petgraph::visit::Dfs;
+use petgraph::Direction;
 use petgraph::Graph;
-use std::collections::{HashMap, HashSet};
+use regex::Regex;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Write;
 use std::iter::Iterator;
+use std::str::FromStr;
 use timed_function::timed;
 
-type Index = NodeIndex<usize>;
+pub type Index = NodeIndex<usize>;
 
 #[derive(Debug)]
 pub struct Analysis {
@@ -34,6 +42,217 @@ pub struct Analysis {
 
     // output only class names in flamegraph
     class_name_only: bool,
+
+    // Objects whose memory counts towards sizes
+    generations: GenerationRange,
+
+    // Which gems objects are allocated by, for GroupBy::Gem
+    gemfile_lock: Option<gems::Lockfile>,
+}
+
+// A node of the dominator tree (or of an object's tree of referrers), with
+// children sorted by retained size.
+#[derive(Debug, Serialize)]
+pub struct TreeNode {
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    pub self_bytes: usize,
+    pub retained_bytes: usize,
+    // Of `retained_bytes`, those in GC heap slots and those malloc'd
+    pub slot_bytes: usize,
+    pub malloc_bytes: usize,
+    pub count: usize,
+    pub children: Vec<TreeNode>,
+}
+
+// Order of the rows in summary tables, largest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    // The table's bytes: retained bytes for retainers, otherwise its total
+    Bytes,
+    Count,
+    // Retainers by their own size; the same as `Bytes` for by-kind tables
+    SelfBytes,
+    Retained,
+}
+
+impl FromStr for Sort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Sort, String> {
+        match s {
+            "bytes" => Ok(Sort::Bytes),
+            "count" => Ok(Sort::Count),
+            "self" => Ok(Sort::SelfBytes),
+            "retained" => Ok(Sort::Retained),
+            _ => Err(format!("Unknown sort {:?}", s)),
+        }
+    }
+}
+
+pub const SORTS: &[&str] = &["bytes", "count", "self", "retained"];
+
+impl Sort {
+    fn key(self, stats: Stats) -> usize {
+        match self {
+            Sort::Count => stats.count,
+            _ => stats.bytes,
+        }
+    }
+}
+
+// What the by-kind stats are keyed by: each object's class (or type, for
+// objects without one), just the top-level module of its class, or where it
+// was allocated (for dumps taken with allocation tracing).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Kind,
+    Namespace,
+    File,
+    Location,
+    Method,
+    Gem,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GroupBy, String> {
+        match s {
+            "kind" => Ok(GroupBy::Kind),
+            "namespace" => Ok(GroupBy::Namespace),
+            "file" => Ok(GroupBy::File),
+            "location" => Ok(GroupBy::Location),
+            "method" => Ok(GroupBy::Method),
+            "gem" => Ok(GroupBy::Gem),
+            _ => Err(format!("Unknown grouping {:?}", s)),
+        }
+    }
+}
+
+pub const GROUP_BYS: &[&str] = &["kind", "namespace", "file", "location", "method", "gem"];
+
+// Allocation site key for objects allocated before tracing started, or in
+// untraced dumps
+const UNKNOWN_SITE: &str = "(unknown)";
+
+// Gem key for objects allocated by the app itself or the standard library
+const NO_GEM: &str = "(no gem)";
+
+impl GroupBy {
+    // e.g. "ActiveSupport" for an "ActiveSupport::Cache::Entry", or
+    // "app/models/user.rb:12" for an object allocated there. Dumps don't
+    // record the class of the allocating method, so methods are keyed by file,
+    // e.g. "app/models/user.rb in name".
+    pub fn key<'a>(self, obj: &'a Object, lockfile: Option<&gems::Lockfile>) -> Cow<'a, str> {
+        let kind = obj.kind.as_str();
+        match (self, &obj.location) {
+            (GroupBy::Kind, _) => Cow::Borrowed(kind),
+            (GroupBy::Namespace, _) => Cow::Borrowed(kind.split("::").next().unwrap_or(kind)),
+            (GroupBy::File, Some(location)) => Cow::Borrowed(&location.file),
+            (GroupBy::Location, Some(location)) => Cow::Owned(location.to_string()),
+            (GroupBy::Method, Some(location)) => match location.method {
+                Some(ref method) => Cow::Owned(format!("{} in {}", location.file, method)),
+                None => Cow::Borrowed(&location.file),
+            },
+            (GroupBy::Gem, Some(location)) => match gems::gem_of(&location.file, lockfile) {
+                Some(gem) => Cow::Owned(gem),
+                None => Cow::Borrowed(NO_GEM),
+            },
+            (_, None) => Cow::Borrowed(UNKNOWN_SITE),
+        }
+    }
+}
+
+// Kinds of a referencing and a referenced object
+pub type KindPair<'a> = (Cow<'a, str>, Cow<'a, str>);
+
+// Kinds of objects the Ruby VM allocates for its own bookkeeping, rather than
+// on behalf of application code
+pub const INTERNAL_KINDS: &[&str] = &["IMEMO", "ICLASS", "NODE", "ROOT", "ZOMBIE", "MOVED"];
+
+// Object types to include in summary tables, by regular expressions matched
+// against each object's kind.
+#[derive(Debug, Clone, Default)]
+pub struct KindFilter {
+    pub only: Option<Regex>,
+    pub exclude: Option<Regex>,
+    pub no_internal: bool,
+}
+
+impl KindFilter {
+    pub fn matches(&self, kind: &str) -> bool {
+        !(self.no_internal && INTERNAL_KINDS.contains(&kind))
+            && self.only.as_ref().is_none_or(|r| r.is_match(kind))
+            && !self.exclude.as_ref().is_some_and(|r| r.is_match(kind))
+    }
+}
+
+// GC generations of the objects whose memory counts, e.g. to leave out those
+// allocated at boot. Objects outside the range, including those without a
+// generation, still keep others alive but don't count for anything themselves.
+// The root and its categories always count, as in any other object count.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenerationRange {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+impl GenerationRange {
+    pub fn contains(&self, obj: &Object) -> bool {
+        if obj.is_root() || (self.min.is_none() && self.max.is_none()) {
+            return true;
+        }
+        obj.generation.is_some_and(|g| {
+            self.min.is_none_or(|min| g >= min) && self.max.is_none_or(|max| g <= max)
+        })
+    }
+
+    pub fn stats(&self, obj: &Object) -> Stats {
+        if self.contains(obj) {
+            obj.stats()
+        } else {
+            Stats::default()
+        }
+    }
+}
+
+// Simplifications applied to the relevant part of the dominator tree when
+// drawing it as a graph.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GraphOptions {
+    pub collapse_chains: bool,
+    pub aggregate_siblings: bool,
+    pub by_class: bool,
+    // Levels of the dominator tree to draw below the root
+    pub max_depth: Option<usize>,
+    // Most objects to draw, with the rest folded into an "other" node per
+    // parent
+    pub max_nodes: Option<usize>,
+}
+
+impl TreeNode {
+    // Drops nodes more than `max_depth` levels below this one; retained sizes
+    // and counts are unchanged.
+    pub fn truncate(&mut self, max_depth: usize) {
+        if max_depth == 0 {
+            self.children.clear();
+        } else {
+            for child in self.children.iter_mut() {
+                child.truncate(max_depth - 1);
+            }
+        }
+    }
+}
+
+// What an analysis counts objects' memory towards, besides their kinds
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub generations: GenerationRange,
+    // Gems as locked, for grouping by gem, rather than guessed from where
+    // they're installed
+    pub gemfile_lock: Option<gems::Lockfile>,
 }
 
 #[timed]
@@ -42,7 +261,12 @@ pub fn analyze(
     subgraph_root: Index,
     graph: ReferenceGraph,
     class_name_only: bool,
+    options: Options,
 ) -> Analysis {
+    let Options {
+        generations,
+        gemfile_lock,
+    } = options;
     let dominators = find_dominators(orig_root, &graph);
 
     let (root, dominated_subgraph, rest, dominators) = if subgraph_root == orig_root {
@@ -51,7 +275,7 @@ pub fn analyze(
         extract_dominated_subgraph(subgraph_root, &graph, &dominators)
     };
 
-    let subtree_sizes = dominator_subtree_sizes(&dominated_subgraph, &dominators);
+    let subtree_sizes = dominator_subtree_sizes(&dominated_subgraph, &dominators, generations);
 
     Analysis {
         root,
@@ -60,6 +284,8 @@ pub fn analyze(
         dominators,
         subtree_sizes,
         class_name_only,
+        generations,
+        gemfile_lock,
     }
 }
 
@@ -263,17 +489,18 @@ fn map_indices(
 fn dominator_subtree_sizes(
     graph: &ReferenceGraph,
     dominators: &HashMap<Index, Index>,
+    generations: GenerationRange,
 ) -> HashMap<Index, Stats> {
     let mut subtree_sizes: HashMap<Index, Stats> = HashMap::new();
 
     // Assign each node's stats to itself
     for i in graph.node_indices() {
-        subtree_sizes.insert(i, graph[i].stats());
+        subtree_sizes.insert(i, generations.stats(&graph[i]));
     }
 
     // Assign each node's stats to all of its dominators
     for mut i in graph.node_indices() {
-        let stats = graph[i].stats();
+        let stats = generations.stats(&graph[i]);
         while let Some(&d) = dominators.get(&i) {
             subtree_sizes.entry(d).and_modify(|e| *e = (*e).add(stats));
             i = d;
@@ -283,76 +510,391 @@ fn dominator_subtree_sizes(
     subtree_sizes
 }
 
-fn by_kind<'a, I: Iterator<Item = (&'a Object, Stats)>>(objs: I) -> HashMap<&'a String, Stats> {
+fn by_kind<'a, I: Iterator<Item = (&'a Object, Stats)>>(
+    objs: I,
+    group: GroupBy,
+    lockfile: Option<&gems::Lockfile>,
+) -> HashMap<Cow<'a, str>, Stats> {
     objs.fold(HashMap::new(), |mut by_kind, (obj, stats)| {
         by_kind
-            .entry(&obj.kind)
+            .entry(group.key(obj, lockfile))
             .and_modify(|c| *c = (*c).add(stats))
             .or_insert(stats);
         by_kind
     })
 }
 
-fn largest_and_rest<'a, K, I: Iterator<Item = (&'a K, Stats)>>(
-    iter: I,
-    count: usize,
-) -> (Vec<(&'a K, Stats)>, Stats) {
-    let sorted = {
-        let mut vec: Vec<(&'a K, Stats)> = iter.collect();
-        vec.sort_unstable_by_key(|(_, c)| usize::max_value() - c.bytes);
-        vec
-    };
+fn by_location<'a, I: Iterator<Item = (&'a Object, Stats)>>(objs: I) -> HashMap<String, Stats> {
+    objs.fold(HashMap::new(), |mut by_location, (obj, stats)| {
+        let key = match obj.location {
+            Some(ref location) => format!("{} @ {}", obj.kind, location),
+            None => format!("{} @ (unknown)", obj.kind),
+        };
+        by_location
+            .entry(key)
+            .and_modify(|c| *c = (*c).add(stats))
+            .or_insert(stats);
+        by_location
+    })
+}
+
+fn largest_and_rest<K, I, F>(iter: I, count: usize, key: F) -> (Vec<(K, Stats)>, Stats)
+where
+    I: Iterator<Item = (K, Stats)>,
+    F: Fn(&(K, Stats)) -> usize,
+{
+    let mut largest: Vec<(K, Stats)> = iter.collect();
+    largest.sort_unstable_by_key(|entry| Reverse(key(entry)));
 
-    if count >= sorted.len() {
-        (sorted, Stats::default())
+    if count >= largest.len() {
+        (largest, Stats::default())
     } else {
-        (
-            sorted[0..count].to_vec(),
-            sorted[count..]
-                .iter()
-                .fold(Stats::default(), |mut acc, (_, c)| acc.add(*c)),
-        )
+        let rest = largest
+            .split_off(count)
+            .iter()
+            .fold(Stats::default(), |mut acc, (_, c)| acc.add(*c));
+        (largest, rest)
     }
 }
 
+// Merges each chain of three or more nodes that each have only one child,
+// e.g. A dominating only B dominating only C, into a single node labeled with
+// both ends of the chain and the number of nodes elided between them.
+fn collapse_chains(graph: &ReferenceGraph) -> ReferenceGraph {
+    let only_child = |i: Index| {
+        let mut children = graph.neighbors(i);
+        match (children.next(), children.next()) {
+            (Some(child), None) => Some(child),
+            _ => None,
+        }
+    };
+
+    // The nodes below the head of each chain, the last being its tail
+    let mut chains: HashMap<Index, Vec<Index>> = HashMap::new();
+    for head in graph.node_indices() {
+        let is_head = graph
+            .neighbors_directed(head, Direction::Incoming)
+            .next()
+            .is_none_or(|parent| only_child(parent).is_none());
+        if !is_head {
+            continue;
+        }
+
+        let mut rest = Vec::new();
+        let mut i = head;
+        while let Some(child) = only_child(i) {
+            rest.push(child);
+            i = child;
+        }
+        if rest.len() >= 2 {
+            chains.insert(head, rest);
+        }
+    }
+
+    let below_heads: HashSet<Index> = chains.values().flatten().cloned().collect();
+    let mut collapsed: ReferenceGraph = Graph::default();
+    let mut old_to_new: HashMap<Index, Index> = HashMap::new();
+    for i in graph.node_indices().filter(|i| !below_heads.contains(i)) {
+        let mut obj = graph[i].clone();
+        if let Some(rest) = chains.get(&i) {
+            obj.label = Some(format!(
+                "{}\n⋮ {} more\n{}",
+                graph[i],
+                rest.len() - 1,
+                graph[rest[rest.len() - 1]]
+            ));
+        }
+        old_to_new.insert(i, collapsed.add_node(obj));
+    }
+    for (head, rest) in chains.iter() {
+        for i in rest {
+            old_to_new.insert(*i, old_to_new[head]);
+        }
+    }
+
+    for e in graph.raw_edges() {
+        let (source, target) = (old_to_new[&e.source()], old_to_new[&e.target()]);
+        if source != target {
+            collapsed.add_edge(source, target, e.weight);
+        }
+    }
+    collapsed
+}
+
 impl Analysis {
-    pub fn live_stats_by_kind(&self, top_n: usize) -> (Vec<(&String, Stats)>, Stats) {
-        let stats = by_kind(self.dominated_subgraph.node_indices().map(|i| {
+    pub fn live_stats_by_kind(
+        &self,
+        top_n: usize,
+        sort: Sort,
+        filter: &KindFilter,
+        group: GroupBy,
+    ) -> (Vec<(Cow<'_, str>, Stats)>, Stats) {
+        let stats = by_kind(
+            self.dominated_subgraph
+                .node_indices()
+                .map(|i| {
+                    let obj = &self.dominated_subgraph[i];
+                    (obj, self.generations.stats(obj))
+                })
+                .filter(|(obj, _)| filter.matches(&obj.kind)),
+            group,
+            self.gemfile_lock.as_ref(),
+        );
+        largest_and_rest(stats.into_iter(), top_n, |(_, s)| sort.key(*s))
+    }
+
+    pub fn retained_stats_by_kind(
+        &self,
+        top_n: usize,
+        sort: Sort,
+        filter: &KindFilter,
+        group: GroupBy,
+    ) -> (Vec<(Cow<'_, str>, Stats)>, Stats) {
+        // Instances dominated by another of the same kind are already counted
+        // towards that one, so leave them out to get what all instances of a
+        // kind retain together
+        let nested = self.dominated_by_same_kind(group);
+        let stats = by_kind(
+            self.dominated_subgraph
+                .node_indices()
+                .filter(|i| !nested.contains(i))
+                .map(|i| {
+                    let obj = &self.dominated_subgraph[i];
+                    (obj, self.subtree_sizes[&i])
+                })
+                .filter(|(obj, _)| filter.matches(&obj.kind)),
+            group,
+            self.gemfile_lock.as_ref(),
+        );
+        largest_and_rest(stats.into_iter(), top_n, |(_, s)| sort.key(*s))
+    }
+
+    // What old objects referenced by at most `max_referrers` others retain, by
+    // kind: long-lived memory held through a single path is what a growing
+    // cache or registry looks like, so these are the likeliest leaks.
+    // Instances dominated by another of the same kind are counted once.
+    pub fn suspect_stats_by_kind(
+        &self,
+        top_n: usize,
+        max_referrers: usize,
+        filter: &KindFilter,
+        group: GroupBy,
+    ) -> (Vec<(Cow<'_, str>, Stats)>, Stats) {
+        let graph = &self.dominated_subgraph;
+        let nested = self.dominated_by_same_kind(group);
+        let stats = by_kind(
+            graph
+                .node_indices()
+                .filter(|&i| {
+                    graph[i].old
+                        && !graph[i].is_root()
+                        && graph.neighbors_directed(i, Direction::Incoming).count() <= max_referrers
+                        && !nested.contains(&i)
+                })
+                .map(|i| (&graph[i], self.subtree_sizes[&i]))
+                .filter(|(obj, _)| filter.matches(&obj.kind)),
+            group,
+            self.gemfile_lock.as_ref(),
+        );
+        largest_and_rest(stats.into_iter(), top_n, |(_, s)| s.bytes)
+    }
+
+    // Nodes dominated by another of the same kind, found in one preorder walk
+    // of the dominator tree that counts the kinds on the path down to each
+    // node, so every node's kind is worked out once
+    fn dominated_by_same_kind(&self, group: GroupBy) -> HashSet<Index> {
+        enum Step<'a> {
+            Enter(Index),
+            Leave(Cow<'a, str>),
+        }
+
+        let graph = &self.dominated_subgraph;
+        let mut children: HashMap<Index, Vec<Index>> = HashMap::new();
+        for (child, parent) in self.dominators.iter() {
+            children.entry(*parent).or_default().push(*child);
+        }
+
+        let mut nested = HashSet::new();
+        let mut on_path: HashMap<Cow<str>, usize> = HashMap::new();
+        let mut stack: Vec<Step> = graph
+            .node_indices()
+            .filter(|i| !self.dominators.contains_key(i))
+            .map(Step::Enter)
+            .collect();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Enter(i) => {
+                    let kind = group.key(&graph[i], self.gemfile_lock.as_ref());
+                    let count = on_path.entry(kind.clone()).or_default();
+                    if *count > 0 {
+                        nested.insert(i);
+                    }
+                    *count += 1;
+                    stack.push(Step::Leave(kind));
+                    if let Some(children) = children.get(&i) {
+                        stack.extend(children.iter().map(|&c| Step::Enter(c)));
+                    }
+                }
+                Step::Leave(kind) => {
+                    if let Some(count) = on_path.get_mut(&kind) {
+                        *count -= 1;
+                    }
+                }
+            }
+        }
+        nested
+    }
+
+    // Live instances of `kind`, keyed by the kind of their nearest dominator
+    // that isn't another instance. Counts are numbers of instances, and bytes
+    // what they retain.
+    pub fn dominator_stats_of_kind(
+        &self,
+        kind: &str,
+        top_n: usize,
+        sort: Sort,
+        group: GroupBy,
+    ) -> (Vec<(Cow<'_, str>, Stats)>, Stats) {
+        let graph = &self.dominated_subgraph;
+        let mut stats: HashMap<Cow<str>, Stats> = HashMap::new();
+        for i in graph.node_indices().filter(|i| graph[*i].kind == kind) {
+            let mut d = i;
+            let mut nested = false;
+            while let Some(&next) = self.dominators.get(&d) {
+                d = next;
+                if graph[d].kind != kind {
+                    break;
+                }
+                nested = true;
+            }
+            if graph[d].kind == kind {
+                continue;
+            }
+
+            // Nested instances' memory is already counted towards the
+            // outermost one
+            let instance = if nested {
+                Stats {
+                    count: 1,
+                    ..Stats::default()
+                }
+            } else {
+                Stats {
+                    count: 1,
+                    ..self.subtree_sizes[&i]
+                }
+            };
+            stats
+                .entry(group.key(&graph[d], self.gemfile_lock.as_ref()))
+                .and_modify(|s| *s = s.add(instance))
+                .or_insert(instance);
+        }
+
+        largest_and_rest(stats.into_iter(), top_n, |(_, s)| sort.key(*s))
+    }
+
+    // Keyed by kind and allocation site, e.g. "String @ app/models/user.rb:12"
+    pub fn live_stats_by_location(&self) -> HashMap<String, Stats> {
+        by_location(self.dominated_subgraph.node_indices().map(|i| {
             let obj = &self.dominated_subgraph[i];
-            (obj, obj.stats())
-        }));
-        largest_and_rest(stats.iter().map(|(k, v)| (*k, *v)), top_n)
+            (obj, self.generations.stats(obj))
+        }))
     }
 
-    pub fn retained_stats_by_kind(&self, top_n: usize) -> (Vec<(&String, Stats)>, Stats) {
-        let stats = by_kind(self.dominated_subgraph.node_indices().map(|i| {
+    pub fn retained_stats_by_location(&self) -> HashMap<String, Stats> {
+        by_location(self.dominated_subgraph.node_indices().map(|i| {
             let obj = &self.dominated_subgraph[i];
             (obj, self.subtree_sizes[&i])
-        }));
-        largest_and_rest(stats.iter().map(|(k, v)| (*k, *v)), top_n)
+        }))
     }
 
-    pub fn unreachable_stats_by_kind(&self, top_n: usize) -> (Vec<(&String, Stats)>, Stats) {
-        let stats = by_kind(self.rest.iter().map(|o| (o, o.stats())));
-        largest_and_rest(stats.iter().map(|(k, v)| (*k, *v)), top_n)
+    pub fn unreachable_stats_by_kind(
+        &self,
+        top_n: usize,
+        sort: Sort,
+        filter: &KindFilter,
+        group: GroupBy,
+    ) -> (Vec<(Cow<'_, str>, Stats)>, Stats) {
+        let stats = by_kind(
+            self.rest
+                .iter()
+                .filter(|o| filter.matches(&o.kind))
+                .map(|o| (o, self.generations.stats(o))),
+            group,
+            self.gemfile_lock.as_ref(),
+        );
+        largest_and_rest(stats.into_iter(), top_n, |(_, s)| sort.key(*s))
     }
 
-    pub fn dominator_subtree_stats(&self, top_n: usize) -> (Vec<(&Object, Stats)>, Stats) {
-        let (largest, rest) =
-            largest_and_rest(self.subtree_sizes.iter().map(|(k, v)| (k, *v)), top_n);
+    pub fn dominator_subtree_stats(
+        &self,
+        top_n: usize,
+        sort: Sort,
+        filter: &KindFilter,
+    ) -> (Vec<(Index, Stats)>, Stats) {
+        let (largest, rest) = largest_and_rest(
+            self.subtree_sizes
+                .iter()
+                .filter(|(i, _)| filter.matches(&self.dominated_subgraph[**i].kind))
+                .map(|(k, v)| (k, *v)),
+            top_n,
+            |(i, s)| match sort {
+                Sort::SelfBytes => self.dominated_subgraph[**i].bytes,
+                _ => sort.key(*s),
+            },
+        );
         (
-            largest
-                .into_iter()
-                .map(|(i, stats)| (&self.dominated_subgraph[*i], stats))
-                .collect(),
+            largest.into_iter().map(|(i, stats)| (*i, stats)).collect(),
             rest,
         )
     }
 
-    pub fn relevant_dominator_subgraph(&self, relevance_threshold: f64) -> ReferenceGraph {
-        let threshold_bytes =
-            (self.dominated_totals().bytes as f64 * relevance_threshold).floor() as usize;
+    // References between live objects, keyed by the kinds of the referencing
+    // and referenced objects. Counts are numbers of references, and bytes the
+    // self bytes of the referenced objects (counted once per reference).
+    pub fn reference_stats_by_kind(
+        &self,
+        top_n: usize,
+        sort: Sort,
+        filter: &KindFilter,
+        group: GroupBy,
+    ) -> (Vec<(KindPair<'_>, Stats)>, Stats) {
+        let graph = &self.dominated_subgraph;
+        let mut stats: HashMap<KindPair, Stats> = HashMap::new();
+        for edge in graph.raw_edges() {
+            let (source, target) = (&graph[edge.source()], &graph[edge.target()]);
+            if !filter.matches(&target.kind) {
+                continue;
+            }
+            let References(count) = edge.weight;
+            let reference = Stats {
+                count,
+                bytes: target.bytes * count,
+                slot_bytes: target.slot_bytes * count,
+            };
+            stats
+                .entry((
+                    group.key(source, self.gemfile_lock.as_ref()),
+                    group.key(target, self.gemfile_lock.as_ref()),
+                ))
+                .and_modify(|s| *s = s.add(reference))
+                .or_insert(reference);
+        }
+
+        largest_and_rest(stats.into_iter(), top_n, |(_, s)| sort.key(*s))
+    }
 
+    // Minimum retained size of nodes retaining at least the given fraction of memory.
+    pub fn threshold_bytes(&self, relevance_threshold: f64) -> usize {
+        (self.dominated_totals().bytes as f64 * relevance_threshold).floor() as usize
+    }
+
+    pub fn relevant_dominator_subgraph(
+        &self,
+        threshold_bytes: usize,
+        units: Units,
+    ) -> ReferenceGraph {
+        let total_bytes = self.dominated_totals().bytes;
         let mut subgraph: ReferenceGraph = Graph::default();
         let mut old_to_new: HashMap<Index, Index> = HashMap::new();
 
@@ -362,7 +904,9 @@ impl Analysis {
             .filter(|(_, stats)| stats.bytes >= threshold_bytes)
         {
             let obj = &self.dominated_subgraph[*i];
-            let added = subgraph.add_node(obj.with_dominator_stats(*stats));
+            let self_bytes = self.generations.stats(obj).bytes;
+            let added =
+                subgraph.add_node(obj.with_dominator_stats(self_bytes, *stats, total_bytes, units));
             old_to_new.insert(*i, added);
         }
 
@@ -375,45 +919,787 @@ impl Analysis {
         subgraph
     }
 
+    // Like `relevant_dominator_subgraph`, but with siblings of the same class
+    // that dominate nothing relevant drawn as a single summary node, e.g.
+    // "String ×500, 3.2 MiB", when there are at least two of them and they
+    // are relevant together. Individually irrelevant siblings are included.
+    fn aggregated_dominator_subgraph(
+        &self,
+        threshold_bytes: usize,
+        units: Units,
+    ) -> ReferenceGraph {
+        let total_bytes = self.dominated_totals().bytes;
+        let is_relevant = |i: &Index| self.subtree_sizes[i].bytes >= threshold_bytes;
+        // Siblings never dominate each other, so their retained sizes add up
+        let retained = |members: &[Index]| {
+            members.iter().fold(Stats::default(), |mut acc, i| {
+                acc.add(self.subtree_sizes[i])
+            })
+        };
+
+        let relevant_parents: HashSet<Index> = self
+            .dominators
+            .iter()
+            .filter(|(child, _)| is_relevant(child))
+            .map(|(_, parent)| *parent)
+            .collect();
+
+        let mut siblings: HashMap<(Index, &str), Vec<Index>> = HashMap::new();
+        for (child, parent) in self.dominators.iter() {
+            if is_relevant(parent) && !relevant_parents.contains(child) {
+                let kind = self.dominated_subgraph[*child].kind.as_str();
+                siblings.entry((*parent, kind)).or_default().push(*child);
+            }
+        }
+        siblings
+            .retain(|_, members| members.len() >= 2 && retained(members).bytes >= threshold_bytes);
+        let aggregated: HashSet<Index> = siblings.values().flatten().cloned().collect();
+        // Children of a summary node are already counted in it
+        siblings.retain(|(parent, _), _| !aggregated.contains(parent));
+
+        let mut subgraph: ReferenceGraph = Graph::default();
+        let mut old_to_new: HashMap<Index, Index> = HashMap::new();
+        for (i, stats) in self.subtree_sizes.iter() {
+            if stats.bytes >= threshold_bytes && !aggregated.contains(i) {
+                let obj = &self.dominated_subgraph[*i];
+                let self_bytes = self.generations.stats(obj).bytes;
+                let added = subgraph.add_node(obj.with_dominator_stats(
+                    self_bytes,
+                    *stats,
+                    total_bytes,
+                    units,
+                ));
+                old_to_new.insert(*i, added);
+            }
+        }
+        for (old, new) in old_to_new.iter() {
+            if let Some(d) = self.dominators.get(old) {
+                subgraph.add_edge(old_to_new[d], *new, EDGE_WEIGHT);
+            }
+        }
+
+        for ((parent, kind), members) in siblings.iter_mut() {
+            members.sort_unstable_by_key(|i| Reverse(self.subtree_sizes[i].bytes));
+            let stats = retained(members);
+            let summary = Object {
+                address: self.dominated_subgraph[members[0]].address,
+                bytes: members
+                    .iter()
+                    .map(|i| self.dominated_subgraph[*i].bytes)
+                    .sum(),
+                slot_bytes: members
+                    .iter()
+                    .map(|i| self.dominated_subgraph[*i].slot_bytes)
+                    .sum(),
+                kind: kind.to_string(),
+                label: Some(format!(
+                    "{} ×{}, {}, {}",
+                    kind,
+                    members.len(),
+                    units.bytes(stats.bytes),
+                    percent_of(stats.bytes, total_bytes)
+                )),
+                location: None,
+                generation: None,
+                old: false,
+            };
+            let added = subgraph.add_node(summary);
+            subgraph.add_edge(old_to_new[parent], added, EDGE_WEIGHT);
+        }
+
+        subgraph
+    }
+
+    // The dominator tree with all instances of each class merged into one
+    // node, keeping classes that retain at least the threshold together. Each
+    // edge stands for every instance of one class dominating one of another.
+    fn class_dominator_graph(&self, relevance_threshold: f64, units: Units) -> ReferenceGraph {
+        let threshold_bytes = self.threshold_bytes(relevance_threshold);
+        let no_filter = KindFilter::default();
+        let (live, _) = self.live_stats_by_kind(usize::MAX, Sort::Bytes, &no_filter, GroupBy::Kind);
+        let live: HashMap<Cow<str>, Stats> = live.into_iter().collect();
+        let (mut retained, _) =
+            self.retained_stats_by_kind(usize::MAX, Sort::Bytes, &no_filter, GroupBy::Kind);
+        retained.retain(|(_, stats)| stats.bytes >= threshold_bytes);
+
+        let mut graph: ReferenceGraph = Graph::default();
+        let mut classes: HashMap<Cow<str>, Index> = HashMap::new();
+        for (kind, stats) in retained {
+            let instances = live[&kind];
+            let class = Object {
+                address: 0,
+                bytes: instances.bytes,
+                slot_bytes: instances.slot_bytes,
+                kind: kind.to_string(),
+                label: Some(format!(
+                    "{} ×{}: {} self, {} refs, {} objects, {}",
+                    kind,
+                    instances.count,
+                    units.bytes(instances.bytes),
+                    units.bytes(stats.bytes - instances.bytes),
+                    stats.count,
+                    percent_of(stats.bytes, self.dominated_totals().bytes)
+                )),
+                location: None,
+                generation: None,
+                old: false,
+            };
+            classes.insert(kind, graph.add_node(class));
+        }
+
+        let mut edges: HashMap<(Index, Index), usize> = HashMap::new();
+        for (child, parent) in self.dominators.iter() {
+            let kinds = (
+                self.dominated_subgraph[*parent].kind.as_str(),
+                self.dominated_subgraph[*child].kind.as_str(),
+            );
+            if let (Some(&source), Some(&target)) = (classes.get(kinds.0), classes.get(kinds.1)) {
+                if source != target {
+                    *edges.entry((source, target)).or_default() += 1;
+                }
+            }
+        }
+        let mut edges: Vec<_> = edges.into_iter().collect();
+        edges.sort_unstable();
+        for ((source, target), count) in edges {
+            graph.add_edge(source, target, References(count));
+        }
+
+        graph
+    }
+
+    // Drops nodes of a graph drawn from the dominator tree more than
+    // `max_depth` levels below its root. Nodes left without some of their
+    // children are labeled with the memory retained below them.
+    fn truncate_graph(
+        &self,
+        graph: &ReferenceGraph,
+        max_depth: usize,
+        units: Units,
+    ) -> ReferenceGraph {
+        let mut depths: HashMap<Index, usize> = HashMap::new();
+        let mut queue: VecDeque<Index> = graph
+            .externals(Direction::Incoming)
+            .inspect(|i| {
+                depths.insert(*i, 0);
+            })
+            .collect();
+        while let Some(i) = queue.pop_front() {
+            let depth = depths[&i] + 1;
+            for j in graph.neighbors(i) {
+                if let Entry::Vacant(e) = depths.entry(j) {
+                    e.insert(depth);
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        let indices: HashMap<usize, Index> = self
+            .dominated_subgraph
+            .node_indices()
+            .map(|i| (self.dominated_subgraph[i].address, i))
+            .collect();
+        graph.filter_map(
+            |i, obj| {
+                let depth = depths[&i];
+                if depth > max_depth {
+                    return None;
+                }
+                let mut obj = obj.clone();
+                if depth == max_depth && graph.neighbors(i).next().is_some() {
+                    let stats = self.subtree_sizes[&indices[&obj.address]];
+                    let own = self.generations.stats(&obj);
+                    obj.label = Some(format!(
+                        "{}\npruned: {} in {} objects",
+                        obj,
+                        units.bytes(stats.bytes - own.bytes),
+                        stats.count - own.count
+                    ));
+                }
+                Some(obj)
+            },
+            |_, e| Some(*e),
+        )
+    }
+
+    // The lowest threshold for drawing at most `max_nodes` objects; ties with
+    // the last one mean there may be fewer.
+    fn capped_threshold_bytes(&self, max_nodes: usize) -> usize {
+        let mut sizes: Vec<usize> = self.subtree_sizes.values().map(|s| s.bytes).collect();
+        sizes.sort_unstable_by_key(|bytes| Reverse(*bytes));
+        sizes.get(max_nodes).map_or(0, |bytes| bytes + 1)
+    }
+
+    // Adds an "other" node under each node of a graph drawn from the
+    // dominator tree with `threshold_bytes`, for its children that retain at
+    // least `relevant_bytes` but were left out.
+    fn add_other_nodes(
+        &self,
+        graph: &mut ReferenceGraph,
+        threshold_bytes: usize,
+        relevant_bytes: usize,
+        units: Units,
+    ) {
+        let total_bytes = self.dominated_totals().bytes;
+        let mut others: HashMap<usize, Stats> = HashMap::new();
+        for (child, parent) in self.dominators.iter() {
+            let stats = self.subtree_sizes[child];
+            let parent_drawn = self.subtree_sizes[parent].bytes >= threshold_bytes;
+            if parent_drawn && stats.bytes >= relevant_bytes && stats.bytes < threshold_bytes {
+                let address = self.dominated_subgraph[*parent].address;
+                let other = others.entry(address).or_default();
+                *other = other.add(stats);
+            }
+        }
+
+        for i in graph.node_indices().collect::<Vec<_>>() {
+            if let Some(stats) = others.remove(&graph[i].address) {
+                let other = graph.add_node(Object {
+                    address: 0,
+                    bytes: 0,
+                    slot_bytes: 0,
+                    kind: "other".to_string(),
+                    label: Some(format!(
+                        "other ({} objects, {}, {})",
+                        stats.count,
+                        units.bytes(stats.bytes),
+                        percent_of(stats.bytes, total_bytes)
+                    )),
+                    location: None,
+                    generation: None,
+                    old: false,
+                });
+                graph.add_edge(i, other, EDGE_WEIGHT);
+            }
+        }
+    }
+
+    // The relevant dominator subgraph, simplified for drawing.
+    pub fn dominator_graph(
+        &self,
+        relevance_threshold: f64,
+        units: Units,
+        options: GraphOptions,
+    ) -> ReferenceGraph {
+        let relevant_bytes = self.threshold_bytes(relevance_threshold);
+        let threshold_bytes = match options.max_nodes {
+            Some(max_nodes) => relevant_bytes.max(self.capped_threshold_bytes(max_nodes)),
+            None => relevant_bytes,
+        };
+        let mut graph = if options.by_class {
+            self.class_dominator_graph(relevance_threshold, units)
+        } else if options.aggregate_siblings {
+            self.aggregated_dominator_subgraph(threshold_bytes, units)
+        } else {
+            self.relevant_dominator_subgraph(threshold_bytes, units)
+        };
+        if threshold_bytes > relevant_bytes && !options.by_class {
+            self.add_other_nodes(&mut graph, threshold_bytes, relevant_bytes, units);
+        }
+        let graph = match options.max_depth {
+            Some(max_depth) if !options.by_class => self.truncate_graph(&graph, max_depth, units),
+            _ => graph,
+        };
+        if options.collapse_chains {
+            collapse_chains(&graph)
+        } else {
+            graph
+        }
+    }
+
+    // Like `relevant_dominator_subgraph`, but as a tree rooted at `self.root`.
+    pub fn dominator_tree(&self, relevance_threshold: f64) -> TreeNode {
+        let threshold_bytes = self.threshold_bytes(relevance_threshold);
+
+        // A node's retained size is never larger than its dominator's, so the
+        // parent of every relevant node is also relevant
+        let mut children: HashMap<Index, Vec<Index>> = HashMap::new();
+        for (child, parent) in self.dominators.iter() {
+            if self.subtree_sizes[child].bytes >= threshold_bytes {
+                children.entry(*parent).or_default().push(*child);
+            }
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_unstable_by_key(|i| {
+                (
+                    usize::MAX - self.subtree_sizes[i].bytes,
+                    self.dominated_subgraph[*i].address,
+                )
+            });
+        }
+
+        // Build bottom-up rather than recursively, since dominator chains can
+        // be arbitrarily deep
+        let mut preorder = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(i) = stack.pop() {
+            preorder.push(i);
+            if let Some(c) = children.get(&i) {
+                stack.extend(c.iter());
+            }
+        }
+
+        let mut built: HashMap<Index, TreeNode> = HashMap::new();
+        for i in preorder.into_iter().rev() {
+            let node = self.tree_node(
+                i,
+                children
+                    .get(&i)
+                    .map(|c| c.iter().map(|c| built.remove(c).unwrap()).collect())
+                    .unwrap_or_default(),
+            );
+            built.insert(i, node);
+        }
+
+        built.remove(&self.root).unwrap()
+    }
+
+    // The objects referencing `target`, and (up to `depth` levels up) the
+    // objects referencing those, as a tree rooted at `target`. Objects
+    // already on the way down from a node aren't repeated below it.
+    pub fn referrer_tree(&self, target: Index, depth: usize) -> TreeNode {
+        let mut path = vec![target];
+        self.referrer_subtree(&mut path, depth)
+    }
+
+    fn referrer_subtree(&self, path: &mut Vec<Index>, depth: usize) -> TreeNode {
+        let graph = &self.dominated_subgraph;
+        let i = *path.last().unwrap();
+
+        let mut children = Vec::new();
+        if depth > 0 {
+            let mut referrers: Vec<Index> = graph
+                .neighbors_directed(i, Direction::Incoming)
+                .filter(|r| !path.contains(r))
+                .collect::<HashSet<Index>>()
+                .into_iter()
+                .collect();
+            referrers.sort_unstable_by_key(|r| {
+                (
+                    Reverse(self.subtree_sizes[r].bytes),
+                    self.dominated_subgraph[*r].address,
+                )
+            });
+            for r in referrers {
+                path.push(r);
+                children.push(self.referrer_subtree(path, depth - 1));
+                path.pop();
+            }
+        }
+
+        self.tree_node(i, children)
+    }
+
+    fn tree_node(&self, i: Index, children: Vec<TreeNode>) -> TreeNode {
+        let obj = &self.dominated_subgraph[i];
+        let stats = self.subtree_sizes[&i];
+        TreeNode {
+            address: format!("{:#x}", obj.address),
+            kind: obj.kind.clone(),
+            label: obj.to_string(),
+            self_bytes: obj.bytes,
+            retained_bytes: stats.bytes,
+            slot_bytes: stats.slot_bytes,
+            malloc_bytes: stats.malloc_bytes(),
+            count: stats.count,
+            children,
+        }
+    }
+
     pub fn dominated_totals(&self) -> Stats {
         self.subtree_sizes[&self.root]
     }
 
-    // Produces valid input for inferno::flamegraph::from_lines
-    //
-    // The basic idea is that we treat every reachable byte as a sample.
-    pub fn flamegraph_lines(&self) -> Vec<String> {
-        let mut lines = Vec::with_capacity(self.dominated_subgraph.node_count());
+    pub fn root(&self) -> Index {
+        self.root
+    }
+
+    // The Gemfile.lock objects are grouped by gem with, if any
+    pub fn gemfile_lock(&self) -> Option<&gems::Lockfile> {
+        self.gemfile_lock.as_ref()
+    }
+
+    // The reference graph itself, restricted to the dominated subgraph.
+    pub fn dominated_subgraph(&self) -> &ReferenceGraph {
+        &self.dominated_subgraph
+    }
+
+    // What the graph exports include: the objects retaining at least
+    // `threshold_bytes`, and the references between them.
+    pub fn exported(&self, threshold_bytes: usize) -> (Vec<Index>, Vec<&Edge<References, usize>>) {
+        let graph = &self.dominated_subgraph;
+        let relevant = |i| self.retained_stats(i).bytes >= threshold_bytes;
+        let nodes = graph.node_indices().filter(|&i| relevant(i)).collect();
+        let edges = graph
+            .raw_edges()
+            .iter()
+            .filter(|e| relevant(e.source()) && relevant(e.target()))
+            .collect();
+        (nodes, edges)
+    }
+
+    // Objects unreachable from the root if the analysis is of the full graph,
+    // or else those reachable from, but not dominated by, the subtree root.
+    pub fn rest(&self) -> &[Object] {
+        &self.rest
+    }
+
+    // Immediate dominator of a node of the dominated subgraph, or None for the root.
+    pub fn dominator(&self, i: Index) -> Option<Index> {
+        self.dominators.get(&i).cloned()
+    }
+
+    // Node of the dominated subgraph for the object at `address`, if any.
+    pub fn index_of(&self, address: usize) -> Option<Index> {
+        self.dominated_subgraph
+            .node_indices()
+            .find(|i| self.dominated_subgraph[*i].address == address)
+    }
+
+    // Up to `count` of the shortest reference paths from `source` to
+    // `target`, each through a different one of the objects referencing it,
+    // shortest first.
+    pub fn paths_between(&self, source: Index, target: Index, count: usize) -> Vec<Vec<Index>> {
+        let graph = &self.dominated_subgraph;
+        if target == source {
+            return vec![vec![target]];
+        }
 
+        // Breadth-first, so each node's parent is on a shortest path to it.
+        // Paths end at the target, so nothing is reached through it.
+        let mut parents: HashMap<Index, Index> = HashMap::new();
+        let mut distances: HashMap<Index, usize> = HashMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(source, 0);
+        queue.push_back(source);
+        while let Some(i) = queue.pop_front() {
+            if i == target {
+                continue;
+            }
+            let distance = distances[&i] + 1;
+            for j in graph.neighbors(i) {
+                if let Entry::Vacant(entry) = distances.entry(j) {
+                    entry.insert(distance);
+                    parents.insert(j, i);
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        let mut referrers: Vec<Index> = graph
+            .neighbors_directed(target, Direction::Incoming)
+            .filter(|i| *i != target && distances.contains_key(i))
+            .collect::<HashSet<Index>>()
+            .into_iter()
+            .collect();
+        referrers.sort_unstable_by_key(|i| (distances[i], graph[*i].address));
+
+        referrers
+            .into_iter()
+            .take(count)
+            .map(|referrer| {
+                let mut path = vec![target, referrer];
+                let mut i = referrer;
+                while let Some(&parent) = parents.get(&i) {
+                    path.push(parent);
+                    i = parent;
+                }
+                path.reverse();
+                path
+            })
+            .collect()
+    }
+
+    // Reference cycles (strongly connected components of more than one node)
+    // with the memory retained by their members between them, most members
+    // first.
+    pub fn cycles(&self) -> Vec<(Vec<Index>, Stats)> {
+        let mut cycles: Vec<(Vec<Index>, Stats)> = kosaraju_scc(&self.dominated_subgraph)
+            .into_iter()
+            .filter(|members| members.len() > 1)
+            .map(|mut members| {
+                members.sort_unstable_by_key(|i| {
+                    (
+                        Reverse(self.subtree_sizes[i].bytes),
+                        self.dominated_subgraph[*i].address,
+                    )
+                });
+                let retained = self.retained_stats_of_all(&members);
+                (members, retained)
+            })
+            .collect();
+        cycles.sort_unstable_by_key(|(members, retained)| {
+            (
+                Reverse(members.len()),
+                Reverse(retained.bytes),
+                self.dominated_subgraph[members[0]].address,
+            )
+        });
+        cycles
+    }
+
+    // Memory retained by a set of nodes, without counting twice the nodes
+    // dominated by others in the set.
+    fn retained_stats_of_all(&self, nodes: &[Index]) -> Stats {
+        let set: HashSet<Index> = nodes.iter().cloned().collect();
+        nodes
+            .iter()
+            .filter(|&&i| {
+                let mut d = self.dominator(i);
+                while let Some(j) = d {
+                    if set.contains(&j) {
+                        return false;
+                    }
+                    d = self.dominator(j);
+                }
+                true
+            })
+            .fold(Stats::default(), |mut acc, i| {
+                acc.add(self.subtree_sizes[i])
+            })
+    }
+
+    // A smallest set of references (as referrer and referenced node) whose
+    // removal would make `target` unreachable from the root: a minimum cut,
+    // found by pushing a unit of flow along each reference.
+    pub fn min_cut(&self, target: Index) -> Vec<(Index, Index)> {
+        let graph = &self.dominated_subgraph;
+        if target == self.root {
+            return Vec::new();
+        }
+
+        // Residual graph search from the root, recording how each node was
+        // reached: forwards along a reference without flow, or backwards
+        // along one with flow.
+        let mut flow: HashSet<(Index, Index)> = HashSet::new();
+        let search = |flow: &HashSet<(Index, Index)>| {
+            let mut parents: HashMap<Index, (Index, bool)> = HashMap::new();
+            let mut queue = VecDeque::new();
+            parents.insert(self.root, (self.root, true));
+            queue.push_back(self.root);
+            while let Some(i) = queue.pop_front() {
+                if i == target {
+                    break;
+                }
+                let forwards = graph
+                    .neighbors(i)
+                    .filter(|j| !flow.contains(&(i, *j)))
+                    .map(|j| (j, true));
+                let backwards = graph
+                    .neighbors_directed(i, Direction::Incoming)
+                    .filter(|j| flow.contains(&(*j, i)))
+                    .map(|j| (j, false));
+                for (j, forward) in forwards.chain(backwards) {
+                    if let Entry::Vacant(entry) = parents.entry(j) {
+                        entry.insert((i, forward));
+                        queue.push_back(j);
+                    }
+                }
+            }
+            parents
+        };
+
+        loop {
+            let parents = search(&flow);
+            if !parents.contains_key(&target) {
+                break;
+            }
+
+            let mut j = target;
+            while j != self.root {
+                let (i, forward) = parents[&j];
+                if !forward {
+                    flow.remove(&(j, i));
+                } else if !(i == self.root && graph[j].is_root()) {
+                    // References from the root to its categories aren't real,
+                    // so they can carry any amount of flow and never get cut
+                    flow.insert((i, j));
+                }
+                j = i;
+            }
+        }
+
+        // Of the minimum cuts, the one closest to the target is the most
+        // useful: cut the references into the nodes that can still reach
+        // the target through the residual graph.
+        let mut sink_side: HashSet<Index> = HashSet::new();
+        let mut queue = VecDeque::new();
+        sink_side.insert(target);
+        queue.push_back(target);
+        while let Some(j) = queue.pop_front() {
+            let backwards = graph
+                .neighbors_directed(j, Direction::Incoming)
+                .filter(|i| !flow.contains(&(*i, j)));
+            let forwards = graph.neighbors(j).filter(|i| flow.contains(&(j, *i)));
+            for i in backwards.chain(forwards).collect::<Vec<Index>>() {
+                if sink_side.insert(i) {
+                    queue.push_back(i);
+                }
+            }
+        }
+
+        let mut cut: Vec<(Index, Index)> = sink_side
+            .iter()
+            .flat_map(|j| {
+                graph
+                    .neighbors_directed(*j, Direction::Incoming)
+                    .map(move |i| (i, *j))
+            })
+            .filter(|(i, _)| !sink_side.contains(i))
+            .collect();
+        cut.sort_unstable_by_key(|(i, j)| (graph[*i].address, graph[*j].address));
+        cut.dedup();
+        cut
+    }
+
+    // Length of the shortest reference path from the root to each node of the
+    // dominated subgraph.
+    pub fn reference_depths(&self) -> HashMap<Index, usize> {
+        let graph = &self.dominated_subgraph;
+        let mut depths: HashMap<Index, usize> = HashMap::new();
+        let mut queue = VecDeque::new();
+        depths.insert(self.root, 0);
+        queue.push_back(self.root);
+        while let Some(i) = queue.pop_front() {
+            let depth = depths[&i] + 1;
+            for j in graph.neighbors(i) {
+                if let Entry::Vacant(entry) = depths.entry(j) {
+                    entry.insert(depth);
+                    queue.push_back(j);
+                }
+            }
+        }
+        depths
+    }
+
+    // The object each node of the dominated subgraph is first reached through
+    // from the root, breadth-first, so following them back gives a shortest
+    // reference path to it.
+    pub fn reference_parents(&self) -> HashMap<Index, Index> {
+        let graph = &self.dominated_subgraph;
+        let mut parents: HashMap<Index, Index> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(self.root);
+        while let Some(i) = queue.pop_front() {
+            for j in graph.neighbors(i) {
+                if j != self.root {
+                    if let Entry::Vacant(entry) = parents.entry(j) {
+                        entry.insert(i);
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+        parents
+    }
+
+    // Number of distinct objects referencing each node of the dominated
+    // subgraph, for the nodes referenced at all.
+    pub fn referrer_counts(&self) -> HashMap<Index, usize> {
+        let mut edges: Vec<(Index, Index)> = self
+            .dominated_subgraph
+            .raw_edges()
+            .iter()
+            .map(|e| (e.target(), e.source()))
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut counts: HashMap<Index, usize> = HashMap::new();
+        for (target, _) in edges {
+            *counts.entry(target).or_default() += 1;
+        }
+        counts
+    }
+
+    // Depth of each node of the dominated subgraph in the dominator tree, the
+    // root being at depth 0.
+    pub fn dominator_depths(&self) -> HashMap<Index, usize> {
+        let mut depths: HashMap<Index, usize> = HashMap::new();
+        depths.insert(self.root, 0);
+
+        // Nodes up the dominator tree whose depth isn't known yet
+        let mut chain = Vec::new();
+        for i in self.dominated_subgraph.node_indices() {
+            let mut j = i;
+            while !depths.contains_key(&j) {
+                chain.push(j);
+                j = self.dominators[&j];
+            }
+            let mut depth = depths[&j];
+            while let Some(k) = chain.pop() {
+                depth += 1;
+                depths.insert(k, depth);
+            }
+        }
+        depths
+    }
+
+    // Number of strongly connected components of the dominated subgraph, and
+    // how many of those are cycles of more than one node.
+    pub fn component_counts(&self) -> (usize, usize) {
+        let components = kosaraju_scc(&self.dominated_subgraph);
+        let cycles = components.iter().filter(|c| c.len() > 1).count();
+        (components.len(), cycles)
+    }
+
+    // Memory of everything reachable from a node of the dominated subgraph,
+    // whether the node retains it by itself or shares it with others.
+    pub fn reachable_stats(&self, i: Index) -> Stats {
+        let graph = &self.dominated_subgraph;
+        let mut stats = Stats::default();
+        let mut dfs = Dfs::new(graph, i);
+        while let Some(j) = dfs.next(graph) {
+            stats = stats.add(self.generations.stats(&graph[j]));
+        }
+        stats
+    }
+
+    // Memory retained by a node of the dominated subgraph.
+    pub fn retained_stats(&self, i: Index) -> Stats {
+        self.subtree_sizes[&i]
+    }
+
+    // Calls `f` with the dominator chain of every node other than the root,
+    // ordered from the root down to (and including) the node itself.
+    pub fn for_each_dominator_chain<F: FnMut(&[&Object])>(&self, mut f: F) {
         // Re-usable buffer
-        let mut ancestors: Vec<Index> = Vec::new();
+        let mut chain: Vec<&Object> = Vec::new();
 
         for mut i in self.dominators.keys() {
-            let node = &self.dominated_subgraph[*i];
+            chain.push(&self.dominated_subgraph[*i]);
 
             while let Some(d) = self.dominators.get(i) {
-                ancestors.push(*d);
+                chain.push(&self.dominated_subgraph[*d]);
                 i = d;
             }
 
+            chain.reverse();
+            f(&chain);
+            chain.clear();
+        }
+    }
+
+    // Produces valid input for inferno::flamegraph::from_lines
+    //
+    // The basic idea is that we treat every reachable byte as a sample.
+    pub fn flamegraph_lines(&self) -> Vec<String> {
+        let mut lines = Vec::with_capacity(self.dominated_subgraph.node_count());
+
+        self.for_each_dominator_chain(|chain| {
             let mut line = String::new();
-            for d in ancestors.iter().rev() {
-                write!(
-                    line,
-                    "{}",
-                    self.dominated_subgraph[*d].format(self.class_name_only)
-                )
-                .unwrap();
-                line.push(';');
+            for (n, obj) in chain.iter().enumerate() {
+                if n > 0 {
+                    line.push(';');
+                }
+                write!(line, "{}", obj.format(self.class_name_only)).unwrap();
             }
-            ancestors.clear();
 
-            write!(line, "{}", node.format(self.class_name_only)).unwrap();
             line.push(' ');
-            write!(line, "{}", node.bytes).unwrap();
+            write!(line, "{}", chain[chain.len() - 1].bytes).unwrap();
 
             lines.push(line);
-        }
+        });
 
         lines
     }
@@ -0,0 +1,64 @@
+use crate::analyze::Analysis;
+use crate::parse::parse_address;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+struct Line {
+    address: Option<String>,
+}
+
+// Re-emits each line of the original dump, injecting "retained_memsize" and
+// "dominator" fields into reachable objects. The dominator is null for
+// objects dominated directly by the root. Other lines are copied unchanged,
+// and fields are appended in place rather than re-serialized, so existing
+// jq filters see the dump exactly as before.
+pub fn write_annotated(
+    analysis: &Analysis,
+    mut reader: impl BufRead,
+    output: &Path,
+) -> io::Result<usize> {
+    let graph = analysis.dominated_subgraph();
+    let indices: HashMap<usize, _> = graph
+        .node_indices()
+        .map(|i| (graph[i].address, i))
+        .collect();
+
+    let mut out = io::BufWriter::new(File::create(output)?);
+    let mut annotated = 0;
+    let mut line = String::new();
+
+    while reader.read_line(&mut line)? > 0 {
+        let trimmed = line.trim_end();
+        let index = serde_json::from_str::<Line>(trimmed)?
+            .address
+            .and_then(|a| parse_address(a.as_str()).ok())
+            .and_then(|a| indices.get(&a));
+
+        match (index, trimmed.rfind('}')) {
+            (Some(&i), Some(end)) => {
+                let dominator = match analysis.dominator(i).map(|d| &graph[d]) {
+                    Some(d) if !d.is_root() => format!("\"{:#x}\"", d.address),
+                    _ => "null".to_string(),
+                };
+                writeln!(
+                    out,
+                    "{}, \"retained_memsize\":{}, \"dominator\":{}}}",
+                    &trimmed[..end],
+                    analysis.retained_stats(i).bytes,
+                    dominator
+                )?;
+                annotated += 1;
+            }
+            _ => writeln!(out, "{}", trimmed)?,
+        }
+
+        line.clear();
+    }
+
+    out.flush()?;
+    Ok(annotated)
+}
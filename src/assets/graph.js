@@ -0,0 +1,307 @@
+// Renders the dominator tree embedded in the page as an interactive,
+// force-directed graph. Node areas are proportional to retained memory; click
+// a node for its details, and double-click it to expand or collapse the
+// objects it dominates. Drag the background to pan, and scroll to zoom.
+(function () {
+  "use strict";
+
+  var SVG_NS = "http://www.w3.org/2000/svg";
+  var OPEN_DEPTH = 2;
+  var MAX_RADIUS = 40;
+  var MIN_RADIUS = 3;
+  var REPULSION = 2000;
+  var SPRING_LENGTH = 60;
+  var SPRING = 0.05;
+  var GRAVITY = 0.01;
+  var DAMPING = 0.85;
+
+  var tree = JSON.parse(document.getElementById("tree").textContent);
+  var svg = document.getElementById("graph");
+  var details = document.getElementById("details");
+  var viewport = document.createElementNS(SVG_NS, "g");
+  svg.appendChild(viewport);
+
+  var view = { x: 0, y: 0, scale: 1 };
+  var visible = [];
+  var links = [];
+  var selected = null;
+  var alpha = 1;
+
+  function formatBytes(bytes) {
+    var units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    var i = 0;
+    while (bytes >= 1024 && i < units.length - 1) {
+      bytes /= 1024;
+      i++;
+    }
+    return (i === 0 ? bytes : bytes.toFixed(1)) + " " + units[i];
+  }
+
+  function color(kind) {
+    var hash = 0;
+    for (var i = 0; i < kind.length; i++) {
+      hash = (hash * 31 + kind.charCodeAt(i)) | 0;
+    }
+    return "hsl(" + (Math.abs(hash) % 360) + ", 55%, 60%)";
+  }
+
+  function radius(node) {
+    var share = node.retained_bytes / Math.max(tree.retained_bytes, 1);
+    return Math.max(MIN_RADIUS, Math.sqrt(share) * MAX_RADIUS);
+  }
+
+  // Annotate the tree with layout state, expanding the top few levels
+  (function init() {
+    var stack = [{ node: tree, parent: null, depth: 0 }];
+    while (stack.length > 0) {
+      var entry = stack.pop();
+      var node = entry.node;
+      node.parent = entry.parent;
+      node.expanded = entry.depth < OPEN_DEPTH;
+      node.x = (Math.random() - 0.5) * 100;
+      node.y = (Math.random() - 0.5) * 100;
+      node.vx = 0;
+      node.vy = 0;
+      node.children.forEach(function (child) {
+        stack.push({ node: child, parent: node, depth: entry.depth + 1 });
+      });
+    }
+  })();
+
+  function collectVisible() {
+    visible = [];
+    links = [];
+    var stack = [tree];
+    while (stack.length > 0) {
+      var node = stack.pop();
+      visible.push(node);
+      if (node.expanded) {
+        node.children.forEach(function (child) {
+          links.push({ source: node, target: child });
+          stack.push(child);
+        });
+      }
+    }
+  }
+
+  function showDetails(node) {
+    details.innerHTML = "";
+    if (node === null) {
+      return;
+    }
+
+    var title = document.createElement("h3");
+    title.textContent = node.label;
+    details.appendChild(title);
+
+    var rows = [
+      ["Address", node.address],
+      ["Kind", node.kind],
+      ["Self", formatBytes(node.self_bytes)],
+      ["Retained", formatBytes(node.retained_bytes)],
+      ["Objects retained", node.count],
+      ["Dominated (shown)", node.children.length]
+    ];
+    var table = document.createElement("table");
+    rows.forEach(function (row) {
+      var tr = document.createElement("tr");
+      row.forEach(function (cell) {
+        var td = document.createElement("td");
+        td.textContent = cell;
+        tr.appendChild(td);
+      });
+      table.appendChild(tr);
+    });
+    details.appendChild(table);
+
+    if (node.children.length > 0) {
+      var button = document.createElement("button");
+      button.textContent = node.expanded ? "Collapse" : "Expand";
+      button.addEventListener("click", function () {
+        toggle(node);
+      });
+      details.appendChild(button);
+    }
+  }
+
+  function toggle(node) {
+    node.expanded = !node.expanded;
+    node.children.forEach(function (child) {
+      child.x = node.x + (Math.random() - 0.5) * 20;
+      child.y = node.y + (Math.random() - 0.5) * 20;
+    });
+    collectVisible();
+    alpha = 1;
+    showDetails(selected);
+    draw();
+  }
+
+  // One step of a simple force simulation: all nodes repel each other,
+  // dominator edges act as springs, and gravity keeps the graph centered
+  function tick() {
+    for (var i = 0; i < visible.length; i++) {
+      var a = visible[i];
+      for (var j = i + 1; j < visible.length; j++) {
+        var b = visible[j];
+        var dx = b.x - a.x;
+        var dy = b.y - a.y;
+        var d2 = Math.max(dx * dx + dy * dy, 1);
+        var force = (REPULSION * alpha) / d2;
+        var d = Math.sqrt(d2);
+        a.vx -= (dx / d) * force;
+        a.vy -= (dy / d) * force;
+        b.vx += (dx / d) * force;
+        b.vy += (dy / d) * force;
+      }
+    }
+
+    links.forEach(function (link) {
+      var dx = link.target.x - link.source.x;
+      var dy = link.target.y - link.source.y;
+      var d = Math.max(Math.sqrt(dx * dx + dy * dy), 1);
+      var length = SPRING_LENGTH + radius(link.source) + radius(link.target);
+      var force = (d - length) * SPRING * alpha;
+      link.source.vx += (dx / d) * force;
+      link.source.vy += (dy / d) * force;
+      link.target.vx -= (dx / d) * force;
+      link.target.vy -= (dy / d) * force;
+    });
+
+    visible.forEach(function (node) {
+      node.vx -= node.x * GRAVITY * alpha;
+      node.vy -= node.y * GRAVITY * alpha;
+      if (node !== dragging) {
+        node.x += node.vx;
+        node.y += node.vy;
+      }
+      node.vx *= DAMPING;
+      node.vy *= DAMPING;
+    });
+
+    alpha *= 0.99;
+  }
+
+  function element(name, attrs) {
+    var el = document.createElementNS(SVG_NS, name);
+    Object.keys(attrs).forEach(function (key) {
+      el.setAttribute(key, attrs[key]);
+    });
+    return el;
+  }
+
+  function draw() {
+    viewport.innerHTML = "";
+    viewport.setAttribute(
+      "transform",
+      "translate(" + view.x + "," + view.y + ") scale(" + view.scale + ")"
+    );
+
+    links.forEach(function (link) {
+      viewport.appendChild(element("line", {
+        x1: link.source.x,
+        y1: link.source.y,
+        x2: link.target.x,
+        y2: link.target.y,
+        class: "link"
+      }));
+    });
+
+    visible.forEach(function (node) {
+      var circle = element("circle", {
+        cx: node.x,
+        cy: node.y,
+        r: radius(node),
+        fill: color(node.kind),
+        class: "node" + (node === selected ? " selected" : "") +
+          (!node.expanded && node.children.length > 0 ? " collapsed" : "")
+      });
+      var title = element("title", {});
+      title.textContent = node.label + "\n" + formatBytes(node.retained_bytes) + " retained";
+      circle.appendChild(title);
+      circle.addEventListener("mousedown", function (event) {
+        event.stopPropagation();
+        dragging = node;
+        moved = false;
+      });
+      circle.addEventListener("click", function (event) {
+        event.stopPropagation();
+        if (!moved) {
+          selected = node;
+          showDetails(node);
+          draw();
+        }
+      });
+      circle.addEventListener("dblclick", function (event) {
+        event.stopPropagation();
+        if (node.children.length > 0) {
+          toggle(node);
+        }
+      });
+      viewport.appendChild(circle);
+    });
+  }
+
+  // Panning, zooming & dragging nodes
+  var dragging = null;
+  var panning = null;
+  var moved = false;
+
+  function toGraph(event) {
+    var rect = svg.getBoundingClientRect();
+    return {
+      x: (event.clientX - rect.left - view.x) / view.scale,
+      y: (event.clientY - rect.top - view.y) / view.scale
+    };
+  }
+
+  svg.addEventListener("mousedown", function (event) {
+    panning = { x: event.clientX - view.x, y: event.clientY - view.y };
+  });
+
+  window.addEventListener("mousemove", function (event) {
+    if (dragging !== null) {
+      var p = toGraph(event);
+      dragging.x = p.x;
+      dragging.y = p.y;
+      moved = true;
+      alpha = Math.max(alpha, 0.3);
+      draw();
+    } else if (panning !== null) {
+      view.x = event.clientX - panning.x;
+      view.y = event.clientY - panning.y;
+      draw();
+    }
+  });
+
+  window.addEventListener("mouseup", function () {
+    dragging = null;
+    panning = null;
+  });
+
+  svg.addEventListener("wheel", function (event) {
+    event.preventDefault();
+    var rect = svg.getBoundingClientRect();
+    var mx = event.clientX - rect.left;
+    var my = event.clientY - rect.top;
+    var factor = event.deltaY < 0 ? 1.1 : 1 / 1.1;
+    view.x = mx - (mx - view.x) * factor;
+    view.y = my - (my - view.y) * factor;
+    view.scale *= factor;
+    draw();
+  }, { passive: false });
+
+  function animate() {
+    if (alpha > 0.01) {
+      tick();
+      draw();
+    }
+    window.requestAnimationFrame(animate);
+  }
+
+  view.x = svg.clientWidth / 2;
+  view.y = svg.clientHeight / 2;
+  collectVisible();
+  selected = tree;
+  showDetails(tree);
+  animate();
+})();
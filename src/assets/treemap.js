@@ -0,0 +1,179 @@
+// Renders the dominator tree embedded in the page as a zoomable, squarified
+// treemap: each rectangle's area is the memory its object retains, and it is
+// nested inside the rectangle of its immediate dominator.
+(function () {
+  "use strict";
+
+  var MAX_DEPTH = 4;
+  var HEADER = 16;
+  var MIN_SIZE = 4;
+
+  var tree = JSON.parse(document.getElementById("tree").textContent);
+  var container = document.getElementById("treemap");
+  var crumbs = document.getElementById("crumbs");
+  var path = [tree];
+
+  function formatBytes(bytes) {
+    var units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    var i = 0;
+    while (bytes >= 1024 && i < units.length - 1) {
+      bytes /= 1024;
+      i++;
+    }
+    return (i === 0 ? bytes : bytes.toFixed(1)) + " " + units[i];
+  }
+
+  function describe(node) {
+    return node.label + "\n" + formatBytes(node.retained_bytes) + " retained, " +
+      formatBytes(node.self_bytes) + " self, " + node.count + " objects";
+  }
+
+  function color(kind) {
+    var hash = 0;
+    for (var i = 0; i < kind.length; i++) {
+      hash = (hash * 31 + kind.charCodeAt(i)) | 0;
+    }
+    return "hsl(" + (Math.abs(hash) % 360) + ", 55%, 75%)";
+  }
+
+  // Worst aspect ratio of a row of items laid out along a side of length `side`
+  function worst(row, sum, side) {
+    var max = 0;
+    var min = Infinity;
+    row.forEach(function (item) {
+      max = Math.max(max, item.area);
+      min = Math.min(min, item.area);
+    });
+    return Math.max((side * side * max) / (sum * sum), (sum * sum) / (side * side * min));
+  }
+
+  // Squarified layout (Bruls, Huizing & van Wijk) of items sorted by value
+  function squarify(items, total, x, y, w, h) {
+    var rects = [];
+    if (total <= 0 || w <= 0 || h <= 0) {
+      return rects;
+    }
+
+    var scale = (w * h) / total;
+    var queue = items.map(function (item) {
+      return { item: item, area: item.value * scale };
+    }).filter(function (entry) {
+      return entry.area > 0;
+    });
+
+    var row = [];
+    var rowSum = 0;
+
+    function layoutRow() {
+      var offset;
+      if (w >= h) {
+        var width = rowSum / h;
+        offset = y;
+        row.forEach(function (entry) {
+          var height = entry.area / width;
+          rects.push({ item: entry.item, x: x, y: offset, w: width, h: height });
+          offset += height;
+        });
+        x += width;
+        w -= width;
+      } else {
+        var height = rowSum / w;
+        offset = x;
+        row.forEach(function (entry) {
+          var width = entry.area / height;
+          rects.push({ item: entry.item, x: offset, y: y, w: width, h: height });
+          offset += width;
+        });
+        y += height;
+        h -= height;
+      }
+      row = [];
+      rowSum = 0;
+    }
+
+    while (queue.length > 0) {
+      var side = Math.min(w, h);
+      var next = queue[0];
+      if (row.length === 0 ||
+          worst(row.concat([next]), rowSum + next.area, side) <= worst(row, rowSum, side)) {
+        row.push(next);
+        rowSum += next.area;
+        queue.shift();
+      } else {
+        layoutRow();
+      }
+    }
+    if (row.length > 0) {
+      layoutRow();
+    }
+
+    return rects;
+  }
+
+  function render(node, parent, x, y, w, h, depth) {
+    var div = document.createElement("div");
+    div.className = "node";
+    div.style.left = x + "px";
+    div.style.top = y + "px";
+    div.style.width = Math.max(w - 1, 0) + "px";
+    div.style.height = Math.max(h - 1, 0) + "px";
+    div.style.background = color(node.kind);
+    div.title = describe(node);
+    div.addEventListener("click", function (event) {
+      event.stopPropagation();
+      if (node.children.length > 0) {
+        path.push(node);
+        draw();
+      }
+    });
+
+    if (w > 30 && h > HEADER) {
+      var label = document.createElement("span");
+      label.textContent = node.label + " (" + formatBytes(node.retained_bytes) + ")";
+      div.appendChild(label);
+    }
+    parent.appendChild(div);
+
+    if (depth >= MAX_DEPTH || node.children.length === 0 || h <= HEADER + MIN_SIZE) {
+      return;
+    }
+
+    // Memory retained directly, or by descendants below the threshold, is
+    // left as empty space in the parent
+    var items = node.children.map(function (child) {
+      return { node: child, value: child.retained_bytes };
+    });
+    squarify(items, node.retained_bytes, 1, HEADER, w - 3, h - HEADER - 2)
+      .forEach(function (rect) {
+        if (rect.w >= MIN_SIZE && rect.h >= MIN_SIZE) {
+          render(rect.item.node, div, rect.x, rect.y, rect.w, rect.h, depth + 1);
+        }
+      });
+  }
+
+  function draw() {
+    container.innerHTML = "";
+    crumbs.innerHTML = "";
+
+    path.forEach(function (node, i) {
+      var crumb = document.createElement("a");
+      crumb.textContent = node.label;
+      crumb.href = "#";
+      crumb.addEventListener("click", function (event) {
+        event.preventDefault();
+        path = path.slice(0, i + 1);
+        draw();
+      });
+      if (i > 0) {
+        crumbs.appendChild(document.createTextNode(" › "));
+      }
+      crumbs.appendChild(crumb);
+    });
+
+    var current = path[path.length - 1];
+    render(current, container, 0, 0, container.clientWidth, container.clientHeight, 0);
+  }
+
+  window.addEventListener("resize", draw);
+  draw();
+})();
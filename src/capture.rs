@@ -0,0 +1,81 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// How to have a process dump its heap
+pub struct Options {
+    pub rbtrace: PathBuf,
+    // Where the process writes its dumps, which reap has to be able to read
+    pub dir: PathBuf,
+    pub timeout: Duration,
+    // How many dumps to take, and how long from the start of one to the next
+    pub count: usize,
+    pub interval: Duration,
+}
+
+// Where a process is told to dump its heap to, e.g.
+// /tmp/heap-1234-1700000000000.json, named after the time in milliseconds so
+// several dumps sort in the order they were taken
+fn dump_path(dir: &Path, pid: u32) -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0);
+    dir.join(format!("heap-{}-{}.json", pid, now))
+}
+
+// Ruby dumping the heap to a file from a thread of its own, so it doesn't
+// hold up rbtrace. The dump is written under another name and renamed once
+// complete, so it isn't read half-written.
+pub fn ruby(path: &Path) -> String {
+    let quote = |path: &str| format!("'{}'", path.replace('\\', "\\\\").replace('\'', "\\'"));
+    let path = path.display().to_string();
+    let partial = quote(&format!("{}.partial", path));
+    format!(
+        "Thread.new{{require 'objspace';f=open({},'w');ObjectSpace.dump_all(output: f, full: true);f.close;File.rename({},{})}}",
+        partial,
+        partial,
+        quote(&path)
+    )
+}
+
+// Has a process with rbtrace required dump its heap, and waits for it to
+// finish, returning the dump's path
+pub fn capture(pid: u32, options: &Options) -> io::Result<PathBuf> {
+    let path = dump_path(&options.dir, pid);
+    let status = process::Command::new(&options.rbtrace)
+        .arg("-p")
+        .arg(pid.to_string())
+        .arg("-e")
+        .arg(ruby(&path))
+        .stdout(process::Stdio::null())
+        .status()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::other(format!(
+                "{} was not found; install it with `gem install rbtrace`",
+                options.rbtrace.display()
+            )),
+            _ => e,
+        })?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "rbtrace exited with {}; is rbtrace required in process {}?",
+            status, pid
+        )));
+    }
+
+    let start = Instant::now();
+    while !path.exists() {
+        if start.elapsed() > options.timeout {
+            return Err(io::Error::other(format!(
+                "Timed out waiting for process {} to write {}",
+                pid,
+                path.display()
+            )));
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+    Ok(path)
+}
@@ -0,0 +1,154 @@
+use crate::analyze::Analysis;
+use parquet::basic::Compression;
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::errors::Result;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+const OBJECTS_SCHEMA: &str = "
+message object {
+    REQUIRED INT64 address;
+    REQUIRED BYTE_ARRAY kind (UTF8);
+    REQUIRED BYTE_ARRAY label (UTF8);
+    REQUIRED INT64 self_bytes;
+    REQUIRED INT64 retained_bytes;
+    REQUIRED INT64 retained_count;
+    OPTIONAL INT64 dominator;
+}
+";
+
+const REFERENCES_SCHEMA: &str = "
+message reference {
+    REQUIRED INT64 source;
+    REQUIRED INT64 target;
+    REQUIRED INT64 references;
+}
+";
+
+// Bounds memory use for heaps with tens of millions of objects.
+const ROW_GROUP_SIZE: usize = 1 << 20;
+
+// Values of one column of a row group, in schema order.
+enum Column {
+    Int64(Vec<i64>),
+    OptionalInt64(Vec<Option<i64>>),
+    Utf8(Vec<String>),
+}
+
+struct TableWriter {
+    writer: SerializedFileWriter<File>,
+}
+
+impl TableWriter {
+    fn new(schema: &str, filename: &Path) -> Result<TableWriter> {
+        let schema = Arc::new(parse_message_type(schema)?);
+        let props = Arc::new(
+            WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .build(),
+        );
+        let file = File::create(filename)?;
+        Ok(TableWriter {
+            writer: SerializedFileWriter::new(file, schema, props)?,
+        })
+    }
+
+    fn row_group(&mut self, columns: Vec<Column>) -> Result<()> {
+        let mut row_group = self.writer.next_row_group()?;
+        for column in columns {
+            let mut writer = row_group.next_column()?.unwrap();
+            match column {
+                Column::Int64(values) => {
+                    writer
+                        .typed::<Int64Type>()
+                        .write_batch(&values, None, None)?;
+                }
+                Column::OptionalInt64(values) => {
+                    let levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+                    let present: Vec<i64> = values.into_iter().flatten().collect();
+                    writer
+                        .typed::<Int64Type>()
+                        .write_batch(&present, Some(&levels), None)?;
+                }
+                Column::Utf8(values) => {
+                    let values: Vec<ByteArray> = values
+                        .into_iter()
+                        .map(|v| ByteArray::from(v.into_bytes()))
+                        .collect();
+                    writer
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, None, None)?;
+                }
+            }
+            writer.close()?;
+        }
+        row_group.close()?;
+        Ok(())
+    }
+
+    fn close(self) -> Result<()> {
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+// Writes objects.parquet, with one row per object (including the address of
+// its immediate dominator), and references.parquet, with one row per edge of
+// the reference graph.
+pub fn write_parquet(
+    analysis: &Analysis,
+    threshold_bytes: usize,
+    dir: &Path,
+) -> Result<(usize, usize)> {
+    fs::create_dir_all(dir)?;
+    let graph = analysis.dominated_subgraph();
+    let (indices, edges) = analysis.exported(threshold_bytes);
+
+    let mut objects = TableWriter::new(OBJECTS_SCHEMA, &dir.join("objects.parquet"))?;
+    for chunk in indices.chunks(ROW_GROUP_SIZE) {
+        let objs = || chunk.iter().map(|&i| &graph[i]);
+        let retained = || chunk.iter().map(|&i| analysis.retained_stats(i));
+        objects.row_group(vec![
+            Column::Int64(objs().map(|o| o.address as i64).collect()),
+            Column::Utf8(objs().map(|o| o.kind.clone()).collect()),
+            Column::Utf8(objs().map(|o| o.to_string()).collect()),
+            Column::Int64(objs().map(|o| o.bytes as i64).collect()),
+            Column::Int64(retained().map(|s| s.bytes as i64).collect()),
+            Column::Int64(retained().map(|s| s.count as i64).collect()),
+            Column::OptionalInt64(
+                chunk
+                    .iter()
+                    .map(|&i| analysis.dominator(i).map(|d| graph[d].address as i64))
+                    .collect(),
+            ),
+        ])?;
+    }
+    objects.close()?;
+
+    let mut references = TableWriter::new(REFERENCES_SCHEMA, &dir.join("references.parquet"))?;
+    for chunk in edges.chunks(ROW_GROUP_SIZE) {
+        references.row_group(vec![
+            Column::Int64(
+                chunk
+                    .iter()
+                    .map(|e| graph[e.source()].address as i64)
+                    .collect(),
+            ),
+            Column::Int64(
+                chunk
+                    .iter()
+                    .map(|e| graph[e.target()].address as i64)
+                    .collect(),
+            ),
+            Column::Int64(chunk.iter().map(|e| e.weight.0 as i64).collect()),
+        ])?;
+    }
+    references.close()?;
+
+    Ok((indices.len(), edges.len()))
+}
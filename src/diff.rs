@@ -0,0 +1,225 @@
+use crate::analyze::{Analysis, GroupBy, KindFilter, Sort};
+use crate::object::*;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+// Signed difference between two sets of stats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(into = "DeltaFields")]
+pub struct Delta {
+    pub count: i64,
+    pub bytes: i64,
+    pub slot_bytes: i64,
+}
+
+// A delta as written out, with the malloc'd bytes next to the slot bytes
+#[derive(Serialize)]
+struct DeltaFields {
+    count: i64,
+    bytes: i64,
+    slot_bytes: i64,
+    malloc_bytes: i64,
+}
+
+impl From<Delta> for DeltaFields {
+    fn from(delta: Delta) -> DeltaFields {
+        DeltaFields {
+            count: delta.count,
+            bytes: delta.bytes,
+            slot_bytes: delta.slot_bytes,
+            malloc_bytes: delta.malloc_bytes(),
+        }
+    }
+}
+
+impl Delta {
+    pub fn between(before: Stats, after: Stats) -> Delta {
+        Delta {
+            count: after.count as i64 - before.count as i64,
+            bytes: after.bytes as i64 - before.bytes as i64,
+            slot_bytes: after.slot_bytes as i64 - before.slot_bytes as i64,
+        }
+    }
+
+    pub fn add(&mut self, other: Delta) -> Delta {
+        Delta {
+            count: self.count + other.count,
+            bytes: self.bytes + other.bytes,
+            slot_bytes: self.slot_bytes + other.slot_bytes,
+        }
+    }
+
+    pub fn malloc_bytes(&self) -> i64 {
+        self.bytes - self.slot_bytes
+    }
+}
+
+// Per-kind stats extracted from an analysis, so that the (much larger) graph
+// can be dropped before the next dump is parsed.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub totals: Stats,
+    pub live: HashMap<String, Stats>,
+    pub retained: HashMap<String, Stats>,
+}
+
+impl Summary {
+    pub fn new(analysis: &Analysis) -> Summary {
+        let to_map = |(stats, _): (Vec<(Cow<str>, Stats)>, Stats)| {
+            stats
+                .into_iter()
+                .map(|(k, s)| (k.into_owned(), s))
+                .collect::<HashMap<String, Stats>>()
+        };
+
+        let all = KindFilter::default();
+        Summary {
+            totals: analysis.dominated_totals(),
+            live: to_map(analysis.live_stats_by_kind(usize::MAX, Sort::Bytes, &all, GroupBy::Kind)),
+            retained: to_map(analysis.retained_stats_by_kind(
+                usize::MAX,
+                Sort::Bytes,
+                &all,
+                GroupBy::Kind,
+            )),
+        }
+    }
+
+    // Like `new`, but keyed by kind and allocation site rather than kind alone,
+    // since addresses are recycled between dumps but allocation sites are not.
+    pub fn by_location(analysis: &Analysis) -> Summary {
+        Summary {
+            totals: analysis.dominated_totals(),
+            live: analysis.live_stats_by_location(),
+            retained: analysis.retained_stats_by_location(),
+        }
+    }
+}
+
+// An object type (or "total", for the whole heap) whose retained memory grew
+// by more than the allowed margin.
+#[derive(Debug)]
+pub struct Regression {
+    pub kind: String,
+    pub before: Stats,
+    pub after: Stats,
+}
+
+impl Regression {
+    pub fn growth(&self) -> Delta {
+        Delta::between(self.before, self.after)
+    }
+}
+
+#[derive(Debug)]
+pub struct Diff {
+    pub totals: (Stats, Stats),
+    live: HashMap<String, Delta>,
+    retained: HashMap<String, Delta>,
+}
+
+pub fn diff(before: &Summary, after: &Summary) -> Diff {
+    Diff {
+        totals: (before.totals, after.totals),
+        live: deltas_by_kind(&before.live, &after.live),
+        retained: deltas_by_kind(&before.retained, &after.retained),
+    }
+}
+
+// Finds regressions in retained memory relative to a baseline, where
+// `max_growth` is a fraction (0.1 allows 10% growth), and types growing by
+// fewer than `min_bytes` are ignored. Types absent from the baseline exceed
+// any `max_growth`, so only `min_bytes` filters them.
+pub fn check(
+    baseline: &Summary,
+    current: &Summary,
+    max_growth: f64,
+    min_bytes: usize,
+) -> Vec<Regression> {
+    let exceeds = |before: Stats, after: Stats| {
+        after.bytes >= before.bytes + min_bytes
+            && after.bytes as f64 > before.bytes as f64 * (1.0 + max_growth)
+    };
+
+    let mut regressions = Vec::new();
+
+    if exceeds(baseline.totals, current.totals) {
+        regressions.push(Regression {
+            kind: "total".to_string(),
+            before: baseline.totals,
+            after: current.totals,
+        });
+    }
+
+    // The root's retained memory is the total, already checked
+    for (kind, &after) in current
+        .retained
+        .iter()
+        .filter(|(kind, _)| !is_root_key(kind))
+    {
+        let before = baseline.retained.get(kind).cloned().unwrap_or_default();
+        if exceeds(before, after) {
+            regressions.push(Regression {
+                kind: kind.clone(),
+                before,
+                after,
+            });
+        }
+    }
+
+    regressions.sort_by_key(|r| -r.growth().bytes);
+    regressions
+}
+
+fn deltas_by_kind(
+    before: &HashMap<String, Stats>,
+    after: &HashMap<String, Stats>,
+) -> HashMap<String, Delta> {
+    let kinds: HashSet<&String> = before.keys().chain(after.keys()).collect();
+    kinds
+        .into_iter()
+        .map(|k| {
+            let b = before.get(k).cloned().unwrap_or_default();
+            let a = after.get(k).cloned().unwrap_or_default();
+            (k.clone(), Delta::between(b, a))
+        })
+        .collect()
+}
+
+// Kinds that grew, largest growth first. The root is left out, as its growth
+// is the total's.
+fn largest_growth_and_rest(
+    deltas: &HashMap<String, Delta>,
+    count: usize,
+) -> (Vec<(&String, Delta)>, Delta) {
+    let mut sorted: Vec<(&String, Delta)> = deltas
+        .iter()
+        .filter(|(k, d)| !is_root_key(k) && d.bytes > 0)
+        .map(|(k, d)| (k, *d))
+        .collect();
+    sorted.sort_unstable_by_key(|(_, d)| -d.bytes);
+
+    if count >= sorted.len() {
+        (sorted, Delta::default())
+    } else {
+        let rest = sorted[count..]
+            .iter()
+            .fold(Delta::default(), |mut acc, (_, d)| acc.add(*d));
+        sorted.truncate(count);
+        (sorted, rest)
+    }
+}
+
+impl Diff {
+    pub fn live_growth_by_kind(&self, top_n: usize) -> (Vec<(&String, Delta)>, Delta) {
+        largest_growth_and_rest(&self.live, top_n)
+    }
+
+    pub fn retained_growth_by_kind(&self, top_n: usize) -> (Vec<(&String, Delta)>, Delta) {
+        largest_growth_and_rest(&self.retained, top_n)
+    }
+
+    pub fn total_growth(&self) -> Delta {
+        Delta::between(self.totals.0, self.totals.1)
+    }
+}
@@ -0,0 +1,238 @@
+use crate::analyze::Index;
+use crate::html::escape_html;
+use crate::object::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io;
+use std::io::prelude::*;
+
+// Fill colors for Ruby's built-in kinds, matched case-insensitively since
+// the dump names some by class (e.g. "String") and some by type (e.g.
+// "ARRAY").
+const KIND_COLORS: &[(&str, &str)] = &[
+    ("ROOT", "#ffffff"),
+    ("String", "#aec7e8"),
+    ("Symbol", "#c5b0d5"),
+    ("Hash", "#ffbb78"),
+    ("Array", "#98df8a"),
+    ("Class", "#ff9896"),
+    ("Module", "#f7b6d2"),
+    ("ICLASS", "#f7b6d2"),
+    ("IMEMO", "#d9d9d9"),
+    ("Regexp", "#dbdb8d"),
+    ("Thread", "#9edae5"),
+    ("DATA", "#c7c7c7"),
+    // Objects folded together by --max-nodes
+    ("other", "#eeeeee"),
+];
+
+// Kinds not listed above, typically application classes, get a pale color
+// picked by a hash of their name, the same way as in the HTML graph page, so
+// each keeps its color from one dump to the next.
+pub fn kind_color(kind: &str) -> String {
+    if let Some((_, color)) = KIND_COLORS
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(kind))
+    {
+        return color.to_string();
+    }
+
+    let hash = kind
+        .chars()
+        .fold(0i32, |hash, c| hash.wrapping_mul(31).wrapping_add(c as i32));
+    format!(
+        "{:.3} 0.350 0.950",
+        (hash.unsigned_abs() % 360) as f64 / 360.0
+    )
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options<'a> {
+    // Draw objects of namespaced classes in a box per top-level module, e.g.
+    // "ActiveRecord" for "ActiveRecord::Relation"
+    pub cluster_namespaces: bool,
+    // Full values of strings, for tooltips
+    pub values: Option<&'a HashMap<usize, String>>,
+    // Link for each object, with "{address}" replaced by its address
+    pub url: Option<&'a str>,
+    // Explain the graph in a box beside it, including the relevance threshold
+    // it was drawn with
+    pub legend: Option<f64>,
+    // Point arrows from objects to their retainers, keeping the layout
+    pub invert_edges: bool,
+    // Add each object's allocation site, when traced, to its label
+    pub locations: bool,
+}
+
+// What the parts of each label mean, in the order they appear
+const LABEL_FIELDS: &[&str] = &[
+    "Object[address]: its class and address",
+    "self: memory used by the object itself",
+    "refs: memory it retains beyond that",
+    "objects: number of objects it retains, itself included",
+    "%: share of all reachable memory it retains",
+];
+
+// Top-level module of a namespaced class, e.g. "Gem" for "Gem::Specification"
+fn namespace(kind: &str) -> Option<&str> {
+    kind.find("::").map(|end| &kind[..end])
+}
+
+// Shown on hover in SVG renderings: the label, plus what doesn't fit in it
+fn tooltip(obj: &Object, options: Options) -> String {
+    let mut lines = vec![obj.to_string()];
+    if obj.address != 0 && !obj.is_root() {
+        lines.push(format!(
+            "{} at {:#x}, {} B",
+            obj.kind, obj.address, obj.bytes
+        ));
+    }
+    if let Some(ref location) = obj.location {
+        lines.push(format!("allocated at {}", location));
+    }
+    if let Some(value) = options.values.and_then(|v| v.get(&obj.address)) {
+        lines.push(format!("value: {}", value));
+    }
+    lines.join("\n")
+}
+
+// A box with a key to the labels, arrows and colors in the graph, as an HTML
+// label since that's the only way to lay out a table in dot.
+fn write_legend<W: Write>(
+    graph: &ReferenceGraph,
+    threshold: f64,
+    invert_edges: bool,
+    out: &mut W,
+) -> io::Result<()> {
+    let kinds: BTreeSet<&str> = graph
+        .raw_nodes()
+        .iter()
+        .map(|n| n.weight.kind.as_str())
+        .collect();
+
+    writeln!(out, "    subgraph cluster_legend {{")?;
+    writeln!(out, "        label=\"Legend\";")?;
+    writeln!(
+        out,
+        "        legend [shape=plaintext, style=\"\", label=<<table border=\"0\" cellborder=\"1\" cellspacing=\"0\">"
+    )?;
+    for field in LABEL_FIELDS {
+        writeln!(
+            out,
+            "            <tr><td align=\"left\">{}</td></tr>",
+            escape_html(field)
+        )?;
+    }
+    writeln!(
+        out,
+        "            <tr><td align=\"left\">arrows: {}</td></tr>",
+        if invert_edges {
+            "from each object to the one that dominates it"
+        } else {
+            "from each object to those it dominates"
+        }
+    )?;
+    writeln!(
+        out,
+        "            <tr><td align=\"left\">drawn: objects retaining at least {}% of memory</td></tr>",
+        // Rounded to avoid printing e.g. 7.000000000000001
+        (threshold * 100_000.0).round() / 1000.0
+    )?;
+    for kind in kinds {
+        writeln!(
+            out,
+            "            <tr><td bgcolor=\"{}\">{}</td></tr>",
+            kind_color(kind),
+            escape_html(kind)
+        )?;
+    }
+    writeln!(out, "        </table>>]")?;
+    writeln!(out, "    }}")
+}
+
+fn write_node<W: Write>(
+    graph: &ReferenceGraph,
+    i: Index,
+    options: Options,
+    indent: &str,
+    out: &mut W,
+) -> io::Result<()> {
+    let obj = &graph[i];
+    let label = match obj.location {
+        Some(ref location) if options.locations => format!("{}\n{}", obj, location),
+        _ => obj.to_string(),
+    };
+    write!(
+        out,
+        "{}{} [label=\"{}\", fillcolor=\"{}\", tooltip=\"{}\"",
+        indent,
+        i.index(),
+        escape_dot(&label),
+        kind_color(&obj.kind),
+        escape_dot(&tooltip(obj, options))
+    )?;
+    match options.url {
+        Some(url) if obj.address != 0 && !obj.is_root() => {
+            let url = url.replace("{address}", &format!("{:#x}", obj.address));
+            writeln!(out, ", URL=\"{}\"]", escape_dot(&url))
+        }
+        _ => writeln!(out, "]"),
+    }
+}
+
+// Writes a graph in graphviz's dot format, with each node filled with the
+// color of its kind and edges standing for several references labeled with
+// how many.
+pub fn write_dot<W: Write>(
+    graph: &ReferenceGraph,
+    options: Options,
+    out: &mut W,
+) -> io::Result<()> {
+    writeln!(out, "digraph {{")?;
+    writeln!(out, "    node [shape=box, style=filled];")?;
+    if options.invert_edges {
+        writeln!(out, "    edge [dir=back];")?;
+    }
+
+    let mut clusters: BTreeMap<&str, Vec<Index>> = BTreeMap::new();
+    for i in graph.node_indices() {
+        match namespace(&graph[i].kind) {
+            Some(namespace) if options.cluster_namespaces => {
+                clusters.entry(namespace).or_default().push(i)
+            }
+            _ => write_node(graph, i, options, "    ", out)?,
+        }
+    }
+    for (n, (namespace, nodes)) in clusters.iter().enumerate() {
+        writeln!(out, "    subgraph cluster_{} {{", n)?;
+        writeln!(out, "        label=\"{}\";", escape_dot(namespace))?;
+        for i in nodes {
+            write_node(graph, *i, options, "        ", out)?;
+        }
+        writeln!(out, "    }}")?;
+    }
+
+    if let Some(threshold) = options.legend {
+        write_legend(graph, threshold, options.invert_edges, out)?;
+    }
+
+    for e in graph.raw_edges() {
+        if e.weight == EDGE_WEIGHT {
+            writeln!(out, "    {} -> {}", e.source().index(), e.target().index())?;
+        } else {
+            writeln!(
+                out,
+                "    {} -> {} [label=\"{}\"]",
+                e.source().index(),
+                e.target().index(),
+                e.weight
+            )?;
+        }
+    }
+    writeln!(out, "}}")
+}
@@ -0,0 +1,160 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// A gem locked in a Gemfile.lock
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Gem {
+    name: String,
+    version: String,
+    source: Source,
+}
+
+// Where Bundler puts a locked gem's files
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    // Installed from a gem server into .../gems/NAME-VERSION/
+    Rubygems,
+    // Checked out into .../bundler/gems/REPO-REVISION/, with the first 12
+    // characters of the locked commit. One repository can hold several gems.
+    Git { checkout: String },
+    // Used in place, from a directory relative to the Gemfile's
+    Path { path: String },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Lockfile {
+    gems: Vec<Gem>,
+}
+
+impl Lockfile {
+    pub fn open(path: &Path) -> io::Result<Lockfile> {
+        Ok(Lockfile::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn parse(contents: &str) -> Lockfile {
+        let mut gems = vec![];
+        let mut section = "";
+        let (mut remote, mut revision) = ("", "");
+        for line in contents.lines() {
+            if !line.starts_with(' ') {
+                section = line.trim();
+                remote = "";
+                revision = "";
+            } else if let Some(value) = line.strip_prefix("  remote: ") {
+                remote = value.trim();
+            } else if let Some(value) = line.strip_prefix("  revision: ") {
+                revision = value.trim();
+            } else if line.starts_with("    ") && !line.starts_with("     ") {
+                // A locked gem, e.g. "    nokogiri (1.15.4-x86_64-linux)",
+                // followed by its dependencies indented further
+                let spec = line.trim();
+                let (name, version) = match spec
+                    .strip_suffix(')')
+                    .and_then(|spec| spec.split_once(" ("))
+                {
+                    Some(spec) => spec,
+                    None => continue,
+                };
+                let source = match section {
+                    "GEM" => Source::Rubygems,
+                    "GIT" => Source::Git {
+                        checkout: format!(
+                            "{}-{}",
+                            repository_name(remote),
+                            &revision[..revision.len().min(12)]
+                        ),
+                    },
+                    "PATH" => Source::Path {
+                        path: remote
+                            .trim_start_matches("./")
+                            .trim_end_matches('/')
+                            .to_string(),
+                    },
+                    _ => continue,
+                };
+                gems.push(Gem {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    source,
+                });
+            }
+        }
+        Lockfile { gems }
+    }
+
+    // The locked gem a source file belongs to
+    fn find(&self, file: &str) -> Option<&Gem> {
+        let dirs: Vec<&str> = file.split('/').collect();
+        // The innermost install directory, in case gems are vendored inside
+        // another gem's
+        for i in (0..dirs.len().saturating_sub(2)).rev() {
+            if dirs[i] != "gems" {
+                continue;
+            }
+            let installed: Vec<&Gem> = self
+                .gems
+                .iter()
+                .filter(|gem| match gem.source {
+                    Source::Rubygems => dirs[i + 1] == format!("{}-{}", gem.name, gem.version),
+                    Source::Git { ref checkout } => dirs[i + 1] == checkout,
+                    Source::Path { .. } => false,
+                })
+                .collect();
+            // Of several gems in one repository, the one whose directory the
+            // file is in, or else the one the repository is named after
+            let gem = installed
+                .iter()
+                .find(|gem| dirs[i + 2] == gem.name)
+                .or_else(|| {
+                    installed
+                        .iter()
+                        .find(|gem| dirs[i + 1].starts_with(&format!("{}-", gem.name)))
+                })
+                .or_else(|| installed.first());
+            if let Some(gem) = gem {
+                return Some(gem);
+            }
+        }
+
+        let file = format!("/{}", file.trim_start_matches('/'));
+        self.gems.iter().find(|gem| match gem.source {
+            // "." is the app's own gemspec, which every file would be in
+            Source::Path { ref path } if path != "." => {
+                file.contains(&format!("/{}/", path.trim_start_matches('/')))
+            }
+            _ => false,
+        })
+    }
+}
+
+// e.g. "rails" for "https://github.com/rails/rails.git"
+fn repository_name(remote: &str) -> &str {
+    let name = remote
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(remote);
+    name.strip_suffix(".git").unwrap_or(name)
+}
+
+// The gem a source file belongs to and its version, e.g. "nokogiri
+// (1.15.4)": as locked in the Gemfile.lock if given and the gem is in it, or
+// else guessed from the directory it's installed in, taking the version to
+// start at the first dash followed by a digit.
+pub fn gem_of(file: &str, lockfile: Option<&Lockfile>) -> Option<String> {
+    if let Some(gem) = lockfile.and_then(|lockfile| lockfile.find(file)) {
+        return Some(format!("{} ({})", gem.name, gem.version));
+    }
+
+    let start = file.rfind("/gems/")? + "/gems/".len();
+    let (dir, _) = file[start..].split_once('/')?;
+    let version = dir
+        .match_indices('-')
+        .map(|(i, _)| i)
+        .find(|i| dir[i + 1..].starts_with(|c: char| c.is_ascii_digit()));
+    Some(match version {
+        Some(i) => format!("{} ({})", &dir[..i], &dir[i + 1..]),
+        None => dir.to_string(),
+    })
+}
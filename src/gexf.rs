@@ -0,0 +1,89 @@
+use crate::analyze::Analysis;
+use crate::html::escape_html;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Node attributes, as (title, GEXF type); attribute IDs are their indices.
+const ATTRIBUTES: &[(&str, &str)] = &[
+    ("class", "string"),
+    ("self_bytes", "long"),
+    ("retained_bytes", "long"),
+    ("count", "long"),
+];
+
+// Writes the reachable part of the reference graph as GEXF 1.3, for Gephi.
+pub fn write_gexf(
+    analysis: &Analysis,
+    threshold_bytes: usize,
+    filename: &Path,
+) -> io::Result<(usize, usize)> {
+    let graph = analysis.dominated_subgraph();
+    let (indices, edges) = analysis.exported(threshold_bytes);
+
+    let file = File::create(filename)?;
+    let mut out = io::BufWriter::new(file);
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">")?;
+    writeln!(
+        out,
+        "<meta><creator>reap v{}</creator></meta>",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(out, "<graph defaultedgetype=\"directed\">")?;
+
+    writeln!(out, "<attributes class=\"node\">")?;
+    for (id, (title, attr_type)) in ATTRIBUTES.iter().enumerate() {
+        writeln!(
+            out,
+            "<attribute id=\"{}\" title=\"{}\" type=\"{}\"/>",
+            id, title, attr_type
+        )?;
+    }
+    writeln!(out, "</attributes>")?;
+
+    let mut node_count = 0;
+    writeln!(out, "<nodes>")?;
+    for i in indices {
+        let obj = &graph[i];
+        let retained = analysis.retained_stats(i);
+        writeln!(
+            out,
+            "<node id=\"{:#x}\" label=\"{}\"><attvalues>\
+             <attvalue for=\"0\" value=\"{}\"/>\
+             <attvalue for=\"1\" value=\"{}\"/>\
+             <attvalue for=\"2\" value=\"{}\"/>\
+             <attvalue for=\"3\" value=\"{}\"/>\
+             </attvalues></node>",
+            obj.address,
+            escape_html(&obj.to_string()),
+            escape_html(&obj.kind),
+            obj.bytes,
+            retained.bytes,
+            retained.count
+        )?;
+        node_count += 1;
+    }
+    writeln!(out, "</nodes>")?;
+
+    let mut edge_count = 0;
+    writeln!(out, "<edges>")?;
+    for e in edges {
+        writeln!(
+            out,
+            "<edge id=\"{}\" source=\"{:#x}\" target=\"{:#x}\" weight=\"{}\"/>",
+            edge_count,
+            graph[e.source()].address,
+            graph[e.target()].address,
+            e.weight.0
+        )?;
+        edge_count += 1;
+    }
+    writeln!(out, "</edges>")?;
+
+    writeln!(out, "</graph>\n</gexf>")?;
+    out.flush()?;
+    Ok((node_count, edge_count))
+}
@@ -0,0 +1,82 @@
+use crate::analyze::Analysis;
+use crate::html::escape_html;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Attribute keys declared for every node, as (id, GraphML type).
+const KEYS: &[(&str, &str)] = &[
+    ("label", "string"),
+    ("kind", "string"),
+    ("self_bytes", "long"),
+    ("retained_bytes", "long"),
+];
+
+// Writes the reachable part of the reference graph as GraphML, with each
+// node's kind, self size and retained size as attributes.
+pub fn write_graphml(
+    analysis: &Analysis,
+    threshold_bytes: usize,
+    filename: &Path,
+) -> io::Result<(usize, usize)> {
+    let graph = analysis.dominated_subgraph();
+    let (indices, edges) = analysis.exported(threshold_bytes);
+    let file = File::create(filename)?;
+    let mut out = io::BufWriter::new(file);
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        out,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    for (id, attr_type) in KEYS {
+        writeln!(
+            out,
+            "<key id=\"{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"{1}\"/>",
+            id, attr_type
+        )?;
+    }
+    // How many references an edge stands for
+    writeln!(
+        out,
+        "<key id=\"references\" for=\"edge\" attr.name=\"references\" attr.type=\"long\"/>"
+    )?;
+    writeln!(out, "<graph id=\"heap\" edgedefault=\"directed\">")?;
+
+    let mut node_count = 0;
+    for i in indices {
+        let obj = &graph[i];
+        writeln!(out, "<node id=\"{:#x}\">", obj.address)?;
+        writeln!(
+            out,
+            "<data key=\"label\">{}</data>",
+            escape_html(&obj.to_string())
+        )?;
+        writeln!(out, "<data key=\"kind\">{}</data>", escape_html(&obj.kind))?;
+        writeln!(out, "<data key=\"self_bytes\">{}</data>", obj.bytes)?;
+        writeln!(
+            out,
+            "<data key=\"retained_bytes\">{}</data>",
+            analysis.retained_stats(i).bytes
+        )?;
+        writeln!(out, "</node>")?;
+        node_count += 1;
+    }
+
+    let mut edge_count = 0;
+    for e in edges {
+        writeln!(
+            out,
+            "<edge source=\"{:#x}\" target=\"{:#x}\"><data key=\"references\">{}</data></edge>",
+            graph[e.source()].address,
+            graph[e.target()].address,
+            e.weight.0
+        )?;
+        edge_count += 1;
+    }
+
+    writeln!(out, "</graph>\n</graphml>")?;
+    out.flush()?;
+    Ok((node_count, edge_count))
+}
@@ -0,0 +1,167 @@
+use crate::analyze::Analysis;
+use crate::object::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Layout of the flattened `nodes` & `edges` arrays, as described by the
+// snapshot's meta section; see
+// https://github.com/ChromeDevTools/devtools-frontend/blob/main/front_end/core/sdk/HeapSnapshotModel.ts
+const NODE_FIELDS: &[&str] = &[
+    "type",
+    "name",
+    "id",
+    "self_size",
+    "edge_count",
+    "trace_node_id",
+];
+const NODE_TYPES: &[&str] = &[
+    "hidden",
+    "array",
+    "string",
+    "object",
+    "code",
+    "closure",
+    "regexp",
+    "number",
+    "native",
+    "synthetic",
+    "concatenated string",
+    "sliced string",
+    "symbol",
+    "bigint",
+];
+const EDGE_FIELDS: &[&str] = &["type", "name_or_index", "to_node"];
+const EDGE_TYPES: &[&str] = &[
+    "context", "element", "property", "internal", "hidden", "shortcut", "weak",
+];
+
+// Ruby dumps don't name references, so every edge is an indexed element.
+const ELEMENT_EDGE: u64 = 1;
+
+// DevTools expects the snapshot root to have a non-zero ID, and Ruby object
+// addresses are never odd.
+const ROOT_ID: u64 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct HeapSnapshot {
+    snapshot: Snapshot,
+    pub nodes: Vec<u64>,
+    pub edges: Vec<u64>,
+    trace_function_infos: Vec<u64>,
+    trace_tree: Vec<u64>,
+    samples: Vec<u64>,
+    locations: Vec<u64>,
+    pub strings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Snapshot {
+    meta: serde_json::Value,
+    node_count: usize,
+    edge_count: usize,
+    trace_function_count: usize,
+}
+
+fn meta() -> serde_json::Value {
+    serde_json::json!({
+        "node_fields": NODE_FIELDS,
+        "node_types": [NODE_TYPES, "string", "number", "number", "number", "number"],
+        "edge_fields": EDGE_FIELDS,
+        "edge_types": [EDGE_TYPES, "string_or_number", "node"],
+        "trace_function_info_fields": [],
+        "trace_node_fields": [],
+        "sample_fields": [],
+        "location_fields": [],
+    })
+}
+
+// DevTools groups objects by name in its summary view, so everything other
+// than strings is named by class.
+fn node_type_and_name(obj: &Object) -> (&'static str, String) {
+    match obj.kind.as_str() {
+        "ROOT" => ("synthetic", obj.to_string()),
+        "String" | "STRING" => ("string", obj.to_string()),
+        "Array" | "ARRAY" => ("array", obj.kind.clone()),
+        "Regexp" | "REGEXP" => ("regexp", obj.kind.clone()),
+        "Symbol" | "SYMBOL" => ("symbol", obj.kind.clone()),
+        "Float" | "FLOAT" | "Integer" | "BIGNUM" => ("number", obj.kind.clone()),
+        "DATA" => ("native", obj.kind.clone()),
+        "IMEMO" | "NODE" | "ICLASS" => ("hidden", obj.kind.clone()),
+        _ => ("object", obj.kind.clone()),
+    }
+}
+
+// Converts the reachable part of the reference graph to a Chrome DevTools heap
+// snapshot. DevTools computes retained sizes & dominators itself, from the
+// root, which it requires to be the first node.
+pub fn snapshot(analysis: &Analysis) -> HeapSnapshot {
+    let graph = analysis.dominated_subgraph();
+    let root = analysis.root();
+
+    let mut order = vec![root];
+    order.extend(graph.node_indices().filter(|&i| i != root));
+
+    let mut positions = vec![0; graph.node_count()];
+    for (position, i) in order.iter().enumerate() {
+        positions[i.index()] = position;
+    }
+
+    let mut strings: Vec<String> = Vec::new();
+    let mut string_indices: HashMap<String, u64> = HashMap::new();
+    let mut nodes = Vec::with_capacity(order.len() * NODE_FIELDS.len());
+    let mut edges = Vec::with_capacity(graph.edge_count() * EDGE_FIELDS.len());
+
+    for &i in order.iter() {
+        let obj = &graph[i];
+        let (node_type, name) = node_type_and_name(obj);
+        let name = *string_indices.entry(name).or_insert_with_key(|name| {
+            strings.push(name.clone());
+            strings.len() as u64 - 1
+        });
+
+        let mut edge_count = 0;
+        for (n, j) in graph.neighbors(i).enumerate() {
+            edges.push(ELEMENT_EDGE);
+            edges.push(n as u64);
+            edges.push((positions[j.index()] * NODE_FIELDS.len()) as u64);
+            edge_count += 1;
+        }
+
+        nodes.push(NODE_TYPES.iter().position(|&t| t == node_type).unwrap() as u64);
+        nodes.push(name);
+        nodes.push(if i == root {
+            ROOT_ID
+        } else {
+            obj.address as u64
+        });
+        nodes.push(obj.bytes as u64);
+        nodes.push(edge_count);
+        nodes.push(0);
+    }
+
+    HeapSnapshot {
+        snapshot: Snapshot {
+            meta: meta(),
+            node_count: order.len(),
+            edge_count: edges.len() / EDGE_FIELDS.len(),
+            trace_function_count: 0,
+        },
+        nodes,
+        edges,
+        trace_function_infos: Vec::new(),
+        trace_tree: Vec::new(),
+        samples: Vec::new(),
+        locations: Vec::new(),
+        strings,
+    }
+}
+
+pub fn write_heapsnapshot(snapshot: &HeapSnapshot, filename: &Path) -> io::Result<()> {
+    let file = File::create(filename)?;
+    let mut writer = io::BufWriter::new(file);
+    serde_json::to_writer(&mut writer, snapshot)?;
+    writer.flush()
+}
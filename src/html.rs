@@ -0,0 +1,247 @@
+use crate::analyze::TreeNode;
+use crate::report::{Printer, Report, Row, SummaryReport, Table};
+use crate::units::Units;
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { padding: 0.2em 0.8em; text-align: right; border-bottom: 1px solid #ddd; }
+th:first-child, td:first-child { text-align: left; font-family: monospace; }
+td.bar { width: 200px; }
+td.bar div { background: #e8743b; height: 0.8em; }
+ul.tree, ul.tree ul { list-style: none; padding-left: 1.2em; }
+ul.tree li { font-family: monospace; margin: 0.1em 0; }
+ul.tree .stats { color: #777; }
+";
+
+const TREEMAP_STYLE: &str = "
+body { font-family: sans-serif; margin: 1em; color: #222; }
+#crumbs { margin-bottom: 0.5em; font-family: monospace; }
+#treemap { position: relative; width: 100%; height: 85vh; }
+.node { position: absolute; overflow: hidden; box-sizing: border-box; border: 1px solid #fff; cursor: pointer; }
+.node span { display: block; padding: 1px 3px; font: 11px monospace; white-space: nowrap; }
+";
+
+const TREEMAP_SCRIPT: &str = include_str!("assets/treemap.js");
+
+const GRAPH_STYLE: &str = "
+body { font-family: sans-serif; margin: 0; color: #222; display: flex; height: 100vh; }
+#graph { flex: 1; cursor: move; }
+#details { width: 22em; padding: 1em; border-left: 1px solid #ddd; overflow: auto; }
+#details h3 { font-family: monospace; word-break: break-all; }
+#details td { padding: 0.1em 0.5em; }
+.link { stroke: #bbb; stroke-width: 1; }
+.node { stroke: #fff; stroke-width: 1.5; cursor: pointer; }
+.node.collapsed { stroke: #555; stroke-dasharray: 2 2; }
+.node.selected { stroke: #000; stroke-width: 3; }
+";
+
+const GRAPH_SCRIPT: &str = include_str!("assets/graph.js");
+
+// Number of dominator tree levels expanded when the page is opened.
+const OPEN_DEPTH: usize = 2;
+
+pub fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Renders report tables, with a bar showing each row's share of the total.
+struct HtmlPrinter<W: Write> {
+    out: W,
+    units: Units,
+    total_bytes: usize,
+}
+
+impl<W: Write> Printer for HtmlPrinter<W> {
+    fn heading(&mut self, title: &str) -> io::Result<()> {
+        writeln!(self.out, "<h2>{}</h2>", escape_html(title))
+    }
+
+    fn table<R: Row + Display>(&mut self, table: &Table<R, R::Rest>) -> io::Result<()> {
+        writeln!(self.out, "<table>")?;
+        write!(self.out, "<tr>")?;
+        for heading in R::HEADER {
+            write!(self.out, "<th>{}</th>", escape_html(heading))?;
+        }
+        writeln!(self.out, "<th></th></tr>")?;
+
+        for row in table.largest.iter() {
+            self.row(row.cells(self.units), row.bytes())?;
+        }
+        if table.has_rest() {
+            self.row(
+                R::rest_cells(table.rest, self.units),
+                R::rest_bytes(table.rest),
+            )?;
+        }
+
+        writeln!(self.out, "</table>")
+    }
+
+    fn text(&mut self, text: &str) -> io::Result<()> {
+        writeln!(self.out, "<p>{}</p>", escape_html(text))
+    }
+
+    fn tree(&mut self, tree: &TreeNode) -> io::Result<()> {
+        writeln!(self.out, "<ul class=\"tree\">")?;
+        self.subtree(tree, 0)?;
+        writeln!(self.out, "</ul>")
+    }
+
+    fn units(&self) -> Units {
+        self.units
+    }
+}
+
+impl<W: Write> HtmlPrinter<W> {
+    fn row(&mut self, cells: Vec<String>, bytes: usize) -> io::Result<()> {
+        write!(self.out, "<tr>")?;
+        for cell in cells {
+            write!(self.out, "<td>{}</td>", escape_html(&cell))?;
+        }
+        let share = (bytes as f64 / self.total_bytes.max(1) as f64 * 100.0).min(100.0);
+        writeln!(
+            self.out,
+            "<td class=\"bar\"><div style=\"width: {:.1}%\"></div></td></tr>",
+            share
+        )
+    }
+
+    fn subtree(&mut self, node: &TreeNode, depth: usize) -> io::Result<()> {
+        let summary = format!(
+            "{} <span class=\"stats\">{} retained, {} self, {} objects</span>",
+            escape_html(&node.label),
+            self.units.bytes(node.retained_bytes),
+            self.units.bytes(node.self_bytes),
+            node.count
+        );
+
+        if node.children.is_empty() {
+            return writeln!(self.out, "<li>{}</li>", summary);
+        }
+
+        let open = if depth < OPEN_DEPTH { " open" } else { "" };
+        writeln!(
+            self.out,
+            "<li><details{}><summary>{}</summary><ul>",
+            open, summary
+        )?;
+        for child in node.children.iter() {
+            self.subtree(child, depth + 1)?;
+        }
+        writeln!(self.out, "</ul></details></li>")
+    }
+}
+
+// Writes a standalone HTML page with the summary report tables and the
+// relevant part of the dominator tree.
+pub fn write_report(
+    report: &SummaryReport,
+    tree: &TreeNode,
+    title: &str,
+    units: Units,
+    filename: &Path,
+) -> io::Result<()> {
+    let file = File::create(filename)?;
+    let mut printer = HtmlPrinter {
+        out: io::BufWriter::new(file),
+        units,
+        total_bytes: report.totals.bytes,
+    };
+
+    writeln!(
+        printer.out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>",
+        escape_html(title),
+        STYLE
+    )?;
+    writeln!(printer.out, "<h1>{}</h1>", escape_html(title))?;
+    writeln!(
+        printer.out,
+        "<p>{} live in {} objects</p>",
+        units.bytes(report.totals.bytes),
+        report.totals.count
+    )?;
+
+    report.print(&mut printer)?;
+
+    printer.heading("Dominator tree")?;
+    printer.tree(tree)?;
+
+    writeln!(printer.out, "</body>\n</html>")?;
+    printer.out.flush()
+}
+
+// Writes a standalone HTML page with the dominator tree embedded as JSON, to
+// be rendered by `script`.
+fn write_tree_page(
+    tree: &TreeNode,
+    title: &str,
+    style: &str,
+    body: &str,
+    script: &str,
+    filename: &Path,
+) -> io::Result<()> {
+    // Keep the embedded JSON from closing its script tag early
+    let json = serde_json::to_string(tree)?.replace("</", "<\\/");
+
+    let file = File::create(filename)?;
+    let mut out = io::BufWriter::new(file);
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>",
+        escape_html(title),
+        style
+    )?;
+    writeln!(out, "{}", body)?;
+    writeln!(
+        out,
+        "<script type=\"application/json\" id=\"tree\">{}</script>",
+        json
+    )?;
+    writeln!(out, "<script>{}</script>", script)?;
+    writeln!(out, "</body>\n</html>")?;
+    out.flush()
+}
+
+// Writes a standalone HTML page rendering the dominator tree as a zoomable
+// treemap, with rectangles sized by retained memory.
+pub fn write_treemap(tree: &TreeNode, title: &str, filename: &Path) -> io::Result<()> {
+    write_tree_page(
+        tree,
+        title,
+        TREEMAP_STYLE,
+        "<div id=\"crumbs\"></div>\n<div id=\"treemap\"></div>",
+        TREEMAP_SCRIPT,
+        filename,
+    )
+}
+
+// Writes a standalone HTML page rendering the dominator tree as an
+// interactive force-directed graph, with collapsible subtrees.
+pub fn write_graph(tree: &TreeNode, title: &str, filename: &Path) -> io::Result<()> {
+    write_tree_page(
+        tree,
+        title,
+        GRAPH_STYLE,
+        "<svg id=\"graph\"></svg>\n<div id=\"details\"></div>",
+        GRAPH_SCRIPT,
+        filename,
+    )
+}
@@ -0,0 +1,210 @@
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// Standard input can only be read once, unlike a file, so commands that read
+// their input more than once spool it to a file first (see `spool`), and it
+// can't be given as more than one input
+static STDIN_READ: AtomicBool = AtomicBool::new(false);
+
+// Number of dumps spooled so far, so each gets a file of its own
+static SPOOLED: AtomicUsize = AtomicUsize::new(0);
+
+// Opens a dump as one stream of lines, given the one or more files (shards)
+// it was written to, in order. "-" reads from standard input, and HTTP(S)
+// and S3 URLs are downloaded as they're read.
+pub fn open(paths: &[PathBuf]) -> io::Result<Box<dyn BufRead>> {
+    let mut reader: Box<dyn Read> = Box::new(io::empty());
+    for path in paths {
+        reader = Box::new(reader.chain(Shard::new(open_file(path)?)));
+    }
+    Ok(Box::new(BufReader::new(reader)))
+}
+
+// One of the files a dump is split into, ending with a newline even if the
+// file doesn't, so its last line isn't run into the next file's first
+struct Shard<R> {
+    inner: R,
+    last: Option<u8>,
+    ended: bool,
+}
+
+impl<R: Read> Shard<R> {
+    fn new(inner: R) -> Shard<R> {
+        Shard {
+            inner,
+            last: None,
+            ended: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Shard<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.ended || buf.is_empty() {
+            return Ok(0);
+        }
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.last = Some(buf[n - 1]);
+            return Ok(n);
+        }
+        self.ended = true;
+        match self.last {
+            Some(last) if last != b'\n' => {
+                buf[0] = b'\n';
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+// A dump that can be read more than once: the files it's in, or a copy of it
+// in the temporary directory if reading it again wouldn't be cheap, as from
+// standard input, where it's gone, or from a URL, where it would have to be
+// downloaded again. The copy is decompressed, and removed when dropped.
+pub struct Spooled {
+    paths: Vec<PathBuf>,
+    copy: bool,
+}
+
+impl Spooled {
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+impl Drop for Spooled {
+    fn drop(&mut self) {
+        if self.copy {
+            let _ = std::fs::remove_file(&self.paths[0]);
+        }
+    }
+}
+
+pub fn spool(paths: &[PathBuf]) -> io::Result<Spooled> {
+    let local = paths.iter().all(|path| {
+        path != Path::new("-")
+            && !path.to_str().is_some_and(|path| {
+                ["http://", "https://", "s3://"]
+                    .iter()
+                    .any(|scheme| path.starts_with(scheme))
+            })
+    });
+    if local {
+        return Ok(Spooled {
+            paths: paths.to_vec(),
+            copy: false,
+        });
+    }
+
+    let path = std::env::temp_dir().join(format!(
+        "reap-input-{}-{}.json",
+        std::process::id(),
+        SPOOLED.fetch_add(1, Ordering::SeqCst)
+    ));
+    let spooled = Spooled {
+        paths: vec![path.clone()],
+        copy: true,
+    };
+    let mut file = io::BufWriter::new(File::create(path)?);
+    io::copy(&mut open(paths)?, &mut file)?;
+    file.flush()?;
+    Ok(spooled)
+}
+
+fn open_file(path: &Path) -> io::Result<Box<dyn Read>> {
+    if let Some(url) = path
+        .to_str()
+        .filter(|path| path.starts_with("http://") || path.starts_with("https://"))
+    {
+        let response = ureq::get(url).call().map_err(io::Error::other)?;
+        return decompress(response.into_reader());
+    }
+    if let Some(location) = path.to_str().and_then(|path| path.strip_prefix("s3://")) {
+        return decompress(open_s3(location)?);
+    }
+    if path != Path::new("-") {
+        return decompress(File::open(path)?);
+    }
+    if STDIN_READ.swap(true, Ordering::SeqCst) {
+        return Err(io::Error::other(
+            "standard input can only be read once, so can't be given as more than one input",
+        ));
+    }
+    decompress(io::stdin())
+}
+
+// An object in S3, e.g. "bucket/path/to/heap.json.gz", streamed from it with
+// the credentials, region and endpoint configured as for the AWS CLI
+#[cfg(feature = "s3")]
+fn open_s3(location: &str) -> io::Result<Box<dyn Read>> {
+    let (bucket, key) = location
+        .split_once('/')
+        .ok_or_else(|| io::Error::other(format!("No key in s3://{}", location)))?;
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let config = runtime.block_on(aws_config::load_defaults(
+        aws_config::BehaviorVersion::latest(),
+    ));
+    let client = aws_sdk_s3::Client::new(&config);
+    let object = runtime
+        .block_on(client.get_object().bucket(bucket).key(key).send())
+        .map_err(|e| io::Error::other(aws_sdk_s3::error::DisplayErrorContext(e).to_string()))?;
+    let body = tokio_util::io::SyncIoBridge::new_with_handle(
+        object.body.into_async_read(),
+        runtime.handle().clone(),
+    );
+    Ok(Box::new(S3Object {
+        body,
+        _runtime: runtime,
+    }))
+}
+
+#[cfg(not(feature = "s3"))]
+fn open_s3(_: &str) -> io::Result<Box<dyn Read>> {
+    Err(io::Error::other(
+        "S3 input requires building reap with `--features s3`",
+    ))
+}
+
+// The body of an object being downloaded, with the runtime downloading it,
+// which has to outlive it
+#[cfg(feature = "s3")]
+struct S3Object<R> {
+    body: tokio_util::io::SyncIoBridge<R>,
+    _runtime: tokio::runtime::Runtime,
+}
+
+#[cfg(feature = "s3")]
+impl<R: tokio::io::AsyncRead + Unpin> Read for S3Object<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.body.read(buf)
+    }
+}
+
+// Gzipped and zstd-compressed dumps are told apart by their magic bytes
+// rather than their name, and decompressed as they're read. A read from a
+// pipe can return fewer bytes than the magic, so it's read until there are
+// enough to tell, or the dump ends.
+pub fn decompress<R: Read + 'static>(mut reader: R) -> io::Result<Box<dyn Read>> {
+    let mut start = Vec::with_capacity(ZSTD_MAGIC.len());
+    (&mut reader)
+        .take(ZSTD_MAGIC.len() as u64)
+        .read_to_end(&mut start)?;
+    let (gzip, zstd) = (start.starts_with(GZIP_MAGIC), start.starts_with(ZSTD_MAGIC));
+    let reader = BufReader::new(io::Cursor::new(start).chain(reader));
+    if gzip {
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else if zstd {
+        Ok(Box::new(zstd::Decoder::with_buffer(reader)?))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
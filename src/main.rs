@@ -1,36 +1,183 @@
-extern crate bytesize;
 extern crate inferno;
 #[macro_use]
 extern crate serde;
 extern crate petgraph;
+extern crate regex;
 extern crate serde_json;
 extern crate structopt;
 extern crate timed_function;
 
 mod analyze;
+mod annotate;
+mod capture;
+#[cfg(feature = "parquet")]
+mod columnar;
+mod diff;
+mod dot;
+mod gems;
+mod gexf;
+mod graphml;
+mod heapsnapshot;
+mod html;
+mod input;
+mod mermaid;
+mod neo4j;
 mod object;
+mod pages;
 mod parse;
+mod pprof;
+mod report;
+mod speedscope;
+mod svg;
+mod trend;
+mod units;
+mod watch;
 
 use crate::object::*;
-use bytesize::ByteSize;
+use crate::units::Units;
 use inferno::flamegraph;
-use petgraph::dot;
+use petgraph::visit::Dfs;
+use petgraph::Direction;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::error;
-use std::fmt::Display;
 use std::fs::File;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+use structopt::clap;
 use structopt::StructOpt;
 
 type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 
-fn write_dot_file(graph: &ReferenceGraph, filename: &Path) -> Result<()> {
-    let mut file = File::create(filename)?;
-    write!(
-        file,
-        "{}",
-        dot::Dot::with_config(&graph, &[dot::Config::EdgeNoLabel])
-    )?;
+// Command line options applying to every dump parsed
+#[derive(Debug, Default)]
+struct ParseOptions {
+    // Categories of GC roots whose references are left out, from
+    // --ignore-roots
+    ignored_roots: Vec<String>,
+    // From --slot-sizes, --missing-memsize and --partial
+    parsing: parse::Options,
+    // From --min-generation, --max-generation and --gemfile-lock
+    analysis: analyze::Options,
+}
+
+// "-" means stdout, so output can be piped straight into another tool
+fn is_stdout(filename: &Path) -> bool {
+    filename == Path::new("-")
+}
+
+fn write_dot_file(graph: &ReferenceGraph, options: dot::Options, filename: &Path) -> Result<()> {
+    let mut out: Box<dyn Write> = if is_stdout(filename) {
+        Box::new(std::io::stdout())
+    } else {
+        Box::new(File::create(filename)?)
+    };
+    dot::write_dot(graph, options, &mut out)?;
+    Ok(())
+}
+
+// Full values of the strings drawn in a graph, which their labels truncate,
+// read from the dump again.
+fn graph_string_values(
+    input: &[PathBuf],
+    graph: &ReferenceGraph,
+) -> Result<HashMap<usize, String>> {
+    let addresses: HashSet<usize> = graph
+        .raw_nodes()
+        .iter()
+        .map(|n| &n.weight)
+        .filter(|obj| obj.kind.eq_ignore_ascii_case("string"))
+        .map(|obj| obj.address)
+        .collect();
+    if addresses.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(parse::find_string_values(input::open(input)?, &addresses)?)
+}
+
+// Writes the part of a graph under each child of its root to a dot file of
+// its own in `dir`, e.g. "Gem-0x7f83df856898.dot", since a graph of a whole
+// heap can be too big to render. Children of categories of roots count as
+// children of the root.
+fn write_split_dot_files(graph: &ReferenceGraph, options: dot::Options, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for root in graph.externals(Direction::Incoming) {
+        let children = graph.neighbors(root).flat_map(|child| {
+            if graph[child].is_root() {
+                graph.neighbors(child).collect()
+            } else {
+                vec![child]
+            }
+        });
+        for child in children {
+            let mut subtree = HashSet::new();
+            let mut dfs = Dfs::new(graph, child);
+            while let Some(i) = dfs.next(graph) {
+                subtree.insert(i);
+            }
+            let subgraph = graph.filter_map(
+                |i, obj| subtree.get(&i).map(|_| obj.clone()),
+                |_, e| Some(*e),
+            );
+
+            let obj = &graph[child];
+            let name = if obj.address == 0 {
+                obj.kind.clone()
+            } else {
+                format!("{}-{:#x}", obj.kind, obj.address)
+            };
+            let name: String = name
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            write_dot_file(&subgraph, options, &dir.join(format!("{}.dot", name)))?;
+        }
+    }
+    Ok(())
+}
+
+// Graphviz output format for a rendered file, e.g. "png" for "out.png"
+fn render_format(filename: &Path) -> Result<String> {
+    match filename.extension().and_then(|e| e.to_str()) {
+        Some(ext) if !ext.is_empty() => Ok(ext.to_lowercase()),
+        _ => Err(format!(
+            "Can't tell which format to render {} as; use an extension like .png or .svg",
+            filename.display()
+        )
+        .into()),
+    }
+}
+
+fn render_dot_file(graph: &ReferenceGraph, options: dot::Options, filename: &Path) -> Result<()> {
+    let format = render_format(filename)?;
+    let mut child = process::Command::new("dot")
+        .arg(format!("-T{}", format))
+        .arg("-o")
+        .arg(filename)
+        .stdin(process::Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                "Graphviz's `dot` was not found on PATH; install graphviz, or use --svg instead"
+                    .to_string()
+            }
+            _ => format!("Failed to run graphviz's `dot`: {}", e),
+        })?;
+
+    dot::write_dot(graph, options, &mut child.stdin.take().unwrap())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("Graphviz's `dot` exited with {}", status).into());
+    }
     Ok(())
 }
 
@@ -38,6 +185,9 @@ fn write_flamegraph(lines: &[String], filename: &Path) -> Result<()> {
     let mut opts = flamegraph::Options::default();
     opts.direction = flamegraph::Direction::Inverted;
     opts.count_name = "bytes".to_string();
+    opts.title = "Retained memory".to_string();
+    opts.subtitle =
+        Some("Frame width is retained bytes; stacks follow the dominator tree".to_string());
 
     let file = File::create(filename)?;
     flamegraph::from_lines(&mut opts, lines.iter().map(|s| s.as_str()), file).unwrap();
@@ -53,63 +203,50 @@ fn write_folded(lines: &[String], filename: &Path) -> Result<()> {
     Ok(())
 }
 
-fn print_largest<K: Display>(largest: &[(K, Stats)], rest: Stats) {
-    if largest.is_empty() {
-        println!("None");
-        return;
-    }
-
-    for (k, stats) in largest {
-        println!(
-            "{}: {} ({} objects)",
-            k,
-            ByteSize(stats.bytes as u64),
-            stats.count
-        );
-    }
-
-    if rest.count > 0 {
-        println!(
-            "...: {} ({} objects)",
-            ByteSize(rest.bytes as u64),
-            rest.count
-        );
-    }
-}
-
 fn parse(
-    file: &Path,
+    input: &[PathBuf],
     rooted_at: Option<usize>,
     class_name_only: bool,
+    options: &ParseOptions,
 ) -> std::io::Result<analyze::Analysis> {
-    let (root, graph) = parse::parse(file, class_name_only)?;
+    let (root, graph) = parse::parse(
+        input::open(input)?,
+        class_name_only,
+        &options.ignored_roots,
+        options.parsing,
+    )?;
 
-    let subgraph_root = rooted_at
-        .map(|address| {
-            graph
-                .node_indices()
-                .find(|i| graph[*i].address == address)
-                .expect("Given subtree root address not found")
-        })
-        .unwrap_or(root);
+    let subgraph_root = match rooted_at {
+        Some(address) => graph
+            .node_indices()
+            .find(|i| graph[*i].address == address)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("No object at {:#x} in the dump", address),
+                )
+            })?,
+        None => root,
+    };
 
     Ok(analyze::analyze(
         root,
         subgraph_root,
         graph,
         class_name_only,
+        options.analysis.clone(),
     ))
 }
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "reap")]
 struct Opt {
-    /// Path to JSON heap dump file to process
+    /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
     #[structopt(name = "INPUT", parse(from_os_str))]
-    input: PathBuf,
+    input: Vec<PathBuf>,
 
     /// Filter to subtree rooted at object with this address
-    #[structopt(short, long)]
+    #[structopt(short, long, raw(alias = r#""root-address""#))]
     root: Option<String>,
 
     /// Flamegraph SVG output for dominator tree
@@ -120,14 +257,86 @@ struct Opt {
     #[structopt(long, parse(from_os_str))]
     folded: Option<PathBuf>,
 
-    /// Dot file output for dominator tree
+    /// Speedscope JSON output for dominator tree
+    #[structopt(long, parse(from_os_str))]
+    speedscope: Option<PathBuf>,
+
+    /// Gzipped pprof profile output for dominator tree
+    #[structopt(long, parse(from_os_str))]
+    pprof: Option<PathBuf>,
+
+    /// Chrome DevTools heap snapshot output for reference graph
+    #[structopt(long, parse(from_os_str))]
+    heapsnapshot: Option<PathBuf>,
+
+    /// Directory for CSV output of the summary tables
+    #[structopt(long, parse(from_os_str))]
+    csv: Option<PathBuf>,
+
+    /// Dot file output for dominator tree ("-" for stdout, moving the summary to stderr)
     #[structopt(short, long, parse(from_os_str))]
     dot: Option<PathBuf>,
 
-    /// Include nodes retaining at least this fraction of memory in dot output
+    /// Directory to write a dot file to for each top-level dominator retaining at least the threshold
+    #[structopt(long = "split-dot", parse(from_os_str))]
+    split_dot: Option<PathBuf>,
+
+    /// Image output for dominator tree, rendered by graphviz in the format given by its extension
+    #[structopt(long, parse(from_os_str))]
+    render: Option<PathBuf>,
+
+    /// SVG output for dominator tree, rendered without graphviz
+    #[structopt(long, parse(from_os_str))]
+    svg: Option<PathBuf>,
+
+    /// Mermaid flowchart output for dominator tree, for embedding in GitHub issues
+    #[structopt(long, parse(from_os_str))]
+    mermaid: Option<PathBuf>,
+
+    /// Include nodes retaining at least this fraction of memory in graph output
     #[structopt(short, long, default_value = "0.005")]
     threshold: f64,
 
+    /// Merge chains of objects that each dominate only one other into a single node in graph output
+    #[structopt(long = "collapse-chains")]
+    collapse_chains: bool,
+
+    /// Draw siblings of the same class as a single summary node in graph output
+    #[structopt(long = "aggregate-siblings")]
+    aggregate_siblings: bool,
+
+    /// Merge all instances of each class into a single node in graph output
+    #[structopt(long = "by-class")]
+    by_class: bool,
+
+    /// Only draw objects up to this many dominator tree levels below the root in graph output
+    #[structopt(long = "max-depth", raw(conflicts_with = r#""by_class""#))]
+    max_depth: Option<usize>,
+
+    /// Only draw this many of the objects retaining the most memory in graph output, folding the rest into "other" nodes
+    #[structopt(long = "max-nodes", raw(conflicts_with = r#""by_class""#))]
+    max_nodes: Option<usize>,
+
+    /// Group objects of namespaced classes by top-level module in dot output
+    #[structopt(long = "cluster-namespaces")]
+    cluster_namespaces: bool,
+
+    /// Link each object in dot output to this URL, with "{address}" replaced by its address
+    #[structopt(long = "url-template")]
+    url_template: Option<String>,
+
+    /// Explain labels, arrows, colors and the threshold in a legend beside dot output
+    #[structopt(long)]
+    legend: bool,
+
+    /// Point arrows in dot output from objects to their retainers
+    #[structopt(long = "invert-edges")]
+    invert_edges: bool,
+
+    /// Add each object's allocation site to its label in dot output (requires dumps taken with allocation tracing)
+    #[structopt(long = "label-locations")]
+    label_locations: bool,
+
     /// Print this many of the types & objects retaining the most memory
     #[structopt(short, long, default_value = "10")]
     count: usize,
@@ -135,162 +344,5656 @@ struct Opt {
     /// Remove address from flamegraph labels
     #[structopt(long = "class-name-only")]
     class_name_only: bool,
-}
 
-fn main() -> Result<()> {
-    const VERSION: &str = env!("CARGO_PKG_VERSION");
-    println!("reap v{}", VERSION);
+    /// Output format for printed reports
+    #[structopt(
+        long,
+        default_value = "table",
+        raw(possible_values = "report::FORMATS", global = "true")
+    )]
+    format: report::Format,
 
-    let opt = Opt::from_args();
+    /// Order summary tables by bytes, object count, or (for retainers) self or retained bytes
+    #[structopt(
+        long,
+        default_value = "bytes",
+        raw(possible_values = "analyze::SORTS", global = "true")
+    )]
+    sort: analyze::Sort,
 
-    let subtree_root = opt
-        .root
-        .map(|r| parse::parse_address(r.as_str()).expect("Invalid subtree root address"));
+    /// Print this many object types in each by-type table, overriding --count
+    #[structopt(long = "top-types", raw(global = "true"))]
+    top_types: Option<usize>,
 
-    let class_name_only = opt.class_name_only;
+    /// Print this many of the objects retaining the most memory, overriding --count
+    #[structopt(long = "top-retainers", raw(global = "true"))]
+    top_retainers: Option<usize>,
 
-    let analysis = parse(opt.input.as_path(), subtree_root, class_name_only)?;
-    println!();
+    /// Only include object types matching this regular expression in the summary tables
+    #[structopt(long, raw(global = "true"))]
+    only: Option<Regex>,
 
-    println!("Object types using the most live memory:");
-    let (largest, rest) = analysis.live_stats_by_kind(opt.count);
-    print_largest(&largest, rest);
+    /// Leave out object types matching this regular expression from the summary tables
+    #[structopt(long, raw(global = "true"))]
+    exclude: Option<Regex>,
 
-    println!("\nObjects retaining the most live memory:");
-    let (largest, rest) = analysis.dominator_subtree_stats(opt.count);
-    print_largest(&largest, rest);
+    /// Leave out Ruby-internal object types (IMEMO, ICLASS, etc.) from the summary tables
+    #[structopt(long = "no-internal", raw(global = "true"))]
+    no_internal: bool,
 
-    println!("\nObject types retaining the most live memory:");
-    let (largest, rest) = analysis.retained_stats_by_kind(opt.count);
-    print_largest(&largest, rest);
+    /// Aggregate the by-type tables by class, top-level module, or allocation file, file:line, method or gem
+    #[structopt(
+        long = "group-by",
+        default_value = "kind",
+        raw(possible_values = "analyze::GROUP_BYS", global = "true")
+    )]
+    group_by: analyze::GroupBy,
 
-    if subtree_root.is_none() {
-        println!("\nObjects unreachable from root:");
-        let (largest, rest) = analysis.unreachable_stats_by_kind(opt.count);
-        print_largest(&largest, rest);
-    } else {
-        println!(
-            "\nObjects reachable from, but not dominated by, {}:",
-            subtree_root.unwrap(),
-        );
-        let (largest, rest) = analysis.unreachable_stats_by_kind(opt.count);
-        print_largest(&largest, rest);
-    }
+    /// Print every row of the summary tables, e.g. when piping them into other tools
+    #[structopt(long, raw(global = "true"))]
+    all: bool,
 
-    if let Some(output) = opt.flamegraph {
-        let lines = analysis.flamegraph_lines();
-        write_flamegraph(&lines, output.as_path())?;
-        println!("\nWrote {} nodes to {}", lines.len(), output.display());
-    }
+    /// Print exact byte counts rather than scaling them to KiB, MiB, etc.
+    #[structopt(long, raw(global = "true"))]
+    bytes: bool,
 
-    if let Some(output) = opt.folded {
-        let lines = analysis.flamegraph_lines();
-        write_folded(&lines, output.as_path())?;
-        println!("\nWrote {} nodes to {}", lines.len(), output.display());
-    }
+    /// Decimal places for scaled byte counts
+    #[structopt(long, default_value = "1", raw(global = "true"))]
+    precision: usize,
 
-    if let Some(output) = opt.dot {
-        let dom_graph = analysis.relevant_dominator_subgraph(opt.threshold.abs());
-        write_dot_file(&dom_graph, output.as_path())?;
-        println!(
-            "\nWrote {} nodes & {} edges to {}",
-            dom_graph.node_count(),
-            dom_graph.edge_count(),
-            output.display()
-        );
-    }
+    /// Leave out references from these categories of GC roots, e.g. machine_context
+    #[structopt(long = "ignore-roots", raw(use_delimiter = "true", global = "true"))]
+    ignore_roots: Vec<String>,
 
-    Ok(())
+    /// Only count the memory of objects allocated in this GC generation or later
+    #[structopt(long = "min-generation", raw(global = "true"))]
+    min_generation: Option<usize>,
+
+    /// Only count the memory of objects allocated in this GC generation or earlier
+    #[structopt(long = "max-generation", raw(global = "true"))]
+    max_generation: Option<usize>,
+
+    /// Count at least each object's slot size (in dumps from Ruby 3.1 or later) towards its self bytes, closer to what GC.stat counts
+    #[structopt(long = "slot-sizes", raw(global = "true"))]
+    slot_sizes: bool,
+
+    /// Count objects dumped without a memsize as taking up their slot (40 bytes before Ruby 3.1) rather than nothing
+    #[structopt(long = "missing-memsize", raw(global = "true"))]
+    missing_memsize: bool,
+
+    /// The dump only has objects allocated since some GC generation, from ObjectSpace.dump_all(since: n): keep references to older objects as placeholders
+    #[structopt(long = "partial", raw(global = "true"))]
+    partial: bool,
+
+    /// The app's Gemfile.lock, to tell exactly which gem allocated each object with --group-by gem
+    #[structopt(long = "gemfile-lock", parse(from_os_str), raw(global = "true"))]
+    gemfile_lock: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use rstest::rstest;
-    #[rstest]
-    #[case(false)]
-    #[case(true)]
-    fn whole_heap(#[case] class_name_only: bool) {
-        let analysis = parse(Path::new("test/heap.json"), None, class_name_only).unwrap();
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Print the object types & objects retaining the most memory (the default report)
+    #[structopt(name = "summary")]
+    Summary {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
 
-        let totals = analysis.dominated_totals();
-        assert_eq!(15472, totals.count);
-        assert_eq!(3439119, totals.bytes);
+        /// Filter to subtree rooted at object with this address
+        #[structopt(short, long, raw(alias = r#""root-address""#))]
+        root: Option<String>,
 
-        let (live_by_kind, _) = analysis.live_stats_by_kind(usize::max_value());
-        let (dead_by_kind, _) = analysis.unreachable_stats_by_kind(usize::max_value());
-        let (retained_by_kind, _) = analysis.retained_stats_by_kind(usize::max_value());
+        /// Print this many of the types & objects retaining the most memory
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
 
-        let live_strs = live_by_kind.iter().find(|(k, _)| *k == "String").unwrap().1;
-        let dead_strs = dead_by_kind.iter().find(|(k, _)| *k == "String").unwrap().1;
-        let retained_strs = retained_by_kind
-            .iter()
-            .find(|(k, _)| *k == "String")
-            .unwrap()
-            .1;
+        /// Directory for CSV output of the summary tables
+        #[structopt(long, parse(from_os_str))]
+        csv: Option<PathBuf>,
+    },
 
-        assert_eq!(9235, live_strs.count);
-        assert_eq!(1175, dead_strs.count);
-        assert_eq!(462583, live_strs.bytes);
-        assert_eq!(81839, dead_strs.bytes);
-        assert_eq!(9408, retained_strs.count);
-        assert_eq!(486278, retained_strs.bytes);
+    /// Write the relevant part of the dominator tree as a graphviz dot file
+    #[structopt(name = "dot")]
+    Dot {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
 
-        let dom_graph = analysis.relevant_dominator_subgraph(0.005);
-        assert_eq!(33, dom_graph.node_count());
-        assert_eq!(32, dom_graph.edge_count());
-    }
+        /// Filter to subtree rooted at object with this address
+        #[structopt(short, long, raw(alias = r#""root-address""#))]
+        root: Option<String>,
 
-    #[rstest]
-    #[case(false)]
-    #[case(true)]
-    fn subtree(#[case] class_name_only: bool) {
-        let analysis = parse(
-            Path::new("test/heap.json"),
-            Some(140204367666240),
-            class_name_only,
-        )
-        .unwrap();
+        /// Include nodes retaining at least this fraction of memory
+        #[structopt(short, long, default_value = "0.005")]
+        threshold: f64,
 
-        let totals = analysis.dominated_totals();
-        assert_eq!(25, totals.count);
-        assert_eq!(1053052, totals.bytes);
+        /// Path of the dot file to write, or "-" for stdout
+        #[structopt(short, long, default_value = "-", parse(from_os_str))]
+        output: PathBuf,
 
-        let (live_by_kind, _) = analysis.live_stats_by_kind(usize::max_value());
-        let (dead_by_kind, _) = analysis.unreachable_stats_by_kind(usize::max_value());
-        let (retained_by_kind, _) = analysis.retained_stats_by_kind(usize::max_value());
+        /// Instead of one dot file, write one per top-level dominator retaining at least the threshold to this directory
+        #[structopt(long = "split-dot", parse(from_os_str))]
+        split_dot: Option<PathBuf>,
 
-        let live_strs = live_by_kind.iter().find(|(k, _)| *k == "String").unwrap().1;
-        let dead_strs = dead_by_kind.iter().find(|(k, _)| *k == "String").unwrap().1;
-        let retained_strs = retained_by_kind
-            .iter()
-            .find(|(k, _)| *k == "String")
-            .unwrap()
-            .1;
+        /// Instead of a dot file, write an image rendered by graphviz, in the format given by its extension
+        #[structopt(
+            long,
+            parse(from_os_str),
+            raw(conflicts_with_all = r#"&["split_dot", "svg", "mermaid"]"#)
+        )]
+        render: Option<PathBuf>,
 
-        assert_eq!(4, live_strs.count);
-        assert_eq!(6604, dead_strs.count);
-        assert_eq!(208, live_strs.bytes);
-        assert_eq!(352283, dead_strs.bytes);
-        assert_eq!(4, retained_strs.count);
-        assert_eq!(208, retained_strs.bytes);
+        /// Instead of a dot file, write SVG rendered without graphviz
+        #[structopt(
+            long,
+            parse(from_os_str),
+            raw(conflicts_with_all = r#"&["split_dot", "mermaid"]"#)
+        )]
+        svg: Option<PathBuf>,
 
-        let dom_graph = analysis.relevant_dominator_subgraph(0.0);
-        assert_eq!(25, dom_graph.node_count());
-        assert_eq!(24, dom_graph.edge_count());
-    }
+        /// Instead of a dot file, write a Mermaid flowchart, for embedding in GitHub issues
+        #[structopt(long, parse(from_os_str), raw(conflicts_with = r#""split_dot""#))]
+        mermaid: Option<PathBuf>,
 
-    #[rstest]
-    #[case(false)]
-    #[case(true)]
-    fn flamegraph_lines_output(#[case] class_name_only: bool) {
-        let analysis = parse(Path::new("test/heap.json"), None, class_name_only).unwrap();
-        let frame_lines = analysis.flamegraph_lines();
-        let lines_with_memory_addresses = frame_lines.iter().filter(|&l| l.contains("0x")).count();
-        if class_name_only {
-            assert_eq!(lines_with_memory_addresses, 125);
-        } else {
-            assert_eq!(lines_with_memory_addresses, frame_lines.len());
-        }
+        /// Merge chains of objects that each dominate only one other into a single node
+        #[structopt(long = "collapse-chains")]
+        collapse_chains: bool,
+
+        /// Draw siblings of the same class that are relevant together as a single summary node
+        #[structopt(long = "aggregate-siblings")]
+        aggregate_siblings: bool,
+
+        /// Merge all instances of each class into a single node, for an overview of large heaps
+        #[structopt(long = "by-class")]
+        by_class: bool,
+
+        /// Only draw objects up to this many dominator tree levels below the root, noting what's pruned
+        #[structopt(long = "max-depth", raw(conflicts_with = r#""by_class""#))]
+        max_depth: Option<usize>,
+
+        /// Only draw this many of the objects retaining the most memory, folding the rest into an "other" node per parent
+        #[structopt(long = "max-nodes", raw(conflicts_with = r#""by_class""#))]
+        max_nodes: Option<usize>,
+
+        /// Group objects of namespaced classes into a box per top-level module
+        #[structopt(long = "cluster-namespaces")]
+        cluster_namespaces: bool,
+
+        /// Link each object to this URL, with "{address}" replaced by its address, e.g. for clickable SVG
+        #[structopt(long = "url-template")]
+        url_template: Option<String>,
+
+        /// Explain labels, arrows, colors and the threshold in a legend beside the graph
+        #[structopt(long)]
+        legend: bool,
+
+        /// Point arrows from objects to their retainers, like Chrome DevTools' retainers view
+        #[structopt(long = "invert-edges")]
+        invert_edges: bool,
+
+        /// Add each object's allocation site to its label (requires dumps taken with allocation tracing)
+        #[structopt(long = "label-locations")]
+        label_locations: bool,
+    },
+
+    /// Write a flamegraph of the dominator tree, with frame widths showing retained memory
+    #[structopt(name = "flamegraph")]
+    Flamegraph {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Filter to subtree rooted at object with this address
+        #[structopt(short, long, raw(alias = r#""root-address""#))]
+        root: Option<String>,
+
+        /// Path of the SVG (or folded stacks, speedscope or pprof) file to write
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+
+        /// Write folded stacks, for inferno-flamegraph or other tools, instead of SVG
+        #[structopt(long)]
+        folded: bool,
+
+        /// Write a speedscope JSON profile, to explore interactively at speedscope.app, instead of SVG
+        #[structopt(long, raw(conflicts_with = r#""folded""#))]
+        speedscope: bool,
+
+        /// Write a gzipped pprof profile, for `go tool pprof`, instead of SVG
+        #[structopt(long, raw(conflicts_with_all = r#"&["folded", "speedscope"]"#))]
+        pprof: bool,
+
+        /// Remove address from flamegraph labels
+        #[structopt(long = "class-name-only")]
+        class_name_only: bool,
+    },
+
+    /// Compare two heap dumps, printing the object types that grew the most
+    #[structopt(name = "diff")]
+    Diff {
+        /// Path to the earlier JSON heap dump
+        #[structopt(name = "BEFORE", parse(from_os_str))]
+        before: PathBuf,
+
+        /// Path to the later JSON heap dump
+        #[structopt(name = "AFTER", parse(from_os_str))]
+        after: PathBuf,
+
+        /// Print this many of the types with the most growth
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+
+        /// Compare by allocation site (requires dumps taken with allocation tracing)
+        #[structopt(long = "by-location")]
+        by_location: bool,
+    },
+
+    /// Estimate how much of a forked worker's heap is still shared with its parent
+    #[structopt(name = "cow")]
+    Cow {
+        /// Path to a JSON heap dump of the parent process, e.g. a preforking server's
+        #[structopt(name = "PARENT", parse(from_os_str))]
+        parent: PathBuf,
+
+        /// Path to a JSON heap dump of a child forked from it
+        #[structopt(name = "CHILD", parse(from_os_str))]
+        child: PathBuf,
+
+        /// Size of the Ruby VM's heap pages, which is 64 KiB from Ruby 3.3
+        #[structopt(long = "page-size", default_value = "16384")]
+        page_size: usize,
+
+        /// Print this many of the types copying the most pages
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Export the reference graph for use in other graph tools
+    #[structopt(name = "export")]
+    Export {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// GraphML output, with kind, self & retained bytes for each object
+        #[structopt(long, parse(from_os_str))]
+        graphml: Option<PathBuf>,
+
+        /// GEXF output for Gephi, with class, self & retained bytes and count for each object
+        #[structopt(long, parse(from_os_str))]
+        gexf: Option<PathBuf>,
+
+        /// Directory for nodes.csv & relationships.csv, for `neo4j-admin database import`
+        #[structopt(long, parse(from_os_str))]
+        neo4j: Option<PathBuf>,
+
+        /// Copy of the heap dump with retained_memsize & dominator fields added to each object
+        #[structopt(long, parse(from_os_str))]
+        jsonl: Option<PathBuf>,
+
+        /// Directory for objects.parquet & references.parquet (requires the `parquet` feature)
+        #[structopt(long, parse(from_os_str))]
+        parquet: Option<PathBuf>,
+
+        /// Chrome DevTools heap snapshot of the whole reference graph, regardless of the threshold
+        #[structopt(long, parse(from_os_str))]
+        heapsnapshot: Option<PathBuf>,
+
+        /// Include only objects retaining at least this fraction of memory
+        #[structopt(short, long, default_value = "0")]
+        threshold: f64,
+    },
+
+    /// Write a standalone HTML report with the summary tables and dominator tree
+    #[structopt(name = "report")]
+    Report {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Path of the HTML file to write
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+
+        /// Include nodes retaining at least this fraction of memory in the dominator tree
+        #[structopt(short, long, default_value = "0.005")]
+        threshold: f64,
+
+        /// Include this many of the types & objects retaining the most memory
+        #[structopt(short, long, default_value = "25")]
+        count: usize,
+    },
+
+    /// Write a standalone HTML page showing the dominator tree as an interactive graph
+    #[structopt(name = "graph")]
+    Graph {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Path of the HTML file to write
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+
+        /// Include nodes retaining at least this fraction of memory
+        #[structopt(short, long, default_value = "0.005")]
+        threshold: f64,
+    },
+
+    /// Write a standalone HTML treemap of the dominator tree, sized by retained memory
+    #[structopt(name = "treemap")]
+    Treemap {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Path of the HTML file to write
+        #[structopt(short, long, parse(from_os_str))]
+        output: PathBuf,
+
+        /// Include nodes retaining at least this fraction of memory
+        #[structopt(short, long, default_value = "0.0001")]
+        threshold: f64,
+    },
+
+    /// Print the dominator tree, from the largest dominators down
+    #[structopt(name = "tree")]
+    Tree {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Start from the object with this address rather than the root
+        #[structopt(short, long, raw(alias = r#""root-address""#))]
+        root: Option<String>,
+
+        /// Include nodes retaining at least this fraction of memory
+        #[structopt(short, long, default_value = "0.005")]
+        threshold: f64,
+
+        /// Include only this many levels below the root
+        #[structopt(long)]
+        depth: Option<usize>,
+    },
+
+    /// Track per-type growth across a series of heap dumps, oldest first
+    #[structopt(name = "trend")]
+    Trend {
+        /// Paths to JSON heap dumps, or directories of them (read in name order)
+        #[structopt(name = "INPUTS", parse(from_os_str), raw(required = "true"))]
+        inputs: Vec<PathBuf>,
+
+        /// Print this many of the types with the most growth
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print the most common references between object types, e.g. Hash -> String
+    #[structopt(name = "refs-matrix")]
+    RefsMatrix {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Filter to subtree rooted at object with this address
+        #[structopt(short, long, raw(alias = r#""root-address""#))]
+        root: Option<String>,
+
+        /// Print this many of the pairs of types with the most references between them
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print what retains the instances of an object type, by the type of their dominators
+    #[structopt(name = "retainers-of")]
+    RetainersOf {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Object type (class name, or e.g. "STRING" for objects without one)
+        #[structopt(name = "CLASS")]
+        kind: String,
+
+        /// Filter to subtree rooted at object with this address
+        #[structopt(short, long, raw(alias = r#""root-address""#))]
+        root: Option<String>,
+
+        /// Print this many of the types dominating the most instances
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print reference paths from the root to an object, with the type of each object on the way
+    #[structopt(name = "path")]
+    Path {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Address of the object to find paths to
+        #[structopt(
+            name = "ADDRESS",
+            raw(required_unless = r#""to""#, conflicts_with = r#""to""#)
+        )]
+        address: Option<String>,
+
+        /// Find paths starting from the object with this address rather than the root
+        #[structopt(long)]
+        from: Option<String>,
+
+        /// Address of the object to find paths to, instead of ADDRESS
+        #[structopt(long)]
+        to: Option<String>,
+
+        /// Print up to this many paths, each through a different referencing object
+        #[structopt(short = "n", long, default_value = "1")]
+        paths: usize,
+    },
+
+    /// Print the objects referencing an object, and optionally the objects referencing those
+    #[structopt(name = "retainers")]
+    Retainers {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Address of the object to find referrers of
+        #[structopt(name = "ADDRESS")]
+        address: String,
+
+        /// Include this many levels of referrers
+        #[structopt(long, default_value = "1")]
+        depth: usize,
+    },
+
+    /// Print a smallest set of references whose removal would make an object unreachable
+    #[structopt(name = "cut")]
+    Cut {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Address of the object
+        #[structopt(name = "ADDRESS")]
+        address: String,
+    },
+
+    /// Print everything an object dominates, by type, i.e. what releasing it would free
+    #[structopt(name = "dominated")]
+    Dominated {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Address of the object
+        #[structopt(name = "ADDRESS")]
+        address: String,
+
+        /// Print this many of the types using the most memory
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print everything known about one object, including what it references
+    #[structopt(name = "explain")]
+    Explain {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Address of the object
+        #[structopt(name = "ADDRESS")]
+        address: String,
+    },
+
+    /// Search the values of strings, printing the largest matches and what dominates them
+    #[structopt(name = "grep")]
+    Grep {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Regular expression to search string values for
+        #[structopt(name = "PATTERN")]
+        pattern: Regex,
+
+        /// Print this many of the largest matching strings
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print the arrays and hashes with the most elements, and what dominates them
+    #[structopt(name = "collections")]
+    Collections {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the largest collections
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print the reference cycles with the most members, and what they retain
+    #[structopt(name = "cycles")]
+    Cycles {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the largest cycles
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print how much of the heap is unreachable from the root and awaiting GC, and what it is
+    #[structopt(name = "unreachable")]
+    Unreachable {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the types and objects using the most memory
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print the references to addresses missing from the dump, which are otherwise ignored
+    #[structopt(name = "dangling")]
+    Dangling {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the types with the most dangling references
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+
+        /// List every dangling reference
+        #[structopt(short, long)]
+        list: bool,
+
+        /// Exit non-zero if there are any dangling references
+        #[structopt(long)]
+        strict: bool,
+    },
+
+    /// Print statistics about the shape of the reference graph and dominator tree
+    #[structopt(name = "metrics")]
+    Metrics {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+    },
+
+    /// Print the objects and types referenced by the most other objects
+    #[structopt(name = "fan-in")]
+    FanIn {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the most referenced objects and types
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print what the top retainers retain alone next to everything reachable from them
+    #[structopt(name = "shared")]
+    Shared {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the objects retaining the most memory
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print what each category of GC roots (VM, globals, C stack, etc.) retains
+    #[structopt(name = "roots")]
+    Roots {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+    },
+
+    /// Print the object types most likely to be leaking: old objects retaining a lot through few references
+    #[structopt(name = "suspects")]
+    Suspects {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the most suspect object types
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+
+        /// Only count old objects referenced by at most this many others
+        #[structopt(long = "max-referrers", default_value = "1")]
+        max_referrers: usize,
+    },
+
+    /// Print IMEMO memory by subtype, e.g. method entries or instruction sequences
+    #[structopt(name = "imemo")]
+    Imemo {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the IMEMO subtypes using & retaining the most memory
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print how plain objects use shapes, for dumps from Ruby 3.2 or later
+    #[structopt(name = "shapes")]
+    Shapes {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the shapes with the most objects
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print how full the heap pages holding objects are, and memory stranded on sparse ones
+    #[structopt(name = "pages")]
+    Pages {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Size of the Ruby VM's heap pages, which is 64 KiB from Ruby 3.3
+        #[structopt(long = "page-size", default_value = "16384")]
+        page_size: usize,
+    },
+
+    /// Print nearly empty heap pages and the objects, pinned or not, keeping them in use
+    #[structopt(name = "compaction")]
+    Compaction {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Size of the Ruby VM's heap pages, which is 64 KiB from Ruby 3.3
+        #[structopt(long = "page-size", default_value = "16384")]
+        page_size: usize,
+
+        /// Consider pages with less than this percentage of their slots in use
+        #[structopt(long = "max-occupancy", default_value = "10")]
+        max_occupancy: usize,
+
+        /// Print this many of the objects on those pages, and of their types
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print live objects by the GC generation they were allocated in, for traced dumps
+    #[structopt(name = "generations")]
+    Generations {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Group this many consecutive generations into each row
+        #[structopt(long, default_value = "1")]
+        bucket: usize,
+    },
+
+    /// Print what each thread retains, e.g. in a multithreaded server
+    #[structopt(name = "threads")]
+    Threads {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the threads retaining the most memory
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print the largest individual objects by their own size, without dominator analysis
+    #[structopt(name = "largest")]
+    Largest {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the largest objects
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print the string values with the most memory taken up by duplicate copies
+    #[structopt(name = "dup-strings")]
+    DupStrings {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the most wasteful values
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print how strings are stored (frozen, shared, embedded) and what freezing duplicates could save
+    #[structopt(name = "strings")]
+    Strings {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+    },
+
+    /// Print how many strings are interned, and the largest and newest of them
+    #[structopt(name = "fstrings")]
+    Fstrings {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the largest & newest interned strings
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+    },
+
+    /// Print how many symbols there are, the largest and newest, and warn if there are too many
+    #[structopt(name = "symbols")]
+    Symbols {
+        /// Path to JSON heap dump file to process (- for standard input), or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Print this many of the largest and most recently created symbols
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+
+        /// Warn when there are more symbols than this
+        #[structopt(long = "max-symbols", default_value = "200000")]
+        max_symbols: usize,
+    },
+
+    /// Exit non-zero if retained memory grew beyond a baseline dump by more than a margin
+    #[structopt(name = "check")]
+    Check {
+        /// Path to JSON heap dump file to check, or to each of the files it's split into
+        #[structopt(name = "INPUT", parse(from_os_str), raw(required = "true"))]
+        input: Vec<PathBuf>,
+
+        /// Path to the baseline JSON heap dump
+        #[structopt(short, long, parse(from_os_str))]
+        baseline: PathBuf,
+
+        /// Allowed growth in total and per-type retained memory, e.g. "10%"
+        #[structopt(
+            short,
+            long = "max-growth",
+            default_value = "10%",
+            parse(try_from_str = "parse_percentage")
+        )]
+        max_growth: f64,
+
+        /// Ignore types whose retained memory grew by fewer than this many bytes. Types absent from the baseline grew from nothing, so without this any new type would count as a regression, however small
+        #[structopt(long = "min-bytes", default_value = "1048576")]
+        min_bytes: usize,
+    },
+
+    /// Dump the heap of a running Ruby process with rbtrace, and print its summary report, or the trend across several dumps
+    #[structopt(name = "capture")]
+    Capture {
+        /// ID of the process to dump, which must have required rbtrace
+        #[structopt(short, long)]
+        pid: u32,
+
+        /// Directory for the process to write its dump to
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+
+        /// Path to the rbtrace executable
+        #[structopt(long, default_value = "rbtrace", parse(from_os_str))]
+        rbtrace: PathBuf,
+
+        /// Give up on the dump after this many seconds
+        #[structopt(long, default_value = "600")]
+        timeout: u64,
+
+        /// Number of dumps to take, printing the trend across them rather than a summary if more than one
+        #[structopt(long, default_value = "1")]
+        count: usize,
+
+        /// Seconds between the start of one dump and the next
+        #[structopt(long, default_value = "60")]
+        interval: u64,
+
+        /// Print this many of the types & objects retaining the most memory
+        #[structopt(short = "n", long = "rows", default_value = "10")]
+        rows: usize,
+    },
+
+    /// Print the summary report of each heap dump written to a directory, and the changes since the one before
+    #[structopt(name = "watch")]
+    Watch {
+        /// Directory to watch for heap dumps, including those already in it
+        #[structopt(name = "DIR", parse(from_os_str))]
+        dir: PathBuf,
+
+        /// Print this many of the types & objects retaining the most memory
+        #[structopt(short, long, default_value = "10")]
+        count: usize,
+
+        /// Seconds between checks for new dumps
+        #[structopt(long, default_value = "10")]
+        poll: u64,
+
+        /// CSV file to append live memory by object type in each dump to, with the growth since the one before
+        #[structopt(long, parse(from_os_str))]
+        csv: Option<PathBuf>,
+    },
+}
+
+fn parse_root(root: Option<String>) -> Result<Option<usize>> {
+    Ok(root.as_deref().map(parse::parse_address).transpose()?)
+}
+
+fn parse_percentage(s: &str) -> std::result::Result<f64, String> {
+    s.trim_end_matches('%')
+        .parse::<f64>()
+        .map(|p| p / 100.0)
+        .map_err(|e| format!("Invalid percentage {:?}: {}", s, e))
+}
+
+fn expand_inputs(inputs: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            let mut entries = std::fs::read_dir(&input)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<Vec<PathBuf>>>()?;
+            entries.retain(|p| p.is_file());
+            entries.sort();
+            paths.extend(entries);
+        } else {
+            paths.push(input);
+        }
+    }
+    Ok(paths)
+}
+
+// Writes a report, set off from the banner & timings by a blank line when
+// printed as a table
+fn write_report<T: report::Report, W: Write>(
+    report: &T,
+    mut out: W,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    if format == report::Format::Table {
+        writeln!(out)?;
+    }
+    report::write(report, &mut out, format, units)?;
+    Ok(())
+}
+
+fn write_summary<W: Write>(
+    out: W,
+    analysis: &analyze::Analysis,
+    subtree_root: Option<usize>,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+    csv: Option<PathBuf>,
+) -> Result<()> {
+    let report = report::SummaryReport::new(analysis, subtree_root, selection);
+    write_report(&report, out, format, units)?;
+
+    if let Some(output) = csv {
+        let written = report.write_csv(output.as_path())?;
+        eprintln!("\nWrote {} tables to {}", written.len(), output.display());
+    }
+    Ok(())
+}
+
+// The format the flamegraph subcommand writes the dominator tree's stacks in
+#[derive(Clone, Copy)]
+enum Profile {
+    Flamegraph,
+    Folded,
+    Speedscope,
+    Pprof,
+}
+
+fn write_profile(
+    analysis: &analyze::Analysis,
+    input: &[PathBuf],
+    output: &Path,
+    profile: Profile,
+    class_name_only: bool,
+) -> Result<()> {
+    match profile {
+        Profile::Flamegraph | Profile::Folded => {
+            let lines = analysis.flamegraph_lines();
+            if let Profile::Folded = profile {
+                write_folded(&lines, output)?;
+            } else {
+                write_flamegraph(&lines, output)?;
+            }
+            eprintln!("\nWrote {} nodes to {}", lines.len(), output.display());
+        }
+        Profile::Speedscope => {
+            let name = format!("Retained memory: {}", describe(input));
+            let profile = speedscope::profile(analysis, &name, class_name_only);
+            speedscope::write_speedscope(&profile, output)?;
+            eprintln!(
+                "\nWrote {} samples to {}",
+                profile.profile().samples.len(),
+                output.display()
+            );
+        }
+        Profile::Pprof => {
+            let profile = pprof::profile(analysis, class_name_only);
+            pprof::write_pprof(&profile, output)?;
+            eprintln!("\nWrote pprof profile to {}", output.display());
+        }
+    }
+    Ok(())
+}
+
+// Writes the relevant part of the dominator tree with `write`
+fn write_dominator_graph<F: Fn(&ReferenceGraph, &Path) -> Result<()>>(
+    analysis: &analyze::Analysis,
+    threshold: f64,
+    options: analyze::GraphOptions,
+    units: Units,
+    output: &Path,
+    write: F,
+) -> Result<()> {
+    let dom_graph = analysis.dominator_graph(threshold, units, options);
+    write(&dom_graph, output)?;
+    eprintln!(
+        "\nWrote {} nodes & {} edges to {}",
+        dom_graph.node_count(),
+        dom_graph.edge_count(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn run_summary(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    root: Option<String>,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+    csv: Option<PathBuf>,
+) -> Result<()> {
+    let subtree_root = parse_root(root)?;
+    let analysis = parse(input, subtree_root, false, parse_options)?;
+    write_summary(
+        std::io::stdout(),
+        &analysis,
+        subtree_root,
+        selection,
+        format,
+        units,
+        csv,
+    )
+}
+
+// Takes a dump, or several at an interval, each summarized as soon as it's
+// taken rather than once they all are
+fn run_capture(
+    pid: u32,
+    parse_options: &ParseOptions,
+    options: &capture::Options,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let (count, interval) = (options.count, options.interval);
+    let start = std::time::Instant::now();
+    let mut summaries = Vec::with_capacity(count);
+    for i in 0..count.max(1) {
+        if let Some(wait) = (interval * i as u32).checked_sub(start.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        eprintln!("Dumping the heap of process {}", pid);
+        let path = capture::capture(pid, options)?;
+        eprintln!("Wrote heap dump to {}", path.display());
+        if count <= 1 {
+            return run_summary(&[path], parse_options, None, selection, format, units, None);
+        }
+        summaries.push(diff::Summary::new(&parse(
+            &[path],
+            None,
+            false,
+            parse_options,
+        )?));
+    }
+
+    let trend = trend::Trend::new(&summaries);
+    write_report(
+        &report::TrendReport::new(&trend, selection.top_types),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_watch(
+    dir: &Path,
+    parse_options: &ParseOptions,
+    poll: Duration,
+    selection: report::Selection,
+    csv: Option<PathBuf>,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let mut watcher = watch::Watcher::new(dir)?;
+    if let Some(csv) = &csv {
+        watcher.ignore(csv);
+    }
+    let mut previous: Option<diff::Summary> = None;
+    loop {
+        for path in watcher.poll()? {
+            eprintln!("\nAnalyzing {}", path.display());
+            // A file that isn't a (complete) dump shouldn't stop the watch,
+            // and is tried again if it changes
+            let analysis = match parse(std::slice::from_ref(&path), None, false, parse_options) {
+                Ok(analysis) => analysis,
+                Err(e) => {
+                    eprintln!("Skipping {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            write_summary(
+                std::io::stdout(),
+                &analysis,
+                None,
+                selection.clone(),
+                format,
+                units,
+                None,
+            )?;
+
+            let summary = diff::Summary::new(&analysis);
+            if let Some(previous) = &previous {
+                write_report(
+                    &report::DiffReport::new(
+                        &diff::diff(previous, &summary),
+                        selection.top_types,
+                        false,
+                    ),
+                    std::io::stdout(),
+                    format,
+                    units,
+                )?;
+            }
+            if let Some(csv) = &csv {
+                watch::append_csv(csv, &path, &summary, previous.as_ref())?;
+            }
+            previous = Some(summary);
+        }
+        std::thread::sleep(poll);
+    }
+}
+
+// Where the dot subcommand writes to, and in which format
+#[derive(Clone, Copy)]
+enum DotOutput<'a> {
+    File(&'a Path),
+    // A file per top-level dominator in this directory
+    Split(&'a Path),
+    // An image rendered by graphviz
+    Render(&'a Path),
+    Svg(&'a Path),
+    Mermaid(&'a Path),
+}
+
+impl DotOutput<'_> {
+    // Whether the graph is drawn by dot, whose labels have the full values
+    // of strings, read from the dump again
+    fn dot(self) -> bool {
+        !matches!(self, DotOutput::Svg(_) | DotOutput::Mermaid(_))
+    }
+}
+
+// How the dot subcommand simplifies the dominator tree, and draws it
+struct DotOptions<'a> {
+    graph: analyze::GraphOptions,
+    dot: dot::Options<'a>,
+}
+
+fn run_dot(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    root: Option<String>,
+    threshold: f64,
+    options: DotOptions,
+    output: DotOutput,
+    units: Units,
+) -> Result<()> {
+    let spooled = if output.dot() {
+        Some(input::spool(input)?)
+    } else {
+        None
+    };
+    let input = spooled.as_ref().map_or(input, input::Spooled::paths);
+    let analysis = parse(input, parse_root(root)?, false, parse_options)?;
+    write_graph(&analysis, input, threshold, &options, output, units)
+}
+
+fn write_graph(
+    analysis: &analyze::Analysis,
+    input: &[PathBuf],
+    threshold: f64,
+    options: &DotOptions,
+    output: DotOutput,
+    units: Units,
+) -> Result<()> {
+    let path = match output {
+        DotOutput::File(path)
+        | DotOutput::Split(path)
+        | DotOutput::Render(path)
+        | DotOutput::Svg(path)
+        | DotOutput::Mermaid(path) => path,
+    };
+    write_dominator_graph(
+        analysis,
+        threshold.abs(),
+        options.graph,
+        units,
+        path,
+        |g, f| {
+            if !output.dot() {
+                return match output {
+                    DotOutput::Svg(_) => Ok(svg::write_svg(g, f)?),
+                    _ => Ok(mermaid::write_mermaid(g, f)?),
+                };
+            }
+            let values = graph_string_values(input, g)?;
+            let dot_options = dot::Options {
+                values: Some(&values),
+                ..options.dot
+            };
+            match output {
+                DotOutput::Split(_) => write_split_dot_files(g, dot_options, f),
+                DotOutput::Render(_) => render_dot_file(g, dot_options, f),
+                _ => write_dot_file(g, dot_options, f),
+            }
+        },
+    )
+}
+
+fn run_flamegraph(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    root: Option<String>,
+    output: &Path,
+    profile: Profile,
+    class_name_only: bool,
+) -> Result<()> {
+    let analysis = parse(input, parse_root(root)?, class_name_only, parse_options)?;
+    write_profile(&analysis, input, output, profile, class_name_only)
+}
+
+fn run_check(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    baseline: &Path,
+    max_growth: f64,
+    min_bytes: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let baseline = diff::Summary::new(&parse(
+        &[baseline.to_path_buf()],
+        None,
+        false,
+        parse_options,
+    )?);
+    let current = diff::Summary::new(&parse(input, None, false, parse_options)?);
+    let regressions = diff::check(&baseline, &current, max_growth, min_bytes);
+
+    let report = report::CheckReport::new(&regressions, max_growth);
+    write_report(&report, std::io::stdout(), format, units)?;
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(
+    analysis: &analyze::Analysis,
+    threshold_bytes: usize,
+    dir: &Path,
+) -> Result<(usize, usize)> {
+    Ok(columnar::write_parquet(analysis, threshold_bytes, dir)?)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_: &analyze::Analysis, _: usize, _: &Path) -> Result<(usize, usize)> {
+    Err("Parquet output requires building reap with `--features parquet`".into())
+}
+
+// Where the export subcommand writes to, in each format asked for
+#[derive(Default)]
+struct Exports {
+    graphml: Option<PathBuf>,
+    gexf: Option<PathBuf>,
+    neo4j: Option<PathBuf>,
+    parquet: Option<PathBuf>,
+    jsonl: Option<PathBuf>,
+    heapsnapshot: Option<PathBuf>,
+}
+
+fn run_export(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    exports: Exports,
+    threshold: f64,
+) -> Result<()> {
+    // The dump is read again to annotate it
+    let spooled = exports
+        .jsonl
+        .as_ref()
+        .map(|_| input::spool(input))
+        .transpose()?;
+    let input = spooled.as_ref().map_or(input, input::Spooled::paths);
+    let analysis = parse(input, None, false, parse_options)?;
+    write_exports(&analysis, input, exports, threshold)
+}
+
+fn write_exports(
+    analysis: &analyze::Analysis,
+    input: &[PathBuf],
+    exports: Exports,
+    threshold: f64,
+) -> Result<()> {
+    let threshold_bytes = analysis.threshold_bytes(threshold.abs());
+
+    if let Some(output) = exports.graphml {
+        let (nodes, edges) = graphml::write_graphml(analysis, threshold_bytes, output.as_path())?;
+        eprintln!(
+            "\nWrote {} nodes & {} edges to {}",
+            nodes,
+            edges,
+            output.display()
+        );
+    }
+
+    if let Some(output) = exports.gexf {
+        let (nodes, edges) = gexf::write_gexf(analysis, threshold_bytes, output.as_path())?;
+        eprintln!(
+            "\nWrote {} nodes & {} edges to {}",
+            nodes,
+            edges,
+            output.display()
+        );
+    }
+
+    if let Some(output) = exports.neo4j {
+        let (nodes, edges) = neo4j::write_neo4j(analysis, threshold_bytes, output.as_path())?;
+        eprintln!(
+            "\nWrote {} nodes & {} relationships to {}",
+            nodes,
+            edges,
+            output.display()
+        );
+    }
+
+    if let Some(output) = exports.parquet {
+        let (objects, references) = write_parquet(analysis, threshold_bytes, output.as_path())?;
+        eprintln!(
+            "\nWrote {} objects & {} references to {}",
+            objects,
+            references,
+            output.display()
+        );
+    }
+
+    if let Some(output) = exports.jsonl {
+        let annotated = annotate::write_annotated(analysis, input::open(input)?, output.as_path())?;
+        eprintln!(
+            "\nWrote {} annotated objects to {}",
+            annotated,
+            output.display()
+        );
+    }
+
+    if let Some(output) = exports.heapsnapshot {
+        let snapshot = heapsnapshot::snapshot(analysis);
+        heapsnapshot::write_heapsnapshot(&snapshot, output.as_path())?;
+        eprintln!("\nWrote heap snapshot to {}", output.display());
+    }
+
+    Ok(())
+}
+
+fn run_report(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    output: &Path,
+    threshold: f64,
+    selection: report::Selection,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    let report = report::SummaryReport::new(&analysis, None, selection);
+    let tree = analysis.dominator_tree(threshold.abs());
+
+    let title = format!("reap: {}", describe(input));
+    html::write_report(&report, &tree, &title, units, output)?;
+    eprintln!("\nWrote report to {}", output.display());
+    Ok(())
+}
+
+// The input's file names, for titles
+fn describe(input: &[PathBuf]) -> String {
+    input
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn run_treemap(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    output: &Path,
+    threshold: f64,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    let tree = analysis.dominator_tree(threshold.abs());
+
+    let title = format!("reap: {}", describe(input));
+    html::write_treemap(&tree, &title, output)?;
+    eprintln!("\nWrote treemap to {}", output.display());
+    Ok(())
+}
+
+fn run_tree(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    root: Option<String>,
+    threshold: f64,
+    depth: Option<usize>,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, parse_root(root)?, false, parse_options)?;
+    let mut tree = analysis.dominator_tree(threshold.abs());
+    if let Some(depth) = depth {
+        tree.truncate(depth);
+    }
+
+    write_report(&tree, std::io::stdout(), format, units)
+}
+
+fn run_graph(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    output: &Path,
+    threshold: f64,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    let tree = analysis.dominator_tree(threshold.abs());
+
+    let title = format!("reap: {}", describe(input));
+    html::write_graph(&tree, &title, output)?;
+    eprintln!("\nWrote graph to {}", output.display());
+    Ok(())
+}
+
+fn run_trend(
+    inputs: Vec<PathBuf>,
+    parse_options: &ParseOptions,
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let inputs = expand_inputs(inputs)?;
+
+    let mut summaries = Vec::with_capacity(inputs.len());
+    for input in inputs.iter() {
+        summaries.push(diff::Summary::new(&parse(
+            std::slice::from_ref(input),
+            None,
+            false,
+            parse_options,
+        )?));
+    }
+    let trend = trend::Trend::new(&summaries);
+    write_report(
+        &report::TrendReport::new(&trend, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_refs_matrix(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    root: Option<String>,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, parse_root(root)?, false, parse_options)?;
+    write_report(
+        &report::RefsMatrixReport::new(&analysis, selection),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_retainers_of(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    kind: &str,
+    root: Option<String>,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, parse_root(root)?, false, parse_options)?;
+    write_report(
+        &report::RetainersOfReport::new(&analysis, kind, selection),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+// Parses the dump, and finds the reachable object at `address` in it
+fn parse_with_target(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    address: &str,
+) -> Result<(analyze::Analysis, analyze::Index)> {
+    let address = parse::parse_address(address)?;
+    let analysis = parse(input, None, false, parse_options)?;
+    let target = find_reachable(&analysis, address)?;
+    Ok((analysis, target))
+}
+
+fn find_reachable(analysis: &analyze::Analysis, address: usize) -> Result<analyze::Index> {
+    analysis
+        .index_of(address)
+        .ok_or_else(|| format!("No object at {:#x} is reachable from the root", address).into())
+}
+
+fn run_retainers(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    address: &str,
+    depth: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let (analysis, target) = parse_with_target(input, parse_options, address)?;
+    write_report(
+        &analysis.referrer_tree(target, depth),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_cut(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    address: &str,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let (analysis, target) = parse_with_target(input, parse_options, address)?;
+    write_report(
+        &report::CutReport::new(&analysis, target),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_dominated(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    address: String,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let address = parse::parse_address(&address)?;
+    let analysis = parse(input, Some(address), false, parse_options)?;
+    write_report(
+        &report::DominatedReport::new(&analysis, selection),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_explain(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    address: &str,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    // The dump is read twice, so it's copied once if reading it again isn't cheap
+    let spooled = input::spool(input)?;
+    let input = spooled.paths();
+    let (analysis, target) = parse_with_target(input, parse_options, address)?;
+    let record = parse::find_object(
+        input::open(input)?,
+        analysis.dominated_subgraph()[target].address,
+    )?;
+    write_report(
+        &report::ExplainReport::new(&analysis, target, record.as_ref()),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_grep(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    pattern: &Regex,
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    // The dump is read twice, so it's copied once if reading it again isn't cheap
+    let spooled = input::spool(input)?;
+    let input = spooled.paths();
+    let found = parse::find_strings(input::open(input)?, pattern)?;
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::GrepReport::new(&analysis, pattern.as_str(), found, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_largest(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    count: usize,
+    filter: &analyze::KindFilter,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let (_, graph) = parse::parse(
+        input::open(input)?,
+        false,
+        &parse_options.ignored_roots,
+        parse_options.parsing,
+    )?;
+    write_report(
+        &report::LargestReport::new(&graph, count, filter),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_dup_strings(
+    input: &[PathBuf],
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let strings = parse::string_values(input::open(input)?)?;
+    write_report(
+        &report::DuplicatesReport::new(strings, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_strings(input: &[PathBuf], format: report::Format, units: Units) -> Result<()> {
+    let strings = parse::find_all_strings(input::open(input)?)?;
+    write_report(
+        &report::StringsReport::new(&strings),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_fstrings(
+    input: &[PathBuf],
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let strings = parse::find_all_strings(input::open(input)?)?;
+    write_report(
+        &report::FstringsReport::new(&strings, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_symbols(
+    input: &[PathBuf],
+    count: usize,
+    max_symbols: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let symbols = parse::find_symbols(input::open(input)?)?;
+    write_report(
+        &report::SymbolsReport::new(symbols, count, max_symbols),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_collections(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    // The dump is read twice, so it's copied once if reading it again isn't cheap
+    let spooled = input::spool(input)?;
+    let input = spooled.paths();
+    let found = parse::find_collections(input::open(input)?)?;
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::CollectionsReport::new(&analysis, found, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_cycles(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::CyclesReport::new(&analysis, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_unreachable(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::UnreachableReport::new(&analysis, selection),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_dangling(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    count: usize,
+    list: bool,
+    strict: bool,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let (_, graph, dangling) = parse::parse_with_dangling(
+        input::open(input)?,
+        false,
+        &parse_options.ignored_roots,
+        parse_options.parsing,
+    )?;
+    let report = report::DanglingReport::new(&graph, dangling, count, list);
+    write_report(&report, std::io::stdout(), format, units)?;
+    if strict && report.references > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run_metrics(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::MetricsReport::new(&analysis),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_fan_in(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::FanInReport::new(&analysis, selection),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_shared(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::SharingReport::new(&analysis, selection),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_roots(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::RootsReport::new(&analysis),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_suspects(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    max_referrers: usize,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::SuspectsReport::new(&analysis, max_referrers, selection),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_imemo(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    // The dump is read twice, so it's copied once if reading it again isn't cheap
+    let spooled = input::spool(input)?;
+    let input = spooled.paths();
+    let found = parse::find_imemo_types(input::open(input)?)?;
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::ImemoReport::new(&analysis, found, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_shapes(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    // The dump is read twice, so it's copied once if reading it again isn't cheap
+    let spooled = input::spool(input)?;
+    let input = spooled.paths();
+    let found = parse::find_shapes(input::open(input)?)?;
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::ShapesReport::new(&analysis, found, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_pages(
+    input: &[PathBuf],
+    page_size: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let slots = parse::find_slots(input::open(input)?)?;
+    write_report(
+        &report::PagesReport::new(&pages::pages(&slots, page_size)),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_compaction(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    page_size: usize,
+    max_occupancy: usize,
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    // The dump is read twice, so it's copied once if reading it again isn't cheap
+    let spooled = input::spool(input)?;
+    let input = spooled.paths();
+    let slots = parse::find_slots(input::open(input)?)?;
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::CompactionReport::new(
+            &analysis,
+            &pages::pages(&slots, page_size),
+            max_occupancy,
+            count,
+        ),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_generations(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    bucket: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::GenerationsReport::new(&analysis, bucket),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_threads(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    selection: report::Selection,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let analysis = parse(input, None, false, parse_options)?;
+    write_report(
+        &report::ThreadsReport::new(&analysis, selection),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_path(
+    input: &[PathBuf],
+    parse_options: &ParseOptions,
+    from: Option<String>,
+    to: &str,
+    paths: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let from = from.as_deref().map(parse::parse_address).transpose()?;
+    let to = parse::parse_address(to)?;
+    let analysis = parse(input, None, false, parse_options)?;
+    let source = match from {
+        Some(address) => find_reachable(&analysis, address)?,
+        None => analysis.root(),
+    };
+    let target = find_reachable(&analysis, to)?;
+    write_report(
+        &report::PathReport::new(&analysis, source, target, paths),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+// Fingerprints of an analysis's objects by address
+fn fingerprints(analysis: &analyze::Analysis) -> HashMap<usize, u64> {
+    analysis
+        .dominated_subgraph()
+        .raw_nodes()
+        .iter()
+        .filter(|node| !node.weight.is_root())
+        .map(|node| (node.weight.address, node.weight.fingerprint()))
+        .collect()
+}
+
+fn run_cow(
+    parent: &Path,
+    child: &Path,
+    parse_options: &ParseOptions,
+    page_size: usize,
+    count: usize,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    // Parse one dump at a time, keeping only fingerprints of the parent's
+    // objects. Each is read twice, so is copied once if reading it again
+    // isn't cheap.
+    let parent = input::spool(&[parent.to_path_buf()])?;
+    let parent_pages = pages::pages(&parse::find_slots(input::open(parent.paths())?)?, page_size);
+    let fingerprints = fingerprints(&parse(parent.paths(), None, false, parse_options)?);
+    drop(parent);
+    let child = input::spool(&[child.to_path_buf()])?;
+    let child_pages = pages::pages(&parse::find_slots(input::open(child.paths())?)?, page_size);
+    let analysis = parse(child.paths(), None, false, parse_options)?;
+    write_report(
+        &report::CowReport::new(&fingerprints, &parent_pages, &analysis, &child_pages, count),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn run_diff(
+    before: &Path,
+    after: &Path,
+    parse_options: &ParseOptions,
+    count: usize,
+    by_location: bool,
+    format: report::Format,
+    units: Units,
+) -> Result<()> {
+    let summarize = if by_location {
+        diff::Summary::by_location
+    } else {
+        diff::Summary::new
+    };
+
+    // Parse one dump at a time, keeping only the per-kind summary of each
+    let before = summarize(&parse(&[before.to_path_buf()], None, false, parse_options)?);
+    let after = summarize(&parse(&[after.to_path_buf()], None, false, parse_options)?);
+    let diff = diff::diff(&before, &after);
+    write_report(
+        &report::DiffReport::new(&diff, count, by_location),
+        std::io::stdout(),
+        format,
+        units,
+    )
+}
+
+fn main() -> Result<()> {
+    const VERSION: &str = env!("CARGO_PKG_VERSION");
+    eprintln!("reap v{}", VERSION);
+
+    let opt = Opt::from_args();
+    let units = Units::new(opt.bytes, opt.precision);
+    let parse_options = ParseOptions {
+        ignored_roots: opt.ignore_roots.clone(),
+        parsing: parse::Options {
+            slot_sizes: opt.slot_sizes,
+            missing_memsize: opt.missing_memsize,
+            partial: opt.partial,
+        },
+        analysis: analyze::Options {
+            generations: analyze::GenerationRange {
+                min: opt.min_generation,
+                max: opt.max_generation,
+            },
+            gemfile_lock: opt
+                .gemfile_lock
+                .as_deref()
+                .map(gems::Lockfile::open)
+                .transpose()?,
+        },
+    };
+
+    // Table sizes default to each command's --count
+    let (all, top_types, top_retainers, sort, group_by) = (
+        opt.all,
+        opt.top_types,
+        opt.top_retainers,
+        opt.sort,
+        opt.group_by,
+    );
+    let filter = analyze::KindFilter {
+        only: opt.only,
+        exclude: opt.exclude,
+        no_internal: opt.no_internal,
+    };
+    let selection = |count: usize| {
+        let limit = |top: Option<usize>| {
+            if all {
+                usize::MAX
+            } else {
+                top.unwrap_or(count)
+            }
+        };
+        report::Selection {
+            top_types: limit(top_types),
+            top_retainers: limit(top_retainers),
+            sort,
+            filter: filter.clone(),
+            group_by,
+        }
+    };
+
+    if let Some(command) = opt.command {
+        return match command {
+            Command::Summary {
+                input,
+                root,
+                count,
+                csv,
+            } => run_summary(
+                &input,
+                &parse_options,
+                root,
+                selection(count),
+                opt.format,
+                units,
+                csv,
+            ),
+            Command::Dot {
+                input,
+                root,
+                threshold,
+                output,
+                split_dot,
+                render,
+                svg,
+                mermaid,
+                collapse_chains,
+                aggregate_siblings,
+                by_class,
+                max_depth,
+                max_nodes,
+                cluster_namespaces,
+                url_template,
+                legend,
+                invert_edges,
+                label_locations,
+            } => run_dot(
+                &input,
+                &parse_options,
+                root,
+                threshold,
+                DotOptions {
+                    graph: analyze::GraphOptions {
+                        collapse_chains,
+                        aggregate_siblings,
+                        by_class,
+                        max_depth,
+                        max_nodes,
+                    },
+                    dot: dot::Options {
+                        cluster_namespaces,
+                        url: url_template.as_deref(),
+                        legend: if legend { Some(threshold.abs()) } else { None },
+                        invert_edges,
+                        locations: label_locations,
+                        ..Default::default()
+                    },
+                },
+                match (&split_dot, &render, &svg, &mermaid) {
+                    (Some(dir), _, _, _) => DotOutput::Split(dir),
+                    (_, Some(file), _, _) => DotOutput::Render(file),
+                    (_, _, Some(file), _) => DotOutput::Svg(file),
+                    (_, _, _, Some(file)) => DotOutput::Mermaid(file),
+                    _ => DotOutput::File(&output),
+                },
+                units,
+            ),
+            Command::Flamegraph {
+                input,
+                root,
+                output,
+                folded,
+                speedscope,
+                pprof,
+                class_name_only,
+            } => run_flamegraph(
+                &input,
+                &parse_options,
+                root,
+                output.as_path(),
+                match (folded, speedscope, pprof) {
+                    (true, _, _) => Profile::Folded,
+                    (_, true, _) => Profile::Speedscope,
+                    (_, _, true) => Profile::Pprof,
+                    _ => Profile::Flamegraph,
+                },
+                class_name_only,
+            ),
+            Command::Cow {
+                parent,
+                child,
+                page_size,
+                count,
+            } => run_cow(
+                parent.as_path(),
+                child.as_path(),
+                &parse_options,
+                page_size,
+                if all { usize::MAX } else { count },
+                opt.format,
+                units,
+            ),
+            Command::Diff {
+                before,
+                after,
+                count,
+                by_location,
+            } => run_diff(
+                before.as_path(),
+                after.as_path(),
+                &parse_options,
+                count,
+                by_location,
+                opt.format,
+                units,
+            ),
+            Command::Export {
+                input,
+                graphml,
+                gexf,
+                neo4j,
+                parquet,
+                jsonl,
+                heapsnapshot,
+                threshold,
+            } => run_export(
+                &input,
+                &parse_options,
+                Exports {
+                    graphml,
+                    gexf,
+                    neo4j,
+                    parquet,
+                    jsonl,
+                    heapsnapshot,
+                },
+                threshold,
+            ),
+            Command::Report {
+                input,
+                output,
+                threshold,
+                count,
+            } => run_report(
+                &input,
+                &parse_options,
+                output.as_path(),
+                threshold,
+                selection(count),
+                units,
+            ),
+            Command::Graph {
+                input,
+                output,
+                threshold,
+            } => run_graph(&input, &parse_options, output.as_path(), threshold),
+            Command::Treemap {
+                input,
+                output,
+                threshold,
+            } => run_treemap(&input, &parse_options, output.as_path(), threshold),
+            Command::Tree {
+                input,
+                root,
+                threshold,
+                depth,
+            } => run_tree(
+                &input,
+                &parse_options,
+                root,
+                threshold,
+                depth,
+                opt.format,
+                units,
+            ),
+            Command::Trend { inputs, count } => {
+                run_trend(inputs, &parse_options, count, opt.format, units)
+            }
+            Command::RefsMatrix { input, root, count } => run_refs_matrix(
+                &input,
+                &parse_options,
+                root,
+                selection(count),
+                opt.format,
+                units,
+            ),
+            Command::RetainersOf {
+                input,
+                kind,
+                root,
+                count,
+            } => run_retainers_of(
+                &input,
+                &parse_options,
+                &kind,
+                root,
+                selection(count),
+                opt.format,
+                units,
+            ),
+            Command::Path {
+                input,
+                address,
+                from,
+                to,
+                paths,
+            } => run_path(
+                &input,
+                &parse_options,
+                from,
+                &address.or(to).unwrap(),
+                paths,
+                opt.format,
+                units,
+            ),
+            Command::Retainers {
+                input,
+                address,
+                depth,
+            } => run_retainers(&input, &parse_options, &address, depth, opt.format, units),
+            Command::Cut { input, address } => {
+                run_cut(&input, &parse_options, &address, opt.format, units)
+            }
+            Command::Dominated {
+                input,
+                address,
+                count,
+            } => run_dominated(
+                &input,
+                &parse_options,
+                address,
+                selection(count),
+                opt.format,
+                units,
+            ),
+            Command::Explain { input, address } => {
+                run_explain(&input, &parse_options, &address, opt.format, units)
+            }
+            Command::Grep {
+                input,
+                pattern,
+                count,
+            } => run_grep(&input, &parse_options, &pattern, count, opt.format, units),
+            Command::Collections { input, count } => {
+                run_collections(&input, &parse_options, count, opt.format, units)
+            }
+            Command::Cycles { input, count } => run_cycles(
+                &input,
+                &parse_options,
+                if all { usize::MAX } else { count },
+                opt.format,
+                units,
+            ),
+            Command::Unreachable { input, count } => {
+                run_unreachable(&input, &parse_options, selection(count), opt.format, units)
+            }
+            Command::Dangling {
+                input,
+                count,
+                list,
+                strict,
+            } => run_dangling(
+                &input,
+                &parse_options,
+                if all { usize::MAX } else { count },
+                list,
+                strict,
+                opt.format,
+                units,
+            ),
+            Command::Metrics { input } => run_metrics(&input, &parse_options, opt.format, units),
+            Command::FanIn { input, count } => {
+                run_fan_in(&input, &parse_options, selection(count), opt.format, units)
+            }
+            Command::Shared { input, count } => {
+                run_shared(&input, &parse_options, selection(count), opt.format, units)
+            }
+            Command::Roots { input } => run_roots(&input, &parse_options, opt.format, units),
+            Command::Suspects {
+                input,
+                count,
+                max_referrers,
+            } => run_suspects(
+                &input,
+                &parse_options,
+                max_referrers,
+                selection(count),
+                opt.format,
+                units,
+            ),
+            Command::Imemo { input, count } => run_imemo(
+                &input,
+                &parse_options,
+                if all { usize::MAX } else { count },
+                opt.format,
+                units,
+            ),
+            Command::Shapes { input, count } => run_shapes(
+                &input,
+                &parse_options,
+                if all { usize::MAX } else { count },
+                opt.format,
+                units,
+            ),
+            Command::Pages { input, page_size } => run_pages(&input, page_size, opt.format, units),
+            Command::Compaction {
+                input,
+                page_size,
+                max_occupancy,
+                count,
+            } => run_compaction(
+                &input,
+                &parse_options,
+                page_size,
+                max_occupancy,
+                if all { usize::MAX } else { count },
+                opt.format,
+                units,
+            ),
+            Command::Generations { input, bucket } => {
+                run_generations(&input, &parse_options, bucket, opt.format, units)
+            }
+            Command::Threads { input, count } => {
+                run_threads(&input, &parse_options, selection(count), opt.format, units)
+            }
+            Command::Largest { input, count } => run_largest(
+                &input,
+                &parse_options,
+                if all { usize::MAX } else { count },
+                &filter,
+                opt.format,
+                units,
+            ),
+            Command::Strings { input } => run_strings(&input, opt.format, units),
+            Command::Fstrings { input, count } => run_fstrings(
+                &input,
+                if all { usize::MAX } else { count },
+                opt.format,
+                units,
+            ),
+            Command::DupStrings { input, count } => run_dup_strings(
+                &input,
+                if all { usize::MAX } else { count },
+                opt.format,
+                units,
+            ),
+            Command::Symbols {
+                input,
+                count,
+                max_symbols,
+            } => run_symbols(
+                &input,
+                if all { usize::MAX } else { count },
+                max_symbols,
+                opt.format,
+                units,
+            ),
+            Command::Check {
+                input,
+                baseline,
+                max_growth,
+                min_bytes,
+            } => run_check(
+                &input,
+                &parse_options,
+                baseline.as_path(),
+                max_growth,
+                min_bytes,
+                opt.format,
+                units,
+            ),
+            Command::Capture {
+                pid,
+                output,
+                rbtrace,
+                timeout,
+                count,
+                interval,
+                rows,
+            } => run_capture(
+                pid,
+                &parse_options,
+                &capture::Options {
+                    rbtrace,
+                    dir: output.unwrap_or_else(std::env::temp_dir),
+                    timeout: Duration::from_secs(timeout),
+                    count,
+                    interval: Duration::from_secs(interval),
+                },
+                selection(rows),
+                opt.format,
+                units,
+            ),
+            Command::Watch {
+                dir,
+                count,
+                poll,
+                csv,
+            } => run_watch(
+                &dir,
+                &parse_options,
+                Duration::from_secs(poll),
+                selection(count),
+                csv,
+                opt.format,
+                units,
+            ),
+        };
+    }
+
+    // Without a subcommand, the original single-command invocation writes
+    // each output asked for from one parse of the dump, as the subcommands do
+    if opt.input.is_empty() {
+        clap::Error::with_description(
+            "The following required arguments were not provided:\n    <INPUT>",
+            clap::ErrorKind::MissingRequiredArgument,
+        )
+        .exit()
+    }
+    let dump = opt.input;
+    let graphs = [
+        opt.dot.as_deref().map(DotOutput::File),
+        opt.split_dot.as_deref().map(DotOutput::Split),
+        opt.render.as_deref().map(DotOutput::Render),
+        opt.svg.as_deref().map(DotOutput::Svg),
+        opt.mermaid.as_deref().map(DotOutput::Mermaid),
+    ];
+    let spooled = if graphs.iter().flatten().any(|output| output.dot()) {
+        Some(input::spool(&dump)?)
+    } else {
+        None
+    };
+    let input = spooled.as_ref().map_or(&dump[..], input::Spooled::paths);
+
+    let subtree_root = parse_root(opt.root)?;
+
+    let class_name_only = opt.class_name_only;
+
+    let analysis = parse(input, subtree_root, class_name_only, &parse_options)?;
+
+    // Keep stdout clean for the dot output, if that's where it's going
+    let summary: Box<dyn Write> = match opt.dot {
+        Some(ref output) if is_stdout(output) => Box::new(std::io::stderr()),
+        _ => Box::new(std::io::stdout()),
+    };
+    write_summary(
+        summary,
+        &analysis,
+        subtree_root,
+        selection(opt.count),
+        opt.format,
+        units,
+        opt.csv,
+    )?;
+
+    let profiles = [
+        (opt.flamegraph, Profile::Flamegraph),
+        (opt.folded, Profile::Folded),
+        (opt.speedscope, Profile::Speedscope),
+        (opt.pprof, Profile::Pprof),
+    ];
+    for (output, profile) in profiles {
+        if let Some(output) = output {
+            write_profile(&analysis, &dump, &output, profile, class_name_only)?;
+        }
+    }
+
+    let exports = Exports {
+        heapsnapshot: opt.heapsnapshot,
+        ..Default::default()
+    };
+    write_exports(&analysis, input, exports, opt.threshold)?;
+
+    let threshold = opt.threshold.abs();
+    let options = DotOptions {
+        graph: analyze::GraphOptions {
+            collapse_chains: opt.collapse_chains,
+            aggregate_siblings: opt.aggregate_siblings,
+            by_class: opt.by_class,
+            max_depth: opt.max_depth,
+            max_nodes: opt.max_nodes,
+        },
+        dot: dot::Options {
+            cluster_namespaces: opt.cluster_namespaces,
+            url: opt.url_template.as_deref(),
+            legend: if opt.legend { Some(threshold) } else { None },
+            invert_edges: opt.invert_edges,
+            locations: opt.label_locations,
+            ..Default::default()
+        },
+    };
+    for output in graphs.iter().flatten() {
+        write_graph(&analysis, input, threshold, &options, *output, units)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::diff::Delta;
+    use rstest::rstest;
+
+    // A file (or directory) in the temporary directory, named for this test
+    // run, e.g. /tmp/reap-1234.svg for "svg"
+    fn temp_path(suffix: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("reap-{}.{}", std::process::id(), suffix))
+    }
+
+    // Writes a file with `write`, returning its result and the file's
+    // contents, then removes the file
+    fn write_and_read<T>(suffix: &str, write: impl FnOnce(&Path) -> T) -> (T, String) {
+        let filename = temp_path(suffix);
+        let result = write(&filename);
+        let contents = std::fs::read_to_string(&filename).unwrap();
+        std::fs::remove_file(&filename).unwrap();
+        (result, contents)
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn whole_heap(#[case] class_name_only: bool) {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            class_name_only,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+
+        let totals = analysis.dominated_totals();
+        assert_eq!(15476, totals.count);
+        assert_eq!(3439119, totals.bytes);
+
+        let all = analyze::KindFilter::default();
+        let (live_by_kind, _) = analysis.live_stats_by_kind(
+            usize::MAX,
+            analyze::Sort::Bytes,
+            &all,
+            analyze::GroupBy::Kind,
+        );
+        let (dead_by_kind, _) = analysis.unreachable_stats_by_kind(
+            usize::MAX,
+            analyze::Sort::Bytes,
+            &all,
+            analyze::GroupBy::Kind,
+        );
+        let (retained_by_kind, _) = analysis.retained_stats_by_kind(
+            usize::MAX,
+            analyze::Sort::Bytes,
+            &all,
+            analyze::GroupBy::Kind,
+        );
+
+        let live_strs = live_by_kind.iter().find(|(k, _)| *k == "String").unwrap().1;
+        let dead_strs = dead_by_kind.iter().find(|(k, _)| *k == "String").unwrap().1;
+        let retained_strs = retained_by_kind
+            .iter()
+            .find(|(k, _)| *k == "String")
+            .unwrap()
+            .1;
+
+        assert_eq!(9235, live_strs.count);
+        assert_eq!(1175, dead_strs.count);
+        assert_eq!(462583, live_strs.bytes);
+        assert_eq!(81839, dead_strs.bytes);
+        assert_eq!(9235, retained_strs.count);
+        assert_eq!(462583, retained_strs.bytes);
+
+        let dom_graph =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.005), Units::default());
+        assert_eq!(34, dom_graph.node_count());
+        assert_eq!(33, dom_graph.edge_count());
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn subtree(#[case] class_name_only: bool) {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            Some(140204367666240),
+            class_name_only,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+
+        let totals = analysis.dominated_totals();
+        assert_eq!(25, totals.count);
+        assert_eq!(1053052, totals.bytes);
+
+        let all = analyze::KindFilter::default();
+        let (live_by_kind, _) = analysis.live_stats_by_kind(
+            usize::MAX,
+            analyze::Sort::Bytes,
+            &all,
+            analyze::GroupBy::Kind,
+        );
+        let (dead_by_kind, _) = analysis.unreachable_stats_by_kind(
+            usize::MAX,
+            analyze::Sort::Bytes,
+            &all,
+            analyze::GroupBy::Kind,
+        );
+        let (retained_by_kind, _) = analysis.retained_stats_by_kind(
+            usize::MAX,
+            analyze::Sort::Bytes,
+            &all,
+            analyze::GroupBy::Kind,
+        );
+
+        let live_strs = live_by_kind.iter().find(|(k, _)| *k == "String").unwrap().1;
+        let dead_strs = dead_by_kind.iter().find(|(k, _)| *k == "String").unwrap().1;
+        let retained_strs = retained_by_kind
+            .iter()
+            .find(|(k, _)| *k == "String")
+            .unwrap()
+            .1;
+
+        assert_eq!(4, live_strs.count);
+        assert_eq!(6604, dead_strs.count);
+        assert_eq!(208, live_strs.bytes);
+        assert_eq!(352283, dead_strs.bytes);
+        assert_eq!(4, retained_strs.count);
+        assert_eq!(208, retained_strs.bytes);
+
+        let dom_graph =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.0), Units::default());
+        assert_eq!(25, dom_graph.node_count());
+        assert_eq!(24, dom_graph.edge_count());
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn flamegraph_lines_output(#[case] class_name_only: bool) {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            class_name_only,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let frame_lines = analysis.flamegraph_lines();
+        let lines_with_memory_addresses = frame_lines.iter().filter(|&l| l.contains("0x")).count();
+        if class_name_only {
+            assert_eq!(lines_with_memory_addresses, 125);
+        } else {
+            // All but the lines of the 4 categories of roots
+            assert_eq!(lines_with_memory_addresses + 4, frame_lines.len());
+        }
+    }
+
+    #[test]
+    fn diff_identical_dumps() {
+        let before = diff::Summary::new(
+            &parse(
+                &[PathBuf::from("test/heap.json")],
+                None,
+                false,
+                &ParseOptions::default(),
+            )
+            .unwrap(),
+        );
+        let after = diff::Summary::new(
+            &parse(
+                &[PathBuf::from("test/heap.json")],
+                None,
+                false,
+                &ParseOptions::default(),
+            )
+            .unwrap(),
+        );
+        let diff = diff::diff(&before, &after);
+
+        assert_eq!(Delta::default(), diff.total_growth());
+        assert_eq!(3439119, diff.totals.1.bytes);
+
+        // Nothing grew, so there's nothing to list
+        let (live, rest) = diff.live_growth_by_kind(usize::MAX);
+        assert!(live.is_empty());
+        assert_eq!(Delta::default(), rest);
+        let (retained, _) = diff.retained_growth_by_kind(3);
+        assert!(retained.is_empty());
+    }
+
+    #[test]
+    fn trend_monotonic_growth() {
+        let summary = |count: usize, bytes: usize| {
+            let mut summary = diff::Summary::default();
+            let stats = Stats {
+                count,
+                bytes,
+                ..Stats::default()
+            };
+            summary.live.insert("Leaky".to_string(), stats);
+            summary.retained.insert("Leaky".to_string(), stats);
+            // The root grows with the heap, but isn't an object type
+            summary.retained.insert("ROOT".to_string(), stats);
+            summary.live.insert(
+                "Bumpy".to_string(),
+                Stats {
+                    count,
+                    bytes: 10,
+                    ..Stats::default()
+                },
+            );
+            summary.retained.insert(
+                "Bumpy".to_string(),
+                Stats {
+                    count: 1,
+                    bytes: 100 - count,
+                    ..Stats::default()
+                },
+            );
+            summary
+        };
+
+        let trend = trend::Trend::new(&[summary(1, 40), summary(3, 80), summary(2, 120)]);
+        assert_eq!(3, trend.dumps);
+
+        // Leaky's count shrank once, but its retained bytes never did
+        let growing = trend.monotonic_growth(10);
+        assert_eq!(1, growing.len());
+        assert_eq!("Leaky", growing[0].kind);
+        assert_eq!(80, growing[0].retained_growth().bytes);
+
+        // Bumpy's count grew overall, but not monotonically, while its bytes shrank
+        assert!(trend.other_growth(10).is_empty());
+
+        let flat = trend::Trend::new(&[summary(2, 40), summary(2, 40)]);
+        assert!(flat.monotonic_growth(10).is_empty());
+    }
+
+    #[test]
+    fn diff_by_location() {
+        let before = parse(
+            &[PathBuf::from("test/traced_before.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let after = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let diff = diff::diff(
+            &diff::Summary::by_location(&before),
+            &diff::Summary::by_location(&after),
+        );
+
+        let (live, _) = diff.live_growth_by_kind(2);
+        assert_eq!("Hash @ app/cache.rb:3", live[0].0);
+        assert_eq!(
+            Delta {
+                count: 0,
+                bytes: 128,
+                slot_bytes: 0
+            },
+            live[0].1
+        );
+        assert_eq!("String @ app/user.rb:12", live[1].0);
+        assert_eq!(
+            Delta {
+                count: 2,
+                bytes: 80,
+                slot_bytes: 80
+            },
+            live[1].1
+        );
+
+        let (retained, _) = diff.retained_growth_by_kind(usize::MAX);
+        let retained_hash = retained
+            .iter()
+            .find(|(k, _)| *k == "Hash @ app/cache.rb:3")
+            .unwrap()
+            .1;
+        assert_eq!(
+            Delta {
+                count: 2,
+                bytes: 208,
+                slot_bytes: 80
+            },
+            retained_hash
+        );
+        assert_eq!(
+            Delta {
+                count: 2,
+                bytes: 208,
+                slot_bytes: 80
+            },
+            diff.total_growth()
+        );
+    }
+
+    #[test]
+    fn check_against_baseline() {
+        let baseline = diff::Summary::new(
+            &parse(
+                &[PathBuf::from("test/traced_before.json")],
+                None,
+                false,
+                &ParseOptions::default(),
+            )
+            .unwrap(),
+        );
+        let current = diff::Summary::new(
+            &parse(
+                &[PathBuf::from("test/traced_after.json")],
+                None,
+                false,
+                &ParseOptions::default(),
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(0.1, parse_percentage("10%").unwrap());
+        assert!(parse_percentage("ten").is_err());
+
+        // The heap as a whole grew by 6.5%, but the Hash and its Strings grew much more
+        let regressions = diff::check(&baseline, &current, 0.1, 0);
+        let kinds: Vec<&str> = regressions.iter().map(|r| r.kind.as_str()).collect();
+        assert_eq!(vec!["Hash", "String"], kinds);
+        assert_eq!(208, regressions[0].growth().bytes);
+
+        let regressions = diff::check(&baseline, &current, 0.05, 100);
+        let kinds: Vec<&str> = regressions.iter().map(|r| r.kind.as_str()).collect();
+        assert_eq!(2, kinds.len());
+        assert!(kinds.contains(&"total") && kinds.contains(&"Hash"));
+
+        assert!(diff::check(&baseline, &current, 1.0, 0).is_empty());
+
+        // A type absent from the baseline grew from nothing, so only
+        // --min-bytes keeps a single small object of it from failing the check
+        let mut current = current;
+        current.retained.insert(
+            "Brand::New".to_string(),
+            Stats {
+                count: 1,
+                bytes: 40,
+                ..Stats::default()
+            },
+        );
+        let kinds = |min_bytes| {
+            diff::check(&baseline, &current, 1.0, min_bytes)
+                .into_iter()
+                .map(|r| r.kind)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(vec!["Brand::New"], kinds(0));
+        let opt = Opt::from_iter(&["reap", "check", "a.json", "--baseline", "b.json"]);
+        match opt.command {
+            Some(Command::Check { min_bytes, .. }) => assert!(kinds(min_bytes).is_empty()),
+            other => panic!("Unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn csv_and_yaml_reports() {
+        let baseline = diff::Summary::new(
+            &parse(
+                &[PathBuf::from("test/traced_before.json")],
+                None,
+                false,
+                &ParseOptions::default(),
+            )
+            .unwrap(),
+        );
+        let current = diff::Summary::new(
+            &parse(
+                &[PathBuf::from("test/traced_after.json")],
+                None,
+                false,
+                &ParseOptions::default(),
+            )
+            .unwrap(),
+        );
+        let report = report::CheckReport::new(&diff::check(&baseline, &current, 0.1, 0), 0.1);
+
+        let mut out = Vec::new();
+        report::write(&report, &mut out, report::Format::Csv, Units::default()).unwrap();
+        assert_eq!(
+            "# Retained memory grew by more than 10% over the baseline\n\
+             kind,before_bytes,after_bytes,growth_bytes,growth_slot_bytes,growth_malloc_bytes\n\
+             Hash,272,480,208,80,128\n\
+             String,120,200,80,80,0\n",
+            String::from_utf8(out).unwrap()
+        );
+
+        let mut out = Vec::new();
+        report::write(&report, &mut out, report::Format::Yaml, Units::default()).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("max_growth: 0.1\n"));
+        assert!(out.contains("  - kind: Hash\n"));
+        assert!(!out.contains("rest"));
+    }
+
+    #[test]
+    fn json_summary_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::SummaryReport::new(
+            &analysis,
+            None,
+            report::Selection {
+                top_types: 3,
+                top_retainers: 3,
+                sort: analyze::Sort::Bytes,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(3439119, json["totals"]["bytes"]);
+        assert_eq!("Thread", json["live_by_kind"]["largest"][0]["kind"]);
+        assert_eq!(3, json["live_by_kind"]["largest"].as_array().unwrap().len());
+        assert_eq!(3, json["retainers"]["largest"].as_array().unwrap().len());
+        assert_eq!("root[vm]", json["retainers"]["largest"][1]["label"]);
+
+        let top = &json["retainers"]["largest"][2];
+        assert_eq!("0x7f83df87dc40", top["address"]);
+        assert_eq!("Thread", top["kind"]);
+        assert_eq!(25, top["count"]);
+        assert!(top["self_bytes"].as_u64().unwrap() < top["retained_bytes"].as_u64().unwrap());
+
+        assert!(json["unreachable_by_kind"].is_object());
+        assert!(json.get("not_dominated_by_kind").is_none());
+        assert!(json.get("root").is_none());
+    }
+
+    #[test]
+    fn sorted_summary_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+
+        let report = report::SummaryReport::new(
+            &analysis,
+            None,
+            report::Selection {
+                top_types: 3,
+                top_retainers: 3,
+                sort: analyze::Sort::Count,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+        let kinds: Vec<&str> = report
+            .live_by_kind
+            .largest
+            .iter()
+            .map(|r| r.kind.as_str())
+            .collect();
+        assert_eq!(vec!["String", "IMEMO", "Array"], kinds);
+
+        let report = report::SummaryReport::new(
+            &analysis,
+            None,
+            report::Selection {
+                top_types: 3,
+                top_retainers: 3,
+                sort: analyze::Sort::SelfBytes,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+        let retainers = &report.retainers.largest;
+        assert_eq!("Thread", retainers[0].kind);
+        // Referenced directly by a category of roots
+        assert_eq!(2, retainers[0].depth);
+        assert!(retainers
+            .windows(2)
+            .all(|w| w[0].self_bytes >= w[1].self_bytes));
+    }
+
+    #[test]
+    fn filtered_summary_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::SummaryReport::new(
+            &analysis,
+            None,
+            report::Selection {
+                top_types: usize::MAX,
+                top_retainers: 5,
+                sort: analyze::Sort::Bytes,
+                filter: analyze::KindFilter {
+                    only: Some(Regex::new("^(String|Array|IMEMO)$").unwrap()),
+                    exclude: Some(Regex::new("IMEMO").unwrap()),
+                    no_internal: false,
+                },
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+
+        let mut kinds: Vec<&str> = report
+            .live_by_kind
+            .largest
+            .iter()
+            .map(|r| r.kind.as_str())
+            .collect();
+        kinds.sort();
+        assert_eq!(vec!["Array", "String"], kinds);
+        assert!(!report.retainers.largest.is_empty());
+        assert!(report
+            .retainers
+            .largest
+            .iter()
+            .all(|r| r.kind == "String" || r.kind == "Array"));
+
+        let filter = analyze::KindFilter {
+            no_internal: true,
+            ..Default::default()
+        };
+        let (live_by_kind, _) = analysis.live_stats_by_kind(
+            usize::MAX,
+            analyze::Sort::Bytes,
+            &filter,
+            analyze::GroupBy::Kind,
+        );
+        assert!(live_by_kind
+            .iter()
+            .all(|(k, _)| *k != "IMEMO" && *k != "ICLASS"));
+        assert!(live_by_kind.iter().any(|(k, _)| *k == "String"));
+        // Internal objects still count towards what others retain
+        assert_eq!(3439119, analysis.dominated_totals().bytes);
+    }
+
+    #[test]
+    fn namespace_summary_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let group = |group_by| {
+            analysis
+                .live_stats_by_kind(
+                    usize::MAX,
+                    analyze::Sort::Bytes,
+                    &analyze::KindFilter::default(),
+                    group_by,
+                )
+                .0
+                .into_iter()
+                .map(|(kind, stats)| (kind.to_string(), stats))
+                .collect::<std::collections::HashMap<String, Stats>>()
+        };
+        let by_kind = group(analyze::GroupBy::Kind);
+        let by_namespace = group(analyze::GroupBy::Namespace);
+
+        assert!(by_namespace.keys().all(|k| !k.contains("::")));
+        let gem = by_kind
+            .iter()
+            .filter(|(k, _)| *k == "Gem" || k.starts_with("Gem::"))
+            .fold(Stats::default(), |mut acc, (_, s)| acc.add(*s));
+        assert!(gem.count > 0);
+        assert_eq!(gem, by_namespace["Gem"]);
+        assert_eq!(by_kind["String"], by_namespace["String"]);
+    }
+
+    #[test]
+    fn refs_matrix_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::RefsMatrixReport::new(
+            &analysis,
+            report::Selection {
+                top_types: usize::MAX,
+                top_retainers: 0,
+                sort: analyze::Sort::Count,
+                filter: analyze::KindFilter {
+                    only: Some(Regex::new("^String$").unwrap()),
+                    ..Default::default()
+                },
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+
+        let rows = &report.references.largest;
+        assert!(rows.iter().all(|r| r.to == "String"));
+        assert!(rows
+            .windows(2)
+            .all(|w| w[0].stats.count >= w[1].stats.count));
+        let hash = rows.iter().find(|r| r.from == "Hash").unwrap();
+        assert_eq!(3616, hash.stats.count);
+        assert!(!report.references.has_rest());
+    }
+
+    #[test]
+    fn retainers_of_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::RetainersOfReport::new(
+            &analysis,
+            "Gem::Specification",
+            report::Selection {
+                top_types: usize::MAX,
+                top_retainers: 0,
+                sort: analyze::Sort::Count,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+
+        assert_eq!(194, report.instances);
+        let top = &report.dominators.largest[0];
+        assert_eq!("Class", top.kind);
+        assert_eq!(167, top.instances);
+
+        let mut out = Vec::new();
+        report::write(&report, &mut out, report::Format::Table, Units::default()).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("86% of Gem::Specification instances are retained by Class\n"));
+    }
+
+    #[test]
+    fn paths_to_object() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let target = analysis.index_of(0x7f83df89f3b8).unwrap();
+        let report = report::PathReport::new(&analysis, analysis.root(), target, 5);
+
+        let lengths: Vec<usize> = report.paths.iter().map(|p| p.len()).collect();
+        assert_eq!(vec![3, 5, 6], lengths);
+        for path in report.paths.iter() {
+            assert_eq!("ROOT", path[0].kind);
+            assert_eq!("0x7f83df89f3b8", path.last().unwrap().address);
+        }
+        assert_eq!("Hash", report.paths[2][4].kind);
+
+        assert_eq!(1, analysis.paths_between(analysis.root(), target, 1).len());
+
+        // Between two objects, through the Hash in the third path above
+        let from = analysis.index_of(0x7f83e102d778).unwrap();
+        let report = report::PathReport::new(&analysis, from, target, 5);
+        assert_eq!("0x7f83e102d778", report.from);
+        let path: Vec<&str> = report.paths[0].iter().map(|h| h.kind.as_str()).collect();
+        assert_eq!(vec!["Module", "Hash", "String"], path);
+        assert!(analysis.paths_between(target, from, 1).is_empty());
+        assert!(analysis.index_of(0xdead).is_none());
+
+        // Paths don't pass through the target on the way back to it, as they
+        // would through objects only reachable from it
+        let target = analysis.index_of(0x7f83de808298).unwrap();
+        let paths = analysis.paths_between(analysis.root(), target, 30);
+        assert_eq!(9, paths.len());
+        for path in paths {
+            assert_eq!(1, path.iter().filter(|i| **i == target).count());
+        }
+    }
+
+    #[test]
+    fn referrers_of_object() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let target = analysis.index_of(0x7f83df89f3b8).unwrap();
+
+        let tree = analysis.referrer_tree(target, 1);
+        assert_eq!("0x7f83df89f3b8", tree.address);
+        let referrers: Vec<&str> = tree.children.iter().map(|c| c.kind.as_str()).collect();
+        assert_eq!(vec!["ARRAY", "Hash", "ROOT"], referrers);
+        assert!(tree.children.iter().all(|c| c.children.is_empty()));
+
+        let tree = analysis.referrer_tree(target, 2);
+        assert_eq!("0x7f83df8dff80", tree.children[0].children[0].address);
+        assert_eq!("root", tree.children[2].children[0].label);
+    }
+
+    #[test]
+    fn dominated_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            Some(0x7f83df87dc40),
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::DominatedReport::new(
+            &analysis,
+            report::Selection {
+                top_types: usize::MAX,
+                top_retainers: 0,
+                sort: analyze::Sort::Bytes,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+
+        assert_eq!("0x7f83df87dc40", report.address);
+        assert_eq!(25, report.totals.count);
+        let counted: usize = report.by_kind.largest.iter().map(|r| r.stats.count).sum();
+        assert_eq!(25, counted);
+        assert_eq!("Thread", report.by_kind.largest[0].kind);
+
+        // An address not in the dump is an error
+        let missing = parse(
+            &[PathBuf::from("test/heap.json")],
+            Some(0xdead),
+            false,
+            &ParseOptions::default(),
+        );
+        assert_eq!(
+            "No object at 0xdead in the dump",
+            missing.err().unwrap().to_string()
+        );
+
+        // As is one that isn't in hex with a 0x prefix, as the dump writes them
+        assert_eq!(
+            Some(0x7f83df87dc40),
+            parse_root(Some("0x7f83df87dc40".to_string())).unwrap()
+        );
+        for invalid in &["0", "7f83df87dc40", "0xzz"] {
+            assert_eq!(
+                format!("Invalid address {}", invalid),
+                parse_root(Some(invalid.to_string()))
+                    .unwrap_err()
+                    .to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn explain_object() {
+        let input = &[PathBuf::from("test/heap.json")];
+        let analysis = parse(input, None, false, &ParseOptions::default()).unwrap();
+        let target = analysis.index_of(0x7f83df809890).unwrap();
+        let record = parse::find_object(input::open(input).unwrap(), 0x7f83df809890).unwrap();
+        let report = report::ExplainReport::new(&analysis, target, record.as_ref());
+
+        assert_eq!("Hash", report.kind);
+        assert_eq!(Some("HASH".to_string()), report.object_type);
+        assert_eq!(25584, report.retained_bytes);
+        assert_eq!("0x7f83e102d778", report.dominator.as_ref().unwrap().address);
+        assert_eq!(1, report.incoming_references);
+        assert_eq!(512, report.references.largest.len());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!("HASH", json["type"]);
+        assert!(parse::find_object(input::open(input).unwrap(), 0x1)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn grep_strings() {
+        let input = &[PathBuf::from("test/heap.json")];
+        let pattern = Regex::new("https?://").unwrap();
+        let found = parse::find_strings(input::open(input).unwrap(), &pattern).unwrap();
+        assert!(found.iter().all(|(_, v)| pattern.is_match(v)));
+
+        let analysis = parse(input, None, false, &ParseOptions::default()).unwrap();
+        let report = report::GrepReport::new(&analysis, pattern.as_str(), found, 4);
+        let matches = &report.matches.largest;
+        assert_eq!(4, matches.len());
+        assert_eq!(33, report.matches.rest.count);
+        assert_eq!("0x7f83df913d30", matches[0].address);
+        assert_eq!(312, matches[0].self_bytes);
+        assert_eq!("root", matches[0].dominators[0]);
+        assert!(matches[0].value.starts_with("Psych is a YAML parser"));
+        assert!(matches
+            .windows(2)
+            .all(|w| w[0].self_bytes >= w[1].self_bytes));
+    }
+
+    #[test]
+    fn largest_collections() {
+        let input = &[PathBuf::from("test/heap.json")];
+        let found = parse::find_collections(input::open(input).unwrap()).unwrap();
+        let analysis = parse(input, None, false, &ParseOptions::default()).unwrap();
+        let report = report::CollectionsReport::new(&analysis, found.clone(), 3);
+
+        let collections = &report.collections.largest;
+        assert_eq!(3, collections.len());
+        assert_eq!(1024, collections[0].elements);
+        assert_eq!("Array[0x7f83df8dff08][len=7]", collections[0].dominator);
+
+        let report = report::CollectionsReport::new(&analysis, found, usize::MAX);
+        let collections = &report.collections.largest;
+        assert!(collections
+            .windows(2)
+            .all(|w| w[0].elements >= w[1].elements));
+        let hash = collections
+            .iter()
+            .find(|c| c.address == "0x7f83df809890")
+            .unwrap();
+        assert_eq!(256, hash.elements);
+
+        let wasteful = &report.most_wasteful.largest;
+        assert_eq!("Hash[0x7f83df8af4e8][size=586]", wasteful[0].label);
+        assert_eq!(28768 - 40 - 586 * 24, wasteful[0].wasted_bytes);
+        assert!(wasteful
+            .windows(2)
+            .all(|w| w[0].wasted_bytes >= w[1].wasted_bytes));
+        let by_kind = &report.wasted_by_kind;
+        assert_eq!(
+            report.wasted,
+            by_kind
+                .largest
+                .iter()
+                .fold(by_kind.rest, |mut acc, r| acc.add(r.stats))
+        );
+    }
+
+    #[test]
+    fn reference_cycles() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::CyclesReport::new(&analysis, 3);
+        assert_eq!(120, report.cycle_count);
+        assert_eq!(2068, report.object_count);
+
+        let cycles = &report.cycles.largest;
+        assert_eq!(3, cycles.len());
+        assert_eq!("Gem[0x7f83df856898][MODULE]", cycles[0].label);
+        assert_eq!(1478, cycles[0].members);
+        assert_eq!(308872, cycles[0].self_bytes);
+        assert_eq!(1080707, cycles[0].retained_bytes);
+        assert!(cycles.windows(2).all(|w| w[0].members >= w[1].members));
+    }
+
+    #[test]
+    fn unreachable_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::UnreachableReport::new(
+            &analysis,
+            report::Selection {
+                top_types: 3,
+                top_retainers: 3,
+                sort: analyze::Sort::Bytes,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+        assert_eq!(3511, report.totals.count);
+        assert_eq!(401582, report.totals.bytes);
+        assert_eq!(
+            analysis.dominated_totals().count + report.totals.count,
+            report.heap.count
+        );
+
+        let objects = &report.objects.largest;
+        assert_eq!("Module[0x7f83de810448][CLASS]", objects[0].label);
+        assert_eq!(4552, objects[0].bytes);
+        assert_eq!(3508, report.objects.rest.count);
+    }
+
+    #[test]
+    fn dangling_references() {
+        let (_, graph, dangling) = parse::parse_with_dangling(
+            input::open(&[PathBuf::from("test/heap.json")]).unwrap(),
+            false,
+            &[],
+            Default::default(),
+        )
+        .unwrap();
+        let report = report::DanglingReport::new(&graph, dangling, 3, true);
+        assert_eq!(4, report.references);
+        assert_eq!(4, report.objects);
+
+        let by_kind = &report.by_kind.largest;
+        assert_eq!("Hash", by_kind[0].kind);
+        assert_eq!(2, by_kind[0].references);
+        assert_eq!(4, report.dangling.unwrap().largest.len());
+    }
+
+    #[test]
+    fn repeated_references() {
+        // This Hash references the same object 293 times
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let graph = analysis.dominated_subgraph();
+        let hash = analysis.index_of(0x7f83df8af4e8).unwrap();
+        let target = analysis.index_of(0x7f83e10924e8).unwrap();
+
+        let edges: Vec<_> = graph
+            .raw_edges()
+            .iter()
+            .filter(|e| e.source() == hash && e.target() == target)
+            .collect();
+        assert_eq!(1, edges.len());
+        assert_eq!(References(293), edges[0].weight);
+        assert_eq!("×293", edges[0].weight.to_string());
+    }
+
+    #[test]
+    fn named_roots() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let graph = analysis.dominated_subgraph();
+        let mut categories: Vec<String> = graph
+            .neighbors(analysis.root())
+            .map(|i| graph[i].to_string())
+            .collect();
+        categories.sort();
+        assert_eq!(
+            vec![
+                "root[global_list]",
+                "root[global_tbl]",
+                "root[machine_context]",
+                "root[vm]"
+            ],
+            categories
+        );
+
+        // Only the VM references this Thread, but the VM and a global both
+        // reference this object
+        let dominator = |address: usize| {
+            let i = analysis.index_of(address).unwrap();
+            graph[analysis.dominator(i).unwrap()].to_string()
+        };
+        assert_eq!("root[vm]", dominator(0x7f83df87dc40));
+        assert_eq!("root", dominator(0x7f83df87dc68));
+    }
+
+    #[test]
+    fn graph_metrics() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::MetricsReport::new(&analysis);
+        assert_eq!(15476, report.objects);
+        assert_eq!(3511, report.unreachable_objects);
+        assert_eq!(23650, report.references);
+        assert_eq!(120, report.cycles);
+        assert_eq!("0x7f83e10924e8", report.most_referenced.address);
+        assert_eq!(294, report.max_in_degree);
+        assert_eq!(1172, report.max_out_degree);
+        assert_eq!(16, report.max_reference_depth);
+
+        let depths = &report.dominator_depths.largest;
+        assert_eq!(15, depths.len());
+        assert_eq!(
+            report.objects,
+            depths.iter().map(|row| row.objects).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn fan_in_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::FanInReport::new(
+            &analysis,
+            report::Selection {
+                top_types: 3,
+                top_retainers: 3,
+                sort: analyze::Sort::Bytes,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+        let objects = &report.objects.largest;
+        assert_eq!(3, objects.len());
+        assert_eq!("0x7f83de809cd8", objects[0].address);
+        assert_eq!(237, objects[0].referrers);
+        assert_eq!("Kernel[0x7f83df8dd3c0][MODULE]", objects[1].label);
+
+        let strings = &report.by_kind.largest[0];
+        assert_eq!("String", strings.kind);
+        assert_eq!(9235, strings.objects);
+        assert_eq!(12525, strings.referrers);
+        assert_eq!(237, strings.max_referrers);
+    }
+
+    #[test]
+    fn min_cut() {
+        let (analysis, target) = parse_with_target(
+            &[PathBuf::from("test/heap.json")],
+            &ParseOptions::default(),
+            "0x7f83de809cd8",
+        )
+        .unwrap();
+        let cut = analysis.min_cut(target);
+        assert_eq!(3, cut.len());
+        assert!(cut.iter().any(|(_, j)| *j == target));
+
+        // Without those references, the string is no longer reachable
+        let mut graph = analysis.dominated_subgraph().clone();
+        graph.retain_edges(|g, e| {
+            let (i, j) = g.edge_endpoints(e).unwrap();
+            !cut.contains(&(i, j))
+        });
+        let mut dfs = petgraph::visit::Dfs::new(&graph, analysis.root());
+        while let Some(i) = dfs.next(&graph) {
+            assert_ne!(target, i);
+        }
+
+        let (analysis, target) = parse_with_target(
+            &[PathBuf::from("test/heap.json")],
+            &ParseOptions::default(),
+            "0x7f83df87dc40",
+        )
+        .unwrap();
+        let report = report::CutReport::new(&analysis, target);
+        let references = &report.references.largest;
+        assert_eq!(1, references.len());
+        assert_eq!("root[vm]", references[0].from);
+        assert_eq!("Thread[0x7f83df87dc40]", references[0].to);
+    }
+
+    #[test]
+    fn shared_memory() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::SharingReport::new(
+            &analysis,
+            report::Selection {
+                top_types: 3,
+                top_retainers: 4,
+                sort: analyze::Sort::Bytes,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+        let objects = &report.objects.largest;
+        // Everything reachable from the root is dominated by it
+        assert_eq!("root", objects[0].label);
+        assert_eq!(0, objects[0].shared_bytes);
+
+        let thread = objects
+            .iter()
+            .find(|o| o.label == "Thread[0x7f83df87dc40]")
+            .unwrap();
+        assert_eq!(1053052, thread.retained_bytes);
+        assert_eq!(2199947, thread.reachable_bytes);
+        assert_eq!(
+            thread.reachable_bytes - thread.retained_bytes,
+            thread.shared_bytes
+        );
+    }
+
+    #[test]
+    fn root_categories() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::RootsReport::new(&analysis);
+
+        let categories = &report.categories.largest;
+        let labels: Vec<&str> = categories.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(vec!["root[global_tbl]", "root[vm]"], labels);
+        assert_eq!(2800, categories[0].retained_bytes);
+        assert_eq!(3, categories[0].retained_count);
+        assert_eq!(560, categories[1].retained_bytes);
+        assert_eq!(560, categories[1].reachable_bytes);
+        assert_eq!(Stats::default(), report.shared);
+
+        let mut out = Vec::new();
+        report::write(&report, &mut out, report::Format::Table, Units::default()).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(
+            "root[global_tbl]: 2.7 KiB (3 objects, 83.3%; 120 B in slots, 2.6 KiB malloc'd), 2.7 KiB reachable\n"
+        ));
+    }
+
+    #[test]
+    fn ignored_roots() {
+        let ignored = ["machine_context".to_string()];
+        let (root, graph) = parse::parse(
+            input::open(&[PathBuf::from("test/heap.json")]).unwrap(),
+            false,
+            &ignored,
+            Default::default(),
+        )
+        .unwrap();
+        let analysis = analyze::analyze(root, root, graph, false, Default::default());
+
+        let report = report::RootsReport::new(&analysis);
+        let labels: Vec<&str> = report
+            .categories
+            .largest
+            .iter()
+            .map(|c| c.label.as_str())
+            .collect();
+        assert_eq!(
+            vec!["root[vm]", "root[global_list]", "root[global_tbl]"],
+            labels
+        );
+        assert!(analysis.dominated_totals().count < 15476);
+
+        let opt = Opt::from_iter(&[
+            "reap",
+            "summary",
+            "heap.json",
+            "--ignore-roots",
+            "machine_context,global_list",
+        ]);
+        assert_eq!(vec!["machine_context", "global_list"], opt.ignore_roots);
+    }
+
+    #[test]
+    fn leak_suspects() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let selection = |filter| report::Selection {
+            top_types: 10,
+            top_retainers: 0,
+            sort: analyze::Sort::Bytes,
+            filter,
+            group_by: analyze::GroupBy::Kind,
+        };
+        let report = report::SuspectsReport::new(&analysis, 1, selection(Default::default()));
+        let suspects: Vec<(&str, usize)> = report
+            .suspects
+            .largest
+            .iter()
+            .map(|r| (r.kind.as_str(), r.stats.bytes))
+            .collect();
+        // The Hash holds on to the strings in it; the unreferenced garbage
+        // string isn't live, let alone old
+        assert_eq!(
+            vec![
+                ("CLASS", 2800),
+                ("Hash", 480),
+                ("String", 200),
+                ("User", 80)
+            ],
+            suspects
+        );
+
+        let only_hashes = analyze::KindFilter {
+            only: Some(Regex::new("^Hash$").unwrap()),
+            ..Default::default()
+        };
+        let report = report::SuspectsReport::new(&analysis, 1, selection(only_hashes));
+        assert_eq!(1, report.suspects.largest.len());
+        let report = report::SuspectsReport::new(&analysis, 0, selection(Default::default()));
+        assert!(report.suspects.largest.is_empty());
+    }
+
+    #[test]
+    fn imemo_subtypes() {
+        let input = &[PathBuf::from("test/heap.json")];
+        let found = parse::find_imemo_types(input::open(input).unwrap()).unwrap();
+        assert_eq!(4833, found.len());
+        let analysis = parse(input, None, false, &ParseOptions::default()).unwrap();
+        let graph = analysis.dominated_subgraph();
+        let live_addresses: HashSet<usize> =
+            graph.node_indices().map(|i| graph[i].address).collect();
+        let live_imemos = found
+            .iter()
+            .filter(|(address, _)| live_addresses.contains(address))
+            .count();
+        let report = report::ImemoReport::new(&analysis, found, 3);
+
+        let live: Vec<&str> = report
+            .live
+            .largest
+            .iter()
+            .map(|r| r.kind.as_str())
+            .collect();
+        assert_eq!(vec!["ment", "iseq", "cref"], live);
+        // Every live IMEMO counted once, whatever its subtype
+        let total = report
+            .live
+            .largest
+            .iter()
+            .fold(report.live.rest, |mut acc, r| acc.add(r.stats));
+        assert_eq!(live_imemos, total.count);
+        // Each subtype retains at least the memory of its own instances
+        for retained in &report.retained.largest {
+            if let Some(live) = report.live.largest.iter().find(|r| r.kind == retained.kind) {
+                assert!(retained.stats.bytes >= live.stats.bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn object_shapes() {
+        let input = &[PathBuf::from("test/shapes.json")];
+        let found = parse::find_shapes(input::open(input).unwrap()).unwrap();
+        // Including the unreachable one
+        assert_eq!(6, found.len());
+        let analysis = parse(input, None, false, &ParseOptions::default()).unwrap();
+        let report = report::ShapesReport::new(&analysis, found, 2);
+
+        assert_eq!(5, report.objects);
+        assert_eq!(3, report.shape_count);
+        let shapes: Vec<(usize, &str, usize)> = report
+            .shapes
+            .largest
+            .iter()
+            .map(|r| (r.shape_id, r.kinds.as_str(), r.stats.count))
+            .collect();
+        assert_eq!(vec![(5, "Point", 3), (2, "Config", 1)], shapes);
+        assert_eq!(1, report.shapes.rest.count);
+        assert_eq!(1000, report.too_complex.bytes);
+        // Both Configs: 80 B and 960 B beyond their 40 B slots
+        assert_eq!(2, report.unembedded.count);
+        assert_eq!(1040, report.unembedded.bytes);
+
+        // Dumps from before shapes have none to report
+        assert!(
+            parse::find_shapes(input::open(&[PathBuf::from("test/heap.json")]).unwrap())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn heap_pages() {
+        let slots =
+            parse::find_slots(input::open(&[PathBuf::from("test/heap.json")]).unwrap()).unwrap();
+        let pages = pages::pages(&slots, 16384);
+        let report = report::PagesReport::new(&pages);
+
+        assert_eq!(57, report.pages);
+        assert_eq!(slots.len(), report.objects);
+        // 409 slots of 40 B fit on each page
+        assert_eq!(57 * 409, report.slots);
+        assert_eq!((report.slots - report.objects) * 40, report.free_bytes);
+        assert_eq!(
+            report.objects,
+            report
+                .occupancy
+                .largest
+                .iter()
+                .map(|r| r.objects)
+                .sum::<usize>()
+        );
+        assert!(report.stranded_bytes <= report.free_bytes);
+        // Pages are aligned to their size
+        assert!(pages.iter().all(|page| page
+            .objects
+            .iter()
+            .all(|slot| slot.address - page.address < 16384)));
+    }
+
+    #[test]
+    fn compaction_candidates() {
+        let input = &[PathBuf::from("test/pages.json")];
+        let slots = parse::find_slots(input::open(input).unwrap()).unwrap();
+        // Six 40 B slots to a page
+        let pages = pages::pages(&slots, 256);
+        assert_eq!(4, pages.len());
+        let analysis = parse(input, None, false, &ParseOptions::default()).unwrap();
+
+        let report = report::CompactionReport::new(&analysis, &pages, 20, 10);
+        assert_eq!(2, report.pages);
+        assert_eq!(2, report.objects);
+        assert_eq!(1, report.pinned);
+        assert_eq!(2 * 5 * 40, report.free_bytes);
+        // The page with only an unreachable string
+        assert_eq!(1, report.movable_pages);
+        assert_eq!(256, report.movable_bytes);
+        let holders: Vec<(&str, bool, &str)> = report
+            .holders
+            .largest
+            .iter()
+            .map(|r| (r.address.as_str(), r.pinned, r.path.as_str()))
+            .collect();
+        assert_eq!(
+            vec![("0x7f0000000100", true, "root[vm] -> HASH -> STRING")],
+            holders
+        );
+
+        // Pinned objects come first, then those on the emptiest pages
+        let report = report::CompactionReport::new(&analysis, &pages, 50, 10);
+        assert_eq!(3, report.pages);
+        let holders: Vec<&str> = report
+            .holders
+            .largest
+            .iter()
+            .map(|r| r.address.as_str())
+            .collect();
+        assert_eq!(
+            vec!["0x7f0000000100", "0x7f0000000200", "0x7f0000000228"],
+            holders
+        );
+        assert_eq!(
+            3,
+            report
+                .kinds
+                .largest
+                .iter()
+                .map(|r| r.stats.count)
+                .sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn cow_sharing() {
+        let parent = &[PathBuf::from("test/pages.json")];
+        let child = &[PathBuf::from("test/pages_child.json")];
+        let parent_pages = pages::pages(
+            &parse::find_slots(input::open(parent).unwrap()).unwrap(),
+            256,
+        );
+        let fingerprints =
+            fingerprints(&parse(parent, None, false, &ParseOptions::default()).unwrap());
+        let child_pages = pages::pages(
+            &parse::find_slots(input::open(child).unwrap()).unwrap(),
+            256,
+        );
+        let analysis = parse(child, None, false, &ParseOptions::default()).unwrap();
+        let report =
+            report::CowReport::new(&fingerprints, &parent_pages, &analysis, &child_pages, 10);
+
+        // A changed string and a new one
+        assert_eq!(6, report.shared.count);
+        assert_eq!(2, report.private.count);
+        assert_eq!(1, report.freed);
+        // The pinned string's page is untouched, one page has a changed
+        // string and another a freed one, and the new string is on a new page
+        assert_eq!(1, report.shared_pages);
+        assert_eq!(2, report.copied_pages);
+        assert_eq!(512, report.copied_bytes);
+        assert_eq!(1, report.new_pages);
+        let kinds: Vec<(&str, Stats)> = report
+            .kinds
+            .largest
+            .iter()
+            .map(|r| (r.kind.as_str(), r.stats))
+            .collect();
+        assert_eq!(
+            vec![(
+                "STRING",
+                Stats {
+                    count: 1,
+                    bytes: 256,
+                    slot_bytes: 256
+                }
+            )],
+            kinds
+        );
+    }
+
+    #[test]
+    fn slot_and_malloc_bytes() {
+        let analysis = parse(
+            &[PathBuf::from("test/shapes.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let (live, _) = analysis.live_stats_by_kind(
+            usize::MAX,
+            analyze::Sort::Bytes,
+            &Default::default(),
+            analyze::GroupBy::Kind,
+        );
+        let config = live.iter().find(|(kind, _)| kind == "Config").unwrap().1;
+        // Both in 40 B slots, with their instance variables malloc'd
+        assert_eq!(1120, config.bytes);
+        assert_eq!(80, config.slot_bytes);
+        assert_eq!(1040, config.malloc_bytes());
+        // The string's memsize is missing, so there's nothing to split
+        let string = live.iter().find(|(kind, _)| kind == "STRING").unwrap().1;
+        assert_eq!((0, 0), (string.bytes, string.slot_bytes));
+    }
+
+    #[test]
+    fn partial_dumps() {
+        let input = &[PathBuf::from("test/partial.json")];
+        let analyze = |partial| {
+            let options = parse::Options {
+                partial,
+                ..Default::default()
+            };
+            let (root, graph) =
+                parse::parse(input::open(input).unwrap(), false, &[], options).unwrap();
+            analyze::analyze(root, root, graph, false, Default::default())
+        };
+
+        // The array only objects left out of the dump reference looks like
+        // garbage
+        let analysis = analyze(false);
+        assert_eq!(200, analysis.dominated_totals().bytes);
+
+        let analysis = analyze(true);
+        assert_eq!(480, analysis.dominated_totals().bytes);
+        let graph = analysis.dominated_subgraph();
+        let placeholders: Vec<usize> = graph
+            .raw_nodes()
+            .iter()
+            .filter(|node| node.weight.kind == "UNKNOWN")
+            .map(|node| node.weight.address)
+            .collect();
+        assert_eq!(2, placeholders.len());
+        let outside = graph
+            .node_indices()
+            .find(|i| graph[*i].to_string() == "root[outside_dump]")
+            .unwrap();
+        assert_eq!(280, analysis.retained_stats(outside).bytes);
+    }
+
+    #[test]
+    fn dump_shards() {
+        let analyze = |input: &[PathBuf]| {
+            let (root, graph) =
+                parse::parse(input::open(input).unwrap(), false, &[], Default::default()).unwrap();
+            analyze::analyze(root, root, graph, false, Default::default())
+        };
+        let whole = analyze(&[PathBuf::from("test/pages.json")]);
+        // Each shard repeats the roots, and both have the object dumped while
+        // the dump was split
+        let shards = analyze(&[
+            PathBuf::from("test/pages_shard1.json"),
+            PathBuf::from("test/pages_shard2.json"),
+        ]);
+
+        assert_eq!(whole.dominated_totals(), shards.dominated_totals());
+        let (whole, shards) = (whole.dominated_subgraph(), shards.dominated_subgraph());
+        assert_eq!(whole.node_count(), shards.node_count());
+        assert_eq!(whole.edge_count(), shards.edge_count());
+        assert!(shards
+            .raw_edges()
+            .iter()
+            .all(|edge| edge.weight == References(1)));
+
+        // A shard without a trailing newline doesn't run into the next one
+        let filename = temp_path("shard.json");
+        let shard = std::fs::read_to_string("test/pages_shard1.json").unwrap();
+        std::fs::write(&filename, shard.trim_end()).unwrap();
+        let unterminated = analyze(&[filename.clone(), PathBuf::from("test/pages_shard2.json")]);
+        std::fs::remove_file(&filename).unwrap();
+        assert_eq!(
+            analyze(&[PathBuf::from("test/pages.json")]).dominated_totals(),
+            unterminated.dominated_totals()
+        );
+    }
+
+    #[test]
+    fn gzip_input() {
+        let analyze = |input: &[PathBuf]| {
+            let (root, graph) =
+                parse::parse(input::open(input).unwrap(), false, &[], Default::default()).unwrap();
+            analyze::analyze(root, root, graph, false, Default::default())
+        };
+        // Recognized without a .gz extension, and alongside uncompressed shards
+        let filename = temp_path("json");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&filename).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder
+            .write_all(&std::fs::read("test/pages_shard1.json").unwrap())
+            .unwrap();
+        encoder.finish().unwrap();
+        let gzipped = analyze(&[filename.clone(), PathBuf::from("test/pages_shard2.json")]);
+        std::fs::remove_file(&filename).unwrap();
+
+        let whole = analyze(&[PathBuf::from("test/pages.json")]);
+        assert_eq!(whole.dominated_totals(), gzipped.dominated_totals());
+
+        // A truncated dump is an error, rather than the part of it before
+        // where it was cut off
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(&std::fs::read("test/heap.json").unwrap())
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+        std::fs::write(&filename, &compressed[..compressed.len() / 2]).unwrap();
+        let truncated = parse::parse(
+            input::open(std::slice::from_ref(&filename)).unwrap(),
+            false,
+            &[],
+            Default::default(),
+        );
+        std::fs::remove_file(&filename).unwrap();
+        assert!(truncated.is_err());
+    }
+
+    #[test]
+    fn http_input() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/heap.json.zst", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let body = zstd::encode_all(File::open("test/pages.json").unwrap(), 0).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        });
+
+        let analysis = parse(&[PathBuf::from(url)], None, false, &ParseOptions::default()).unwrap();
+        server.join().unwrap();
+        assert_eq!(320, analysis.dominated_totals().bytes);
+    }
+
+    #[cfg(not(feature = "s3"))]
+    #[test]
+    fn s3_input_without_feature() {
+        let error = parse(
+            &[PathBuf::from("s3://bucket/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("--features s3"));
+    }
+
+    #[test]
+    fn capture_expression() {
+        // Quotes in the path are escaped, and the dump only appears under it
+        // once complete
+        assert_eq!(
+            "Thread.new{require 'objspace';f=open('/tmp/it\\'s.json.partial','w');ObjectSpace.dump_all(output: f, full: true);f.close;File.rename('/tmp/it\\'s.json.partial','/tmp/it\\'s.json')}",
+            capture::ruby(Path::new("/tmp/it's.json"))
+        );
+        let opt = Opt::from_iter(&["reap", "capture", "--pid", "1234", "-o", "/dumps"]);
+        match opt.command {
+            Some(Command::Capture {
+                pid, output, rows, ..
+            }) => {
+                assert_eq!(1234, pid);
+                assert_eq!(Some(PathBuf::from("/dumps")), output);
+                assert_eq!(10, rows);
+            }
+            other => panic!("Unexpected command {:?}", other),
+        }
+        let opt = Opt::from_iter(&[
+            "reap",
+            "capture",
+            "--pid",
+            "1234",
+            "--interval",
+            "30",
+            "--count",
+            "5",
+        ]);
+        match opt.command {
+            Some(Command::Capture {
+                count, interval, ..
+            }) => assert_eq!((5, 30), (count, interval)),
+            other => panic!("Unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn watch_directory() {
+        let dir = temp_path("watch");
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = std::fs::canonicalize(dir).unwrap();
+        let mut watcher = watch::Watcher::new(&dir).unwrap();
+        watcher.ignore(&dir.join("out.json"));
+        assert!(watcher.poll().unwrap().is_empty());
+
+        // Dumps are picked up once they've stopped growing, and only once
+        // unless they change, while other files are left alone
+        std::fs::copy("test/pages.json", dir.join("a.json")).unwrap();
+        std::fs::copy("test/pages.json", dir.join("b.json.partial")).unwrap();
+        std::fs::copy("test/pages.json", dir.join("out.json")).unwrap();
+        std::fs::write(dir.join("README"), "Heap dumps").unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+        assert_eq!(vec![dir.join("a.json")], watcher.poll().unwrap());
+        assert!(watcher.poll().unwrap().is_empty());
+        std::fs::copy("test/pages_child.json", dir.join("a.json")).unwrap();
+        watcher.poll().unwrap();
+        assert_eq!(vec![dir.join("a.json")], watcher.poll().unwrap());
+
+        // Dumps found together are in the order they were written, which
+        // isn't their names' once numbers in them reach another digit
+        let hour_ago = std::time::SystemTime::now() - Duration::from_secs(3600);
+        std::fs::copy("test/pages.json", dir.join("heap-10.json")).unwrap();
+        std::fs::copy("test/pages.json", dir.join("heap-9.json")).unwrap();
+        File::options()
+            .write(true)
+            .open(dir.join("heap-9.json"))
+            .unwrap()
+            .set_modified(hour_ago)
+            .unwrap();
+        watcher.poll().unwrap();
+        assert_eq!(
+            vec![dir.join("heap-9.json"), dir.join("heap-10.json")],
+            watcher.poll().unwrap()
+        );
+
+        // Neither do lines that aren't part of a dump panic
+        std::fs::write(dir.join("c.json"), "{\"truncated\n").unwrap();
+        assert!(parse(&[dir.join("c.json")], None, false, &ParseOptions::default()).is_err());
+
+        let summary = diff::Summary::new(
+            &parse(&[dir.join("a.json")], None, false, &ParseOptions::default()).unwrap(),
+        );
+        let csv = dir.join("watch.csv");
+        watch::append_csv(&csv, Path::new("a.json"), &summary, None).unwrap();
+        watch::append_csv(&csv, Path::new("b.json"), &summary, Some(&summary)).unwrap();
+        let written = std::fs::read_to_string(&csv).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(
+            "dump,kind,count,bytes,slot_bytes,malloc_bytes,\
+             count_growth,bytes_growth,slot_bytes_growth,malloc_bytes_growth",
+            lines[0]
+        );
+        assert_eq!("a.json,STRING,6,240,240,0,,,,", lines[1]);
+        assert_eq!(
+            1,
+            lines
+                .iter()
+                .filter(|line| line.starts_with("dump,"))
+                .count()
+        );
+        assert!(lines.contains(&"b.json,STRING,6,240,240,0,0,0,0,0"));
+    }
+
+    #[test]
+    fn zstd_input() {
+        let filename = temp_path("json.zst");
+        std::fs::write(
+            &filename,
+            zstd::encode_all(File::open("test/pages.json").unwrap(), 0).unwrap(),
+        )
+        .unwrap();
+        let (root, graph) = parse::parse(
+            input::open(std::slice::from_ref(&filename)).unwrap(),
+            false,
+            &[],
+            Default::default(),
+        )
+        .unwrap();
+        std::fs::remove_file(&filename).unwrap();
+        let analysis = analyze::analyze(root, root, graph, false, Default::default());
+
+        assert_eq!(320, analysis.dominated_totals().bytes);
+
+        // Read a byte at a time, as from a slow pipe, the magic is still found
+        struct Trickle(std::io::Cursor<Vec<u8>>);
+        impl Read for Trickle {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let end = buf.len().min(1);
+                self.0.read(&mut buf[..end])
+            }
+        }
+        let compressed = zstd::encode_all(&b"{\"type\":\"ROOT\"}\n"[..], 0).unwrap();
+        let mut decompressed = String::new();
+        input::decompress(Trickle(std::io::Cursor::new(compressed)))
+            .unwrap()
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!("{\"type\":\"ROOT\"}\n", decompressed);
+    }
+
+    #[test]
+    fn parse_from_reader() {
+        // A dump piped in parses just as the file it came from does
+        let dump = std::fs::read("test/pages.json").unwrap();
+        let (root, graph) = parse::parse(dump.as_slice(), false, &[], Default::default()).unwrap();
+        let analysis = analyze::analyze(root, root, graph, false, Default::default());
+        assert_eq!(320, analysis.dominated_totals().bytes);
+
+        // Files are read again where they are, rather than copied
+        let input = [PathBuf::from("test/pages.json")];
+        assert_eq!(&input[..], input::spool(&input).unwrap().paths());
+    }
+
+    #[test]
+    fn slot_sizes() {
+        let input = &[PathBuf::from("test/shapes.json")];
+        let bytes = |slot_sizes, missing_memsize| {
+            let options = parse::Options {
+                slot_sizes,
+                missing_memsize,
+                ..Default::default()
+            };
+            let (root, graph) =
+                parse::parse(input::open(input).unwrap(), false, &[], options).unwrap();
+            analyze::analyze(root, root, graph, false, Default::default())
+                .dominated_totals()
+                .bytes
+        };
+        // Only the string, with no memsize, is smaller than its slot
+        assert_eq!(bytes(false, false) + 80, bytes(true, false));
+        // The symbol has no memsize or slot size either, so it gets the
+        // default one
+        assert_eq!(bytes(false, false) + 80 + 40, bytes(false, true));
+        assert_eq!(bytes(true, false) + 40, bytes(true, true));
+    }
+
+    #[test]
+    fn generations_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::GenerationsReport::new(&analysis, 10);
+
+        let rows: Vec<(&str, usize, usize)> = report
+            .generations
+            .largest
+            .iter()
+            .map(|r| (r.generations.as_str(), r.stats.count, r.stats.bytes))
+            .collect();
+        assert_eq!(
+            vec![
+                ("0-9", 2, 80),
+                ("10-19", 1, 320),
+                ("20-29", 2, 80),
+                ("30-39", 2, 80)
+            ],
+            rows
+        );
+        // The classes, allocated before tracing started
+        assert_eq!(3, report.untraced.count);
+
+        let report = report::GenerationsReport::new(&analysis, 1);
+        assert_eq!("5", report.generations.largest[0].generations);
+
+        let target = analysis.index_of(0x7f0000001000).unwrap();
+        let explained = report::ExplainReport::new(&analysis, target, None);
+        assert_eq!(Some(10), explained.generation);
+        assert_eq!(
+            Some("app/cache.rb:3 in initialize"),
+            explained.location.as_deref()
+        );
+    }
+
+    #[test]
+    fn gem_resolution() {
+        let lockfile = gems::Lockfile::open(Path::new("test/Gemfile.lock")).unwrap();
+        let gems = "/usr/local/bundle/ruby/3.2.0";
+        let files = [
+            format!("{}/gems/rack-3.0.8/lib/rack/request.rb", gems),
+            // The version includes the platform
+            format!("{}/gems/nokogiri-1.15.4-x86_64-linux/lib/nokogiri.rb", gems),
+            // Several gems checked out from one repository
+            format!(
+                "{}/bundler/gems/rails-0123456789ab/actionpack/lib/action_controller.rb",
+                gems
+            ),
+            format!("{}/bundler/gems/rails-0123456789ab/lib/rails.rb", gems),
+            "/app/engines/billing/app/models/invoice.rb".to_string(),
+            "/app/app/models/user.rb".to_string(),
+        ];
+        let resolve = |lockfile| {
+            files
+                .iter()
+                .map(|file| gems::gem_of(file, lockfile))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec![
+                Some("rack (3.0.8)".to_string()),
+                Some("nokogiri (1.15.4-x86_64-linux)".to_string()),
+                Some("actionpack (7.2.0.alpha)".to_string()),
+                Some("rails (7.2.0.alpha)".to_string()),
+                Some("billing (0.1.0)".to_string()),
+                None,
+            ],
+            resolve(Some(&lockfile))
+        );
+        // Guessing from the paths alone gets neither the git nor path gems
+        // right, taking the revision for a version
+        assert_eq!(
+            vec![
+                Some("rack (3.0.8)".to_string()),
+                Some("nokogiri (1.15.4-x86_64-linux)".to_string()),
+                Some("rails (0123456789ab)".to_string()),
+                Some("rails (0123456789ab)".to_string()),
+                None,
+                None,
+            ],
+            resolve(None)
+        );
+    }
+
+    #[test]
+    fn group_by_location() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let group = |group_by| {
+            let report = report::SummaryReport::new(
+                &analysis,
+                None,
+                report::Selection {
+                    top_types: 10,
+                    top_retainers: 0,
+                    sort: analyze::Sort::Bytes,
+                    filter: Default::default(),
+                    group_by,
+                },
+            );
+            let rows = |table: &report::Table<report::KindRow>| {
+                table
+                    .largest
+                    .iter()
+                    .map(|r| (r.kind.clone(), r.stats.count, r.stats.bytes))
+                    .collect::<Vec<_>>()
+            };
+            (rows(&report.live_by_kind), rows(&report.retained_by_kind))
+        };
+
+        let (live, retained) = group(analyze::GroupBy::File);
+        assert_eq!(("app/user.rb".to_string(), 4, 160), live[2]);
+        // The Hash retains the strings in it, allocated elsewhere
+        assert_eq!(("app/cache.rb".to_string(), 5, 480), retained[1]);
+
+        let (live, retained) = group(analyze::GroupBy::Location);
+        assert_eq!(("app/cache.rb:3".to_string(), 1, 320), live[1]);
+        // The root and classes, allocated before tracing started
+        assert_eq!(("(unknown)".to_string(), 6, 2800), live[0]);
+        assert_eq!(("app/boot.rb:1".to_string(), 2, 80), retained[3]);
+
+        // Both lines of the same method, one object retaining the other
+        let (live, retained) = group(analyze::GroupBy::Method);
+        assert_eq!(("app/boot.rb in boot".to_string(), 2, 80), live[3]);
+        assert_eq!(("app/boot.rb in boot".to_string(), 2, 80), retained[3]);
+    }
+
+    #[test]
+    fn generation_range() {
+        let (root, graph) = parse::parse(
+            input::open(&[PathBuf::from("test/traced_after.json")]).unwrap(),
+            false,
+            &[],
+            Default::default(),
+        )
+        .unwrap();
+        let generations = analyze::GenerationRange {
+            min: Some(20),
+            max: None,
+        };
+        let options = analyze::Options {
+            generations,
+            gemfile_lock: None,
+        };
+        let analysis = analyze::analyze(root, root, graph, false, options);
+
+        // Only the four strings in the Hash were allocated after generation
+        // 20, besides the root and its two categories
+        let totals = analysis.dominated_totals();
+        assert_eq!((7, 160), (totals.count, totals.bytes));
+        let hash = analysis.index_of(0x7f0000001000).unwrap();
+        assert_eq!(160, analysis.retained_stats(hash).bytes);
+
+        let dom_graph = analysis.dominator_graph(0.1, Units::default(), Default::default());
+        assert!(dom_graph
+            .raw_nodes()
+            .iter()
+            .all(|n| n.weight.kind != "User" && n.weight.kind != "Class"));
+
+        let opt = Opt::from_iter(&["reap", "dot", "heap.json", "--min-generation", "20"]);
+        assert_eq!(Some(20), opt.min_generation);
+    }
+
+    #[test]
+    fn thread_retention() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::ThreadsReport::new(
+            &analysis,
+            report::Selection {
+                top_types: 0,
+                top_retainers: 3,
+                sort: analyze::Sort::Bytes,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+
+        let threads = &report.threads.largest;
+        assert_eq!(3, threads.len());
+        assert_eq!("Thread[0x7f83df87dc40]", threads[0].label);
+        assert_eq!(1053052, threads[0].retained_bytes);
+        assert!(threads
+            .windows(2)
+            .all(|w| w[0].retained_bytes >= w[1].retained_bytes));
+
+        assert_eq!(30, report.stack.unwrap().count);
+    }
+
+    #[test]
+    fn largest_objects() {
+        let (_, graph) = parse::parse(
+            input::open(&[PathBuf::from("test/heap.json")]).unwrap(),
+            false,
+            &[],
+            Default::default(),
+        )
+        .unwrap();
+        let report = report::LargestReport::new(&graph, 3, &analyze::KindFilter::default());
+
+        let objects = &report.objects.largest;
+        assert_eq!(3, objects.len());
+        assert_eq!("Thread", objects[0].kind);
+        assert!(objects.windows(2).all(|w| w[0].bytes >= w[1].bytes));
+        // The root and its 4 categories aren't objects
+        assert_eq!(graph.node_count() - 8, report.objects.rest.count);
+    }
+
+    #[test]
+    fn duplicate_strings() {
+        let strings =
+            parse::string_values(input::open(&[PathBuf::from("test/heap.json")]).unwrap()).unwrap();
+        let copies: usize = strings.values().map(|s| s.count).sum();
+        assert_eq!(9406, copies);
+
+        let report = report::DuplicatesReport::new(strings, 2);
+        let top = &report.duplicates.largest[0];
+        assert_eq!("", top.value);
+        assert_eq!(110, top.copies);
+        assert_eq!(top.total_bytes / 110 * 109, top.wasted_bytes);
+        assert_eq!("\"\"", top.to_string());
+        assert_eq!(
+            report.wasted.bytes,
+            report
+                .duplicates
+                .largest
+                .iter()
+                .map(|d| d.wasted_bytes)
+                .sum::<usize>()
+                + report.duplicates.rest.bytes
+        );
+    }
+
+    #[test]
+    fn string_storage() {
+        let strings =
+            parse::find_all_strings(input::open(&[PathBuf::from("test/heap.json")]).unwrap())
+                .unwrap();
+        assert_eq!(10285, strings.len());
+        let report = report::StringsReport::new(&strings);
+
+        let storage: Vec<(&str, usize)> = report
+            .storage
+            .largest
+            .iter()
+            .map(|r| (r.kind.as_str(), r.stats.count))
+            .collect();
+        assert_eq!(
+            vec![
+                ("frozen", 7776),
+                ("not frozen", 10285 - 7776),
+                ("embedded", 7979),
+                ("shared", 443),
+                ("own buffer", 10285 - 7979 - 443),
+            ],
+            storage
+        );
+        assert_eq!(10285, report.total.count);
+        assert!(report.freezable.count > 0);
+        assert!(report.freezable.bytes < report.total.bytes);
+    }
+
+    #[test]
+    fn interned_strings() {
+        let strings =
+            parse::find_all_strings(input::open(&[PathBuf::from("test/heap.json")]).unwrap())
+                .unwrap();
+        let report = report::FstringsReport::new(&strings, 3);
+        assert_eq!(5686, report.fstrings.count);
+        assert_eq!(10285, report.strings.count);
+
+        let largest = &report.largest.largest;
+        assert_eq!(3, largest.len());
+        assert!(largest.windows(2).all(|w| w[0].bytes >= w[1].bytes));
+        assert_eq!(5686 - 3, report.largest.rest.count);
+        // Untraced, so there's no telling which are newest
+        assert!(report.newest.is_none());
+
+        let strings = parse::find_all_strings(
+            input::open(&[PathBuf::from("test/traced_after.json")]).unwrap(),
+        )
+        .unwrap();
+        let report = report::FstringsReport::new(&strings, 3);
+        assert_eq!(0, report.fstrings.count);
+    }
+
+    #[test]
+    fn symbol_census() {
+        let symbols =
+            parse::find_symbols(input::open(&[PathBuf::from("test/heap.json")]).unwrap()).unwrap();
+        let report = report::SymbolsReport::new(symbols, 2, 3);
+        assert_eq!(4, report.symbols.count);
+        assert_eq!(160, report.symbols.bytes);
+        assert!(report.too_many());
+        assert_eq!(2, report.largest.largest.len());
+        assert_eq!(2, report.largest.rest.count);
+        // Not traced, so there's no telling which are newest
+        assert!(report.newest.is_none());
+
+        let mut out = Vec::new();
+        report::write(&report, &mut out, report::Format::Table, Units::default()).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("4 symbols using 160 B"));
+        assert!(out.contains("Warning: more than 3 symbols"));
+    }
+
+    #[test]
+    fn csv_summary_report() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let report = report::SummaryReport::new(
+            &analysis,
+            None,
+            report::Selection {
+                top_types: 1,
+                top_retainers: 1,
+                sort: analyze::Sort::Bytes,
+                filter: analyze::KindFilter::default(),
+                group_by: analyze::GroupBy::Kind,
+            },
+        );
+
+        let dir = temp_path("csv");
+        let written = report.write_csv(&dir).unwrap();
+        assert_eq!(4, written.len());
+
+        let mut retainers = String::new();
+        File::open(dir.join("retainers.csv"))
+            .unwrap()
+            .read_to_string(&mut retainers)
+            .unwrap();
+        let lines: Vec<&str> = retainers.lines().collect();
+        assert_eq!(
+            vec![
+                "address,kind,label,self_bytes,retained_bytes,slot_bytes,malloc_bytes,count,depth",
+                "0x0,ROOT,root,0,3360,400,2960,13,0",
+                ",,...,,6920,1000,5920,27,",
+            ],
+            lines
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dominator_tree() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let tree = analysis.dominator_tree(0.005);
+
+        // Same nodes as the relevant dominator subgraph, as a tree
+        fn count_nodes(node: &analyze::TreeNode) -> usize {
+            1 + node.children.iter().map(count_nodes).sum::<usize>()
+        }
+        assert_eq!(34, count_nodes(&tree));
+
+        assert_eq!("root", tree.label);
+        assert_eq!(3439119, tree.retained_bytes);
+        assert_eq!("root[vm]", tree.children[0].label);
+        assert_eq!("0x7f83df87dc40", tree.children[0].children[0].address);
+        assert!(tree
+            .children
+            .windows(2)
+            .all(|w| w[0].retained_bytes >= w[1].retained_bytes));
+    }
+
+    #[test]
+    fn truncated_dominator_tree() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let mut tree = analysis.dominator_tree(0.0);
+        tree.truncate(2);
+
+        assert_eq!(3360, tree.retained_bytes);
+        let children = tree.children.iter().flat_map(|c| c.children.iter());
+        assert!(children.clone().all(|c| c.children.is_empty()));
+        let hash = children
+            .into_iter()
+            .find(|c| c.address == "0x7f0000001000")
+            .unwrap();
+        assert_eq!(480, hash.retained_bytes);
+        assert_eq!(5, hash.count);
+
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!("root", json["label"]);
+        assert!(json["children"][0]["children"][0]["children"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn text_dominator_tree() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let mut tree = analysis.dominator_tree(0.1);
+        tree.truncate(2);
+
+        let mut out = Vec::new();
+        report::write(&tree, &mut out, report::Format::Table, Units::default()).unwrap();
+        assert_eq!(
+            "root: 3.3 KiB (13 objects; 400 B in slots, 2.9 KiB malloc'd)\n\
+             ├── root[global_tbl]: 2.7 KiB (4 objects; 120 B in slots, 2.6 KiB malloc'd)\n\
+             │   ├── String[0x7f0000000100][CLASS]: 1000 B (1 objects; 40 B in slots, 960 B malloc'd)\n\
+             │   ├── Hash[0x7f0000000110][CLASS]: 1000 B (1 objects; 40 B in slots, 960 B malloc'd)\n\
+             │   └── User[0x7f0000000120][CLASS]: 800 B (1 objects; 40 B in slots, 760 B malloc'd)\n\
+             └── root[vm]: 560 B (8 objects; 280 B in slots, 280 B malloc'd)\n\
+             \x20   └── Hash[0x7f0000001000][size=4]: 480 B (5 objects; 200 B in slots, 280 B malloc'd)\n",
+            String::from_utf8(out).unwrap()
+        );
+
+        let tree = analysis.dominator_tree(0.0);
+        let mut out = Vec::new();
+        report::write(&tree, &mut out, report::Format::Markdown, Units::default()).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(13, out.lines().count());
+        assert!(out.contains(
+            "\n      - String[0x7f0000002100][config]: 40 B (1 objects; 40 B in slots, 0 B malloc'd)\n"
+        ));
+    }
+
+    #[test]
+    fn byte_units() {
+        let units = Units::default();
+        assert_eq!("1000 B", units.bytes(1000));
+        assert_eq!("3.3 KiB", units.bytes(3360));
+        assert_eq!("3.3 MiB", units.bytes(3439119));
+        assert_eq!("-40 B", units.signed_bytes(-40));
+
+        assert_eq!("3.280 MiB", Units::new(false, 3).bytes(3439119));
+        assert_eq!("3 MiB", Units::new(false, 0).bytes(3439119));
+        assert_eq!("3439119 B", Units::new(true, 1).bytes(3439119));
+        assert_eq!("+208 B", Units::new(true, 1).signed_bytes(208));
+    }
+
+    #[test]
+    fn cli_subcommands() {
+        let opt = Opt::from_iter(&["reap", "dot", "heap.json", "-t", "0.01"]);
+        match opt.command {
+            Some(Command::Dot {
+                input,
+                threshold,
+                output,
+                ..
+            }) => {
+                assert_eq!(vec![PathBuf::from("heap.json")], input);
+                assert_eq!(0.01, threshold);
+                assert!(is_stdout(&output));
+            }
+            other => panic!("Unexpected command {:?}", other),
+        }
+
+        let opt = Opt::from_iter(&["reap", "dot", "heap.json", "--root-address", "0x7f"]);
+        match opt.command {
+            Some(Command::Dot { root, .. }) => assert_eq!(Some("0x7f".to_string()), root),
+            other => panic!("Unexpected command {:?}", other),
+        }
+
+        // Graphs can be rendered, or written in other formats, instead of dot
+        let opt = Opt::from_iter(&["reap", "dot", "heap.json", "--svg", "out.svg"]);
+        match opt.command {
+            Some(Command::Dot { svg, render, .. }) => {
+                assert_eq!(Some(PathBuf::from("out.svg")), svg);
+                assert!(render.is_none());
+            }
+            other => panic!("Unexpected command {:?}", other),
+        }
+        assert!(Opt::from_iter_safe(&[
+            "reap",
+            "dot",
+            "heap.json",
+            "--svg",
+            "a.svg",
+            "--mermaid",
+            "a.mmd"
+        ])
+        .is_err());
+
+        // As can profiles, instead of a flamegraph
+        let opt = Opt::from_iter(&[
+            "reap",
+            "flamegraph",
+            "heap.json",
+            "-o",
+            "a.pb.gz",
+            "--pprof",
+        ]);
+        assert!(matches!(
+            opt.command,
+            Some(Command::Flamegraph { pprof: true, .. })
+        ));
+        assert!(Opt::from_iter_safe(&[
+            "reap",
+            "flamegraph",
+            "heap.json",
+            "-o",
+            "a",
+            "--folded",
+            "--speedscope"
+        ])
+        .is_err());
+
+        // --format applies to every subcommand, before or after its name
+        let opt = Opt::from_iter(&["reap", "summary", "heap.json", "--format", "json"]);
+        assert!(matches!(opt.command, Some(Command::Summary { .. })));
+        assert_eq!(report::Format::Json, opt.format);
+        let opt = Opt::from_iter(&["reap", "--format", "yaml", "tree", "heap.json"]);
+        assert_eq!(report::Format::Yaml, opt.format);
+
+        // "-" reads the dump from standard input, alone or as a shard
+        let opt = Opt::from_iter(&["reap", "summary", "shard.json", "-"]);
+        match opt.command {
+            Some(Command::Summary { input, .. }) => {
+                assert_eq!(vec![PathBuf::from("shard.json"), PathBuf::from("-")], input)
+            }
+            other => panic!("Unexpected command {:?}", other),
+        }
+
+        // The original single-command invocation still works
+        let opt = Opt::from_iter(&["reap", "heap.json", "-d", "out.dot", "-c", "3"]);
+        assert!(opt.command.is_none());
+        assert_eq!(vec![PathBuf::from("heap.json")], opt.input);
+        assert_eq!(3, opt.count);
+        let opt = Opt::from_iter(&["reap", "heap-0.json", "heap-1.json", "--svg", "out.svg"]);
+        assert!(opt.command.is_none());
+        assert_eq!(
+            vec![PathBuf::from("heap-0.json"), PathBuf::from("heap-1.json")],
+            opt.input
+        );
+    }
+
+    #[test]
+    fn dot_to_stdout() {
+        assert!(is_stdout(Path::new("-")));
+        assert!(!is_stdout(Path::new("out.dot")));
+        assert!(!is_stdout(Path::new("./-")));
+    }
+
+    #[test]
+    fn render_formats() {
+        assert_eq!("png", render_format(Path::new("/tmp/out.png")).unwrap());
+        assert_eq!("svg", render_format(Path::new("out.SVG")).unwrap());
+        assert!(render_format(Path::new("out")).is_err());
+    }
+
+    #[test]
+    fn svg_output() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let dom_graph =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.1), Units::default());
+        let (_, svg) = write_and_read("svg", |f| svg::write_svg(&dom_graph, f).unwrap());
+
+        assert!(svg.contains("<svg "));
+        assert!(
+            svg.contains("Hash[0x7f0000001000][size=4]: 320 B self, 160 B refs, 5 objects, 14.3%")
+        );
+    }
+
+    #[test]
+    fn mermaid_output() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let dom_graph =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.1), Units::default());
+        let (_, mermaid) =
+            write_and_read("mmd", |f| mermaid::write_mermaid(&dom_graph, f).unwrap());
+
+        let lines: Vec<&str> = mermaid.lines().collect();
+        assert_eq!("graph TD", lines[0]);
+        assert_eq!(1 + 7 + 6, lines.len());
+        assert!(lines.iter().any(|l| l.ends_with(
+            "[\"Hash[0x7f0000001000][size=4]: 320 B self, 160 B refs, 5 objects, 14.3%\"]"
+        )));
+        assert_eq!(6, lines.iter().filter(|l| l.contains(" --> ")).count());
+    }
+
+    #[test]
+    fn collapsed_chains() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let options = analyze::GraphOptions {
+            collapse_chains: true,
+            ..Default::default()
+        };
+        let full =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.005), Units::default());
+        let dom_graph = analysis.dominator_graph(0.005, Units::default(), options);
+
+        assert!(dom_graph.node_count() < full.node_count());
+        assert_eq!(dom_graph.node_count() - 1, dom_graph.edge_count());
+        let chains: Vec<String> = dom_graph
+            .raw_nodes()
+            .iter()
+            .map(|n| n.weight.to_string())
+            .filter(|l| l.contains(" more\n"))
+            .collect();
+        assert!(!chains.is_empty());
+        assert!(chains.iter().all(|l| l.lines().count() == 3));
+
+        let unchanged = analysis.dominator_graph(0.005, Units::default(), Default::default());
+        assert_eq!(full.node_count(), unchanged.node_count());
+    }
+
+    #[test]
+    fn aggregated_siblings() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let options = analyze::GraphOptions {
+            aggregate_siblings: true,
+            ..Default::default()
+        };
+        let dom_graph = analysis.dominator_graph(0.005, Units::default(), options);
+        assert_eq!(dom_graph.node_count() - 1, dom_graph.edge_count());
+
+        let summaries: Vec<String> = dom_graph
+            .raw_nodes()
+            .iter()
+            .map(|n| n.weight.to_string())
+            .filter(|l| l.contains(" ×"))
+            .collect();
+        assert!(summaries.contains(&"String ×663, 32.6 KiB, 1.0%".to_string()));
+        assert!(summaries.contains(&"Thread ×2, 2.0 MiB, 61.1%".to_string()));
+    }
+
+    #[test]
+    fn class_graph() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let options = analyze::GraphOptions {
+            by_class: true,
+            ..Default::default()
+        };
+        let class_graph = analysis.dominator_graph(0.02, Units::default(), options);
+
+        let labels: Vec<String> = class_graph
+            .raw_nodes()
+            .iter()
+            .map(|n| n.weight.to_string())
+            .collect();
+        assert_eq!(11, labels.len());
+        assert!(labels
+            .contains(&"String ×9235: 451.7 KiB self, 0 B refs, 9235 objects, 13.5%".to_string()));
+
+        let kinds = |e: &petgraph::graph::Edge<References, usize>| {
+            (
+                class_graph[e.source()].kind.as_str(),
+                class_graph[e.target()].kind.as_str(),
+            )
+        };
+        let edge = class_graph
+            .raw_edges()
+            .iter()
+            .find(|e| kinds(e) == ("ARRAY", "String"))
+            .unwrap();
+        assert_eq!(References(3051), edge.weight);
+        assert_eq!("×3051", edge.weight.to_string());
+        assert!(class_graph
+            .raw_edges()
+            .iter()
+            .all(|e| e.source() != e.target()));
+    }
+
+    #[test]
+    fn dot_colors() {
+        assert_eq!("#98df8a", dot::kind_color("ARRAY"));
+        assert_eq!(dot::kind_color("Array"), dot::kind_color("ARRAY"));
+        assert_eq!(dot::kind_color("User"), dot::kind_color("User"));
+        assert_ne!(dot::kind_color("User"), dot::kind_color("Account"));
+
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let dom_graph =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.1), Units::default());
+        let mut out = Vec::new();
+        dot::write_dot(&dom_graph, Default::default(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.starts_with("digraph {\n"));
+        assert!(out.contains(
+            "[label=\"Hash[0x7f0000001000][size=4]: 320 B self, 160 B refs, 5 objects, 14.3%\", fillcolor=\"#ffbb78\", tooltip="
+        ));
+        assert_eq!(6, out.matches(" -> ").count());
+    }
+
+    #[test]
+    fn dot_tooltips() {
+        let input = &[PathBuf::from("test/traced_after.json")];
+        let analysis = parse(input, None, false, &ParseOptions::default()).unwrap();
+        let dom_graph = analysis.relevant_dominator_subgraph(0, Units::default());
+        let values = graph_string_values(input, &dom_graph).unwrap();
+        assert_eq!(5, values.len());
+
+        let dot_options = dot::Options {
+            values: Some(&values),
+            url: Some("https://example.com/objects/{address}"),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        dot::write_dot(&dom_graph, dot_options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let alice = out.lines().find(|l| l.contains("[alice]")).unwrap();
+        assert!(alice.contains(
+            "\\nString at 0x7f0000001100, 40 B\\nallocated at app/user.rb:12\\nvalue: alice\""
+        ));
+        assert!(alice.ends_with(", URL=\"https://example.com/objects/0x7f0000001100\"]"));
+        let root = out.lines().find(|l| l.contains("label=\"root")).unwrap();
+        assert!(!root.contains("URL="));
+    }
+
+    #[test]
+    fn dot_legend() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let dom_graph =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.07), Units::default());
+        let dot_options = dot::Options {
+            legend: Some(0.07),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        dot::write_dot(&dom_graph, dot_options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let legend = &out[out.find("    subgraph cluster_legend {").unwrap()..];
+        assert!(legend.contains("objects retaining at least 7% of memory"));
+        assert!(legend.contains("<tr><td bgcolor=\"#ffbb78\">Hash</td></tr>"));
+        assert!(!legend.contains(">String<"));
+
+        let mut out = Vec::new();
+        dot::write_dot(&dom_graph, Default::default(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(!out.contains("legend"));
+        assert!(!out.contains("dir=back"));
+
+        let dot_options = dot::Options {
+            legend: Some(0.07),
+            invert_edges: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        dot::write_dot(&dom_graph, dot_options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\n    edge [dir=back];\n"));
+        assert!(out.contains("arrows: from each object to the one that dominates it"));
+    }
+
+    #[test]
+    fn dot_locations() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let dom_graph =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.07), Units::default());
+        let dot_options = dot::Options {
+            locations: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        dot::write_dot(&dom_graph, dot_options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let hash = out
+            .lines()
+            .find(|l| l.contains("Hash[0x7f0000001000]"))
+            .unwrap();
+        assert!(hash.contains(", 14.3%\\napp/cache.rb:3\", fillcolor="));
+        // Objects allocated before tracing started have no location to show
+        let class = out.lines().find(|l| l.contains("[CLASS]")).unwrap();
+        assert!(class.contains("%\", fillcolor="));
+    }
+
+    #[test]
+    fn split_dot_files() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let dom_graph = analysis.dominator_graph(0.05, Units::default(), Default::default());
+        let dir = temp_path("split");
+        write_split_dot_files(&dom_graph, Default::default(), &dir).unwrap();
+
+        let mut names: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        let mut gem = String::new();
+        File::open(dir.join("Module-0x7f83df856898.dot"))
+            .unwrap()
+            .read_to_string(&mut gem)
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // The root's children: two threads, an array, the Object class & Gem
+        assert_eq!(5, names.len());
+        assert!(names.contains(&"Thread-0x7f83df87dc40.dot".to_string()));
+        assert!(gem.starts_with("digraph {\n"));
+        assert!(gem.contains("Gem[0x7f83df856898][MODULE]"));
+        assert!(!gem.contains("label=\"root"));
+    }
+
+    #[test]
+    fn dot_namespace_clusters() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let options = analyze::GraphOptions {
+            by_class: true,
+            ..Default::default()
+        };
+        let class_graph = analysis.dominator_graph(0.02, Units::default(), options);
+        let dot_options = dot::Options {
+            cluster_namespaces: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        dot::write_dot(&class_graph, dot_options, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let cluster = out
+            .find("    subgraph cluster_0 {\n        label=\"Gem\";\n")
+            .unwrap();
+        let spec = out.find("label=\"Gem::Specification ×").unwrap();
+        let string = out.find("label=\"String ×").unwrap();
+        assert!(cluster < spec);
+        assert!(string < cluster);
+        assert_eq!(1, out.matches("subgraph").count());
+    }
+
+    #[test]
+    fn depth_limited_graph() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let options = analyze::GraphOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let dom_graph = analysis.dominator_graph(0.005, Units::default(), options);
+
+        // The root and its relevant children
+        let root = analysis.root();
+        let children = analysis
+            .dominated_subgraph()
+            .node_indices()
+            .filter(|i| analysis.dominator(*i) == Some(root))
+            .filter(|i| analysis.retained_stats(*i).bytes >= analysis.threshold_bytes(0.005))
+            .count();
+        assert_eq!(1 + children, dom_graph.node_count());
+        assert_eq!(children, dom_graph.edge_count());
+
+        let pruned: Vec<String> = dom_graph
+            .raw_nodes()
+            .iter()
+            .map(|n| n.weight.to_string())
+            .filter(|l| l.contains("\npruned: "))
+            .collect();
+        assert!(!pruned.is_empty());
+        assert!(pruned.contains(
+            &"Gem[0x7f83df856898][MODULE]: 3.9 KiB self, 435.8 KiB refs, 5428 objects, 13.1%\npruned: 435.8 KiB in 5427 objects"
+                .to_string()
+        ));
+
+        let opt = Opt::from_iter(&["reap", "dot", "heap.json", "--max-depth", "2"]);
+        assert!(matches!(
+            opt.command,
+            Some(Command::Dot {
+                max_depth: Some(2),
+                ..
+            })
+        ));
+        let conflict = Opt::clap().get_matches_from_safe([
+            "reap",
+            "dot",
+            "heap.json",
+            "--max-depth",
+            "2",
+            "--by-class",
+        ]);
+        assert!(conflict.is_err());
+    }
+
+    #[test]
+    fn capped_graph() {
+        let analysis = parse(
+            &[PathBuf::from("test/heap.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let full =
+            analysis.relevant_dominator_subgraph(analysis.threshold_bytes(0.005), Units::default());
+        let options = analyze::GraphOptions {
+            max_nodes: Some(10),
+            ..Default::default()
+        };
+        let dom_graph = analysis.dominator_graph(0.005, Units::default(), options);
+        assert_eq!(dom_graph.node_count() - 1, dom_graph.edge_count());
+
+        let (others, objects): (Vec<&Object>, Vec<&Object>) = dom_graph
+            .raw_nodes()
+            .iter()
+            .map(|n| &n.weight)
+            .partition(|obj| obj.kind == "other");
+        assert!(objects.len() <= 10);
+        assert!(!others.is_empty());
+        assert!(others[0].to_string().starts_with("other ("));
+
+        // Every relevant object is drawn, or counted in an "other" node
+        let other_objects: usize = others
+            .iter()
+            .map(|obj| {
+                let label = obj.to_string();
+                label["other (".len()..label.find(" objects").unwrap()]
+                    .parse::<usize>()
+                    .unwrap()
+            })
+            .sum();
+        assert!(objects.len() + other_objects >= full.node_count());
+
+        let unchanged = analysis.dominator_graph(0.005, Units::default(), Default::default());
+        assert_eq!(full.node_count(), unchanged.node_count());
+    }
+
+    #[test]
+    fn interactive_graph_page() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let tree = analysis.dominator_tree(0.0);
+        let (_, page) = write_and_read("html", |f| {
+            html::write_graph(&tree, "reap: </title>", f).unwrap()
+        });
+
+        assert!(page.contains("<title>reap: &lt;/title&gt;</title>"));
+        assert!(page.contains("<svg id=\"graph\"></svg>"));
+        assert!(page.contains("\"label\":\"Hash[0x7f0000001000][size=4]\""));
+        assert_eq!(1, page.matches("id=\"tree\"").count());
+    }
+
+    #[test]
+    fn speedscope_profile() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let file = speedscope::profile(&analysis, "test", false);
+        let profile = file.profile();
+
+        // One sample per reachable object other than the root
+        assert_eq!(12, profile.samples.len());
+        assert_eq!(13, file.shared.frames.len());
+        assert_eq!(3360, profile.weights.iter().sum::<usize>());
+
+        let dave = file
+            .shared
+            .frames
+            .iter()
+            .position(|f| f.name == "String[0x7f0000001118][dave]")
+            .unwrap();
+        let stack = profile
+            .samples
+            .iter()
+            .find(|s| s.last() == Some(&dave))
+            .unwrap();
+        let names: Vec<&str> = stack
+            .iter()
+            .map(|&i| file.shared.frames[i].name.as_str())
+            .collect();
+        assert_eq!(
+            vec![
+                "root",
+                "root[vm]",
+                "Hash[0x7f0000001000][size=4]",
+                "String[0x7f0000001118][dave]"
+            ],
+            names
+        );
+
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            true,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let class_names = speedscope::profile(&analysis, "test", true);
+        assert!(class_names.shared.frames.len() < file.shared.frames.len());
+    }
+
+    #[test]
+    fn pprof_profile() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let profile = pprof::profile(&analysis, false);
+
+        let filename = temp_path("pb.gz");
+        pprof::write_pprof(&profile, &filename).unwrap();
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(File::open(&filename).unwrap())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        std::fs::remove_file(&filename).unwrap();
+        assert_eq!(profile, decompressed);
+
+        // Starts with the "objects/count" sample type, which refers to the
+        // string table by index
+        assert_eq!(&[0x0a, 0x04, 0x08, 0x01, 0x10, 0x02], &profile[0..6]);
+
+        let contains = |s: &str| profile.windows(s.len()).any(|w| w == s.as_bytes());
+        assert!(contains("Hash[0x7f0000001000][size=4]"));
+        assert!(contains("app/user.rb"));
+    }
+
+    #[test]
+    fn graphml_export() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let ((nodes, edges), graphml) = write_and_read("graphml", |f| {
+            graphml::write_graphml(&analysis, 0, f).unwrap()
+        });
+
+        assert_eq!(13, nodes);
+        assert_eq!(nodes, graphml.matches("<node ").count());
+        assert_eq!(analysis.dominated_subgraph().edge_count(), edges);
+        assert_eq!(edges, graphml.matches("<edge ").count());
+        assert!(graphml.contains(
+            "<node id=\"0x7f0000001000\">\n<data key=\"label\">Hash[0x7f0000001000][size=4]</data>\n\
+             <data key=\"kind\">Hash</data>\n<data key=\"self_bytes\">320</data>\n\
+             <data key=\"retained_bytes\">480</data>"
+        ));
+    }
+
+    #[test]
+    fn gexf_export() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+
+        // Only the root, the Hash and the classes retain at least 480 bytes
+        let threshold_bytes = analysis.threshold_bytes(480.0 / 3360.0);
+        let ((nodes, edges), gexf) = write_and_read("gexf", |f| {
+            gexf::write_gexf(&analysis, threshold_bytes, f).unwrap()
+        });
+
+        assert_eq!((7, 6), (nodes, edges));
+        assert_eq!(7, gexf.matches("<node ").count());
+        assert!(gexf.contains(
+            "<node id=\"0x7f0000001000\" label=\"Hash[0x7f0000001000][size=4]\"><attvalues>\
+             <attvalue for=\"0\" value=\"Hash\"/><attvalue for=\"1\" value=\"320\"/>\
+             <attvalue for=\"2\" value=\"480\"/><attvalue for=\"3\" value=\"5\"/>"
+        ));
+        assert!(gexf.contains("<edge id=\"3\" "));
+    }
+
+    #[test]
+    fn neo4j_export() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let dir = temp_path("neo4j");
+        let (nodes, edges) = neo4j::write_neo4j(&analysis, 0, &dir).unwrap();
+        assert_eq!((13, 12), (nodes, edges));
+
+        let read = |name: &str| {
+            let mut contents = String::new();
+            File::open(dir.join(name))
+                .unwrap()
+                .read_to_string(&mut contents)
+                .unwrap();
+            contents
+        };
+        let nodes = read("nodes.csv");
+        let relationships = read("relationships.csv");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            Some("address:ID,kind,label,self_bytes:long,retained_bytes:long,count:long,:LABEL"),
+            nodes.lines().next()
+        );
+        assert!(nodes.contains("\n0x0,ROOT,root,0,3360,13,Object;Root\n"));
+        assert!(nodes.contains("\n0x1,ROOT,root[vm],0,560,8,Object;Root\n"));
+        assert!(
+            nodes.contains("\n0x7f0000001000,Hash,Hash[0x7f0000001000][size=4],320,480,5,Object\n")
+        );
+        assert!(relationships.contains("\n0x7f0000001000,0x7f0000001118,REFERENCES,1\n"));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_export() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::RowAccessor;
+
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let dir = temp_path("parquet");
+        let (objects, references) = columnar::write_parquet(&analysis, 0, &dir).unwrap();
+        assert_eq!((13, 12), (objects, references));
+
+        let reader =
+            SerializedFileReader::new(File::open(dir.join("objects.parquet")).unwrap()).unwrap();
+        assert_eq!(13, reader.metadata().file_metadata().num_rows());
+        let hash = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|r| r.unwrap())
+            .find(|r| r.get_long(0).unwrap() == 0x7f0000001000)
+            .unwrap();
+        assert_eq!("Hash", hash.get_string(1).unwrap());
+        assert_eq!(480, hash.get_long(4).unwrap());
+        assert_eq!(1, hash.get_long(6).unwrap());
+
+        let reader =
+            SerializedFileReader::new(File::open(dir.join("references.parquet")).unwrap()).unwrap();
+        assert_eq!(12, reader.metadata().file_metadata().num_rows());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn annotated_jsonl() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let (annotated, jsonl) = write_and_read("jsonl", |f| {
+            annotate::write_annotated(
+                &analysis,
+                input::open(&[PathBuf::from("test/traced_after.json")]).unwrap(),
+                f,
+            )
+            .unwrap()
+        });
+        assert_eq!(10, annotated);
+
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(13, lines.len());
+
+        let find = |address: &str| lines.iter().find(|l| l["address"] == address).unwrap();
+        let hash = find("0x7f0000001000");
+        assert_eq!(480, hash["retained_memsize"]);
+        assert!(hash["dominator"].is_null());
+        assert_eq!(4, hash["size"]);
+        assert_eq!("0x7f0000001000", find("0x7f0000001118")["dominator"]);
+
+        // Unreachable objects are copied as-is
+        assert!(find("0x7f0000003000").get("retained_memsize").is_none());
+    }
+
+    #[test]
+    fn chrome_heapsnapshot() {
+        let analysis = parse(
+            &[PathBuf::from("test/traced_after.json")],
+            None,
+            false,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let snapshot = heapsnapshot::snapshot(&analysis);
+        let json = serde_json::to_value(&snapshot).unwrap();
+
+        assert_eq!(13, json["snapshot"]["node_count"]);
+        assert_eq!(6 * 13, snapshot.nodes.len());
+        assert_eq!(
+            json["snapshot"]["edge_count"].as_u64().unwrap() as usize * 3,
+            snapshot.edges.len()
+        );
+
+        // The root comes first, as a synthetic node with edges to the
+        // categories of GC roots
+        let synthetic = json["snapshot"]["meta"]["node_types"][0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .position(|t| t == "synthetic")
+            .unwrap();
+        assert_eq!(synthetic as u64, snapshot.nodes[0]);
+        assert_eq!("root", snapshot.strings[snapshot.nodes[1] as usize]);
+        assert_eq!(2, snapshot.nodes[4]);
+
+        // Self sizes add up, and every edge points at the start of a node
+        let self_sizes: u64 = snapshot.nodes.chunks(6).map(|n| n[3]).sum();
+        assert_eq!(3360, self_sizes);
+        assert!(snapshot.edges.chunks(3).all(|e| e[2] % 6 == 0));
+        assert!(snapshot.strings.contains(&"Hash".to_string()));
     }
 }
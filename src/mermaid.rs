@@ -0,0 +1,51 @@
+use crate::object::*;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Mermaid labels are quoted, and support HTML-like entity codes in place of
+// characters which would otherwise end the label, and <br> for line breaks.
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('\n', "<br>")
+}
+
+// Writes a graph as a Mermaid flowchart, which GitHub renders natively in
+// ```mermaid blocks.
+pub fn write_mermaid(graph: &ReferenceGraph, filename: &Path) -> io::Result<()> {
+    let file = File::create(filename)?;
+    let mut out = io::BufWriter::new(file);
+
+    writeln!(out, "graph TD")?;
+    for i in graph.node_indices() {
+        writeln!(
+            out,
+            "    n{}[\"{}\"]",
+            i.index(),
+            escape_mermaid(&graph[i].to_string())
+        )?;
+    }
+    for e in graph.raw_edges() {
+        if e.weight == EDGE_WEIGHT {
+            writeln!(
+                out,
+                "    n{} --> n{}",
+                e.source().index(),
+                e.target().index()
+            )?;
+        } else {
+            writeln!(
+                out,
+                "    n{} -->|\"{}\"| n{}",
+                e.source().index(),
+                e.weight,
+                e.target().index()
+            )?;
+        }
+    }
+
+    out.flush()
+}
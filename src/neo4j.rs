@@ -0,0 +1,67 @@
+use crate::analyze::Analysis;
+use std::fs;
+use std::path::Path;
+
+// Headers in the format expected by `neo4j-admin database import`; see
+// https://neo4j.com/docs/operations-manual/current/tools/neo4j-admin/neo4j-admin-import/
+const NODES_HEADER: &[&str] = &[
+    "address:ID",
+    "kind",
+    "label",
+    "self_bytes:long",
+    "retained_bytes:long",
+    "count:long",
+    ":LABEL",
+];
+const RELATIONSHIPS_HEADER: &[&str] = &[":START_ID", ":END_ID", ":TYPE", "references:long"];
+
+// Writes nodes.csv & relationships.csv to `dir`, for bulk import into Neo4j.
+pub fn write_neo4j(
+    analysis: &Analysis,
+    threshold_bytes: usize,
+    dir: &Path,
+) -> csv::Result<(usize, usize)> {
+    fs::create_dir_all(dir)?;
+    let graph = analysis.dominated_subgraph();
+    let (indices, edges) = analysis.exported(threshold_bytes);
+
+    let mut node_count = 0;
+    let mut nodes = csv::Writer::from_path(dir.join("nodes.csv"))?;
+    nodes.write_record(NODES_HEADER)?;
+    for i in indices {
+        let obj = &graph[i];
+        let retained = analysis.retained_stats(i);
+        let node_label = if obj.is_root() {
+            "Object;Root"
+        } else {
+            "Object"
+        };
+        nodes.write_record(&[
+            format!("{:#x}", obj.address),
+            obj.kind.clone(),
+            obj.to_string(),
+            obj.bytes.to_string(),
+            retained.bytes.to_string(),
+            retained.count.to_string(),
+            node_label.to_string(),
+        ])?;
+        node_count += 1;
+    }
+    nodes.flush()?;
+
+    let mut edge_count = 0;
+    let mut relationships = csv::Writer::from_path(dir.join("relationships.csv"))?;
+    relationships.write_record(RELATIONSHIPS_HEADER)?;
+    for e in edges {
+        relationships.write_record(&[
+            format!("{:#x}", graph[e.source()].address),
+            format!("{:#x}", graph[e.target()].address),
+            "REFERENCES".to_string(),
+            e.weight.0.to_string(),
+        ])?;
+        edge_count += 1;
+    }
+    relationships.flush()?;
+
+    Ok((node_count, edge_count))
+}
@@ -1,27 +1,159 @@
-use bytesize::ByteSize;
+use crate::units::Units;
 use petgraph::{Directed, Graph};
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::Display;
 use std::hash::{Hash, Hasher};
 
+// The kind of the synthetic root and its categories, which stand for the whole
+// heap (or the part of it reachable from a category) rather than objects
+pub const ROOT_KIND: &str = "ROOT";
+
+// Whether stats keyed by `key`, a kind or a kind qualified by more, as in
+// "ROOT @ ...", are those of the root rather than objects of a type
+pub fn is_root_key(key: &str) -> bool {
+    key.strip_prefix(ROOT_KIND)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(" @ "))
+}
+
 #[derive(Debug, Clone)]
 pub struct Object {
     pub address: usize,
     pub bytes: usize,
+    // Of `bytes`, those in the object's slot in the GC heap, the rest being
+    // malloc'd
+    pub slot_bytes: usize,
     pub kind: String,
     pub label: Option<String>,
+    pub location: Option<Location>,
+    // GC generation the object was allocated in, when traced
+    pub generation: Option<usize>,
+    // Promoted to the old generation, having survived several GCs
+    pub old: bool,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+// Allocation site, present when the dump was taken with allocation tracing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub method: Option<String>,
+}
+
+// A dynamic symbol, with the GC generation it was created in when the dump
+// was taken with allocation tracing.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub address: usize,
+    pub value: String,
+    pub bytes: usize,
+    pub slot_bytes: usize,
+    pub generation: Option<usize>,
+}
+
+// An array or hash, with the least memory its elements need.
+#[derive(Debug, Clone)]
+pub struct Collection {
+    pub address: usize,
+    pub elements: usize,
+    pub bytes: usize,
+    pub min_bytes: usize,
+}
+
+// A string as dumped, with how it's stored.
+#[derive(Debug, Clone)]
+pub struct DumpedString {
+    pub address: usize,
+    pub value: Option<String>,
+    pub bytes: usize,
+    pub slot_bytes: usize,
+    pub frozen: bool,
+    // Pointing into another string's buffer rather than owning one
+    pub shared: bool,
+    // The value fits in the string's slot
+    pub embedded: bool,
+    // Interned in the VM's table of frozen strings, like literals
+    pub fstring: bool,
+    pub generation: Option<usize>,
+}
+
+// The heap slot an object takes up.
+#[derive(Debug, Clone, Copy)]
+pub struct Slot {
+    pub address: usize,
+    pub bytes: usize,
+    // Marked as unmovable during the last GC, e.g. because C code references
+    // the object, so compaction leaves it where it is
+    pub pinned: bool,
+}
+
+// A plain object's shape (Ruby 3.2+), which records which instance variables
+// it has, and how they're stored.
+#[derive(Debug, Clone)]
+pub struct ObjectShape {
+    pub address: usize,
+    pub shape_id: usize,
+    // Instance variables fit in the object's slot, rather than a separate
+    // buffer
+    pub embedded: bool,
+    // Too many shape transitions to share, so instance variables are kept in
+    // a hash table
+    pub too_complex: bool,
+    pub bytes: usize,
+    pub slot_bytes: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(into = "StatsFields")]
 pub struct Stats {
     pub count: usize,
     pub bytes: usize,
+    // Of `bytes`, those in GC heap slots
+    pub slot_bytes: usize,
+}
+
+// Stats as written out, with the malloc'd bytes next to the slot bytes
+#[derive(Serialize)]
+struct StatsFields {
+    count: usize,
+    bytes: usize,
+    slot_bytes: usize,
+    malloc_bytes: usize,
+}
+
+impl From<Stats> for StatsFields {
+    fn from(stats: Stats) -> StatsFields {
+        StatsFields {
+            count: stats.count,
+            bytes: stats.bytes,
+            slot_bytes: stats.slot_bytes,
+            malloc_bytes: stats.malloc_bytes(),
+        }
+    }
 }
 
 impl Object {
+    // Identifies what the object holds, to tell whether an object at the same
+    // address in a forked child is still the one inherited from the parent.
+    // Whether it's old counts too, since promoting an object writes to its
+    // slot.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (
+            &self.kind,
+            &self.label,
+            &self.location,
+            self.bytes,
+            self.old,
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
     pub fn stats(&self) -> Stats {
         Stats {
             count: 1,
             bytes: self.bytes,
+            slot_bytes: self.slot_bytes,
         }
     }
 
@@ -29,23 +161,68 @@ impl Object {
         Object {
             address: 0,
             bytes: 0,
-            kind: "ROOT".to_string(),
+            slot_bytes: 0,
+            kind: ROOT_KIND.to_string(),
             label: Some("root".to_string()),
+            location: None,
+            generation: None,
+            old: false,
+        }
+    }
+
+    // A category of GC roots, e.g. "vm" or "global_tbl", between the root and
+    // the objects those roots reference. Categories get small addresses no
+    // real object can have, counting up from 1.
+    pub fn root_category(name: &str, address: usize) -> Object {
+        Object {
+            address,
+            bytes: 0,
+            slot_bytes: 0,
+            kind: ROOT_KIND.to_string(),
+            label: Some(format!("root[{}]", name)),
+            location: None,
+            generation: None,
+            old: false,
+        }
+    }
+
+    // Stands in for an object that a partial dump references but leaves out
+    pub fn placeholder(address: usize) -> Object {
+        Object {
+            address,
+            bytes: 0,
+            slot_bytes: 0,
+            kind: "UNKNOWN".to_string(),
+            label: None,
+            location: None,
+            generation: None,
+            old: false,
         }
     }
 
+    // The root or one of its categories
     pub fn is_root(&self) -> bool {
-        self.address == 0
+        self.kind == ROOT_KIND
     }
 
-    pub fn with_dominator_stats(&self, stats: Stats) -> Object {
+    // Labeled with what it retains, including as a share of `total_bytes`.
+    // `self_bytes` is what counts of its own size, e.g. nothing when it was
+    // allocated outside the generations analyzed.
+    pub fn with_dominator_stats(
+        &self,
+        self_bytes: usize,
+        stats: Stats,
+        total_bytes: usize,
+        units: Units,
+    ) -> Object {
         let mut clone = self.clone();
         clone.label = Some(format!(
-            "{}: {} self, {} refs, {} objects",
+            "{}: {} self, {} refs, {} objects, {}",
             self,
-            ByteSize(self.bytes as u64),
-            ByteSize((stats.bytes - self.bytes) as u64),
-            stats.count
+            units.bytes(self_bytes),
+            units.bytes(stats.bytes - self_bytes),
+            stats.count,
+            percent_of(stats.bytes, total_bytes)
         ));
         clone
     }
@@ -61,6 +238,14 @@ impl Object {
     }
 }
 
+// e.g. "12.3%"
+pub fn percent_of(bytes: usize, total_bytes: usize) -> String {
+    if total_bytes == 0 {
+        return "0.0%".to_string();
+    }
+    format!("{:.1}%", bytes as f64 * 100.0 / total_bytes as f64)
+}
+
 impl PartialEq for Object {
     fn eq(&self, other: &Object) -> bool {
         self.address == other.address
@@ -84,15 +269,69 @@ impl Display for Object {
     }
 }
 
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+impl Collection {
+    // Estimated capacity allocated beyond what the elements need
+    pub fn wasted_bytes(&self) -> usize {
+        self.bytes.saturating_sub(self.min_bytes)
+    }
+}
+
+impl DumpedString {
+    pub fn stats(&self) -> Stats {
+        Stats {
+            count: 1,
+            bytes: self.bytes,
+            slot_bytes: self.slot_bytes,
+        }
+    }
+}
+
+impl Symbol {
+    pub fn stats(&self) -> Stats {
+        Stats {
+            count: 1,
+            bytes: self.bytes,
+            slot_bytes: self.slot_bytes,
+        }
+    }
+}
+
 impl Stats {
     pub fn add(&mut self, other: Stats) -> Stats {
         Stats {
             count: self.count + other.count,
             bytes: self.bytes + other.bytes,
+            slot_bytes: self.slot_bytes + other.slot_bytes,
+        }
+    }
+
+    // Bytes outside GC heap slots, e.g. string and array buffers
+    pub fn malloc_bytes(&self) -> usize {
+        self.bytes.saturating_sub(self.slot_bytes)
+    }
+}
+
+// The number of references an edge stands for, when several are drawn as
+// one. Shown as e.g. "×37" in graph output, and not at all for just one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct References(pub usize);
+
+impl Display for References {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.0 == 1 {
+            Ok(())
+        } else {
+            write!(f, "×{}", self.0)
         }
     }
 }
 
-pub type ReferenceGraph = Graph<Object, &'static str, Directed, usize>;
+pub type ReferenceGraph = Graph<Object, References, Directed, usize>;
 
-pub const EDGE_WEIGHT: &str = "";
+pub const EDGE_WEIGHT: References = References(1);
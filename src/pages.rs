@@ -0,0 +1,49 @@
+use crate::object::Slot;
+use std::collections::BTreeMap;
+
+// A heap page, with the objects in the dump occupying its slots. Pages are
+// aligned to their size, so an object's page is its address rounded down to
+// that.
+#[derive(Debug)]
+pub struct Page {
+    pub address: usize,
+    pub bytes: usize,
+    pub slot_bytes: usize,
+    pub objects: Vec<Slot>,
+}
+
+impl Page {
+    pub fn slots(&self) -> usize {
+        self.bytes / self.slot_bytes.max(1)
+    }
+
+    pub fn occupancy(&self) -> f64 {
+        self.objects.len() as f64 / self.slots().max(1) as f64
+    }
+
+    pub fn pinned(&self) -> usize {
+        self.objects.iter().filter(|slot| slot.pinned).count()
+    }
+
+    // Bytes of empty slots, which the page can't be released to the OS with
+    pub fn free_bytes(&self) -> usize {
+        self.slots().saturating_sub(self.objects.len()) * self.slot_bytes
+    }
+}
+
+// Groups objects into the pages they're on, in address order.
+pub fn pages(objects: &[Slot], page_bytes: usize) -> Vec<Page> {
+    let page_bytes = page_bytes.max(1);
+    let mut pages: BTreeMap<usize, Page> = BTreeMap::new();
+    for slot in objects {
+        let start = slot.address - slot.address % page_bytes;
+        let page = pages.entry(start).or_insert_with(|| Page {
+            address: start,
+            bytes: page_bytes,
+            slot_bytes: slot.bytes,
+            objects: Vec::new(),
+        });
+        page.objects.push(*slot);
+    }
+    pages.into_values().collect()
+}
@@ -1,11 +1,10 @@
 use crate::object::*;
 use petgraph::graph::NodeIndex;
-use petgraph::Graph;
-use std::collections::HashMap;
-use std::fs::File;
+use petgraph::{Direction, Graph};
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
 use std::io::prelude::*;
-use std::io::BufReader;
-use std::path::Path;
 use std::str;
 use timed_function::timed;
 
@@ -13,6 +12,8 @@ use timed_function::timed;
 struct Line {
     address: Option<String>,
     memsize: Option<usize>,
+    // Size of the object's slot, in dumps from Ruby 3.1 or later
+    slot_size: Option<usize>,
 
     #[serde(default)]
     references: Vec<String>,
@@ -20,32 +21,86 @@ struct Line {
     #[serde(rename = "type")]
     object_type: String,
 
+    // Category of a ROOT line, e.g. "vm"
+    root: Option<String>,
+
     class: Option<String>,
     name: Option<String>,
     length: Option<usize>,
     size: Option<usize>,
     value: Option<String>,
+
+    #[serde(default)]
+    flags: Flags,
+
+    // Allocation tracing fields
+    file: Option<String>,
+    line: Option<usize>,
+    method: Option<String>,
+    generation: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Flags {
+    #[serde(default)]
+    old: bool,
 }
 
 #[derive(Debug)]
 struct ParsedLine {
     object: Object,
+    root: Option<String>,
     references: Vec<usize>,
     module: Option<usize>,
     name: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    // Objects take up at least their slot, which memsize leaves out or
+    // undercounts for some types
+    pub slot_sizes: bool,
+    // Objects dumped without a memsize take up their slot, or the default
+    // slot size when that isn't dumped either
+    pub missing_memsize: bool,
+    // The dump only has objects allocated since some GC generation, as with
+    // `ObjectSpace.dump_all(since: n)`
+    pub partial: bool,
+}
+
 impl Line {
-    pub fn parse(self, class_name_only: bool) -> Option<ParsedLine> {
+    pub fn parse(self, class_name_only: bool, options: Options) -> Option<ParsedLine> {
+        let bytes = match (self.memsize, self.slot_size) {
+            (None, slot_size) if options.missing_memsize && self.object_type != "ROOT" => {
+                slot_size.unwrap_or(RVALUE_BYTES)
+            }
+            (memsize, Some(slot_size)) if options.slot_sizes => memsize.unwrap_or(0).max(slot_size),
+            (memsize, _) => memsize.unwrap_or(0),
+        };
         let mut object = Object {
             address: self
                 .address
                 .as_ref()
                 .and_then(|a| parse_address(a.as_str()).ok())
                 .unwrap_or(0),
-            bytes: self.memsize.unwrap_or(0),
+            bytes,
+            slot_bytes: if self.object_type == "ROOT" {
+                0
+            } else {
+                slot_bytes(bytes, self.slot_size)
+            },
             kind: self.object_type,
             label: None,
+            location: match (self.file, self.line) {
+                (Some(file), Some(line)) => Some(Location {
+                    file,
+                    line,
+                    method: self.method,
+                }),
+                _ => None,
+            },
+            generation: self.generation,
+            old: self.flags.old,
         };
 
         if object.address == 0 && object.kind != "ROOT" {
@@ -105,29 +160,347 @@ impl Line {
                 .flat_map(|r| parse_address(r.as_str()).ok())
                 .collect(),
             module: self.class.and_then(|c| parse_address(c.as_str()).ok()),
+            root: self.root,
             name: self.name,
             object,
         })
     }
 }
 
-pub fn parse_address(addr: &str) -> Result<usize, std::num::ParseIntError> {
-    usize::from_str_radix(&addr[2..], 16)
+// Parses an address as the dump writes it, in hex with a 0x prefix
+pub fn parse_address(addr: &str) -> Result<usize, String> {
+    addr.strip_prefix("0x")
+        .and_then(|hex| usize::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| format!("Invalid address {}", addr))
+}
+
+// The dump's own record of the object at `address`, with the fields (like
+// full string values) that aren't kept after parsing.
+pub fn find_object(
+    mut reader: impl BufRead,
+    address: usize,
+) -> std::io::Result<Option<serde_json::Value>> {
+    let needle = format!("\"address\":\"{:#x}\"", address);
+    let mut line_buffer = vec![];
+
+    while reader.read_until(0x0A, &mut line_buffer)? > 0 {
+        let line = String::from_utf8_lossy(&line_buffer);
+        if line.contains(&needle) {
+            return Ok(Some(serde_json::from_str(&line)?));
+        }
+        line_buffer.clear();
+    }
+    Ok(None)
+}
+
+// Calls `f` with each object of one of the given types in the dump, or with
+// every line if no types are given, for fields that aren't kept after parsing.
+fn scan<T: DeserializeOwned, F: FnMut(T)>(
+    mut reader: impl BufRead,
+    types: &[&str],
+    mut f: F,
+) -> std::io::Result<()> {
+    let needles: Vec<String> = types
+        .iter()
+        .map(|t| format!("\"type\":\"{}\"", t))
+        .collect();
+    let mut line_buffer = vec![];
+
+    while reader.read_until(0x0A, &mut line_buffer)? > 0 {
+        let line = String::from_utf8_lossy(&line_buffer);
+        if needles.is_empty() || needles.iter().any(|n| line.contains(n.as_str())) {
+            f(serde_json::from_str(&line)?);
+        }
+        line_buffer.clear();
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct StringLine {
+    address: String,
+    memsize: Option<usize>,
+    slot_size: Option<usize>,
+    value: Option<String>,
+    #[serde(default)]
+    frozen: bool,
+    #[serde(default)]
+    shared: bool,
+    #[serde(default)]
+    embedded: bool,
+    #[serde(default)]
+    fstring: bool,
+
+    // Allocation tracing field
+    generation: Option<usize>,
+}
+
+// Addresses and values of the strings whose values match `pattern`.
+#[timed]
+pub fn find_strings(
+    reader: impl BufRead,
+    pattern: &Regex,
+) -> std::io::Result<Vec<(usize, String)>> {
+    let mut found = Vec::new();
+    scan(reader, &["STRING"], |string: StringLine| {
+        if let (Ok(address), Some(value)) = (parse_address(&string.address), string.value) {
+            if pattern.is_match(&value) {
+                found.push((address, value));
+            }
+        }
+    })?;
+    Ok(found)
+}
+
+// Full values of the strings at `addresses`, which labels truncate.
+#[timed]
+pub fn find_string_values(
+    reader: impl BufRead,
+    addresses: &HashSet<usize>,
+) -> std::io::Result<HashMap<usize, String>> {
+    let mut found = HashMap::new();
+    scan(reader, &["STRING"], |string: StringLine| {
+        if let (Ok(address), Some(value)) = (parse_address(&string.address), string.value) {
+            if addresses.contains(&address) {
+                found.insert(address, value);
+            }
+        }
+    })?;
+    Ok(found)
+}
+
+// Number of copies and total bytes of each string value, reachable or not.
+#[timed]
+pub fn string_values(reader: impl BufRead) -> std::io::Result<HashMap<String, Stats>> {
+    let mut values: HashMap<String, Stats> = HashMap::new();
+    scan(reader, &["STRING"], |string: StringLine| {
+        if let Some(value) = string.value {
+            let bytes = string.memsize.unwrap_or(0);
+            let copy = Stats {
+                count: 1,
+                bytes,
+                slot_bytes: slot_bytes(bytes, string.slot_size),
+            };
+            values
+                .entry(value)
+                .and_modify(|s| *s = s.add(copy))
+                .or_insert(copy);
+        }
+    })?;
+    Ok(values)
+}
+
+// All strings in the dump, reachable or not.
+#[timed]
+pub fn find_all_strings(reader: impl BufRead) -> std::io::Result<Vec<DumpedString>> {
+    let mut found = Vec::new();
+    scan(reader, &["STRING"], |string: StringLine| {
+        if let Ok(address) = parse_address(&string.address) {
+            found.push(DumpedString {
+                address,
+                value: string.value,
+                bytes: string.memsize.unwrap_or(0),
+                slot_bytes: slot_bytes(string.memsize.unwrap_or(0), string.slot_size),
+                frozen: string.frozen,
+                shared: string.shared,
+                embedded: string.embedded,
+                fstring: string.fstring,
+                generation: string.generation,
+            });
+        }
+    })?;
+    Ok(found)
+}
+
+#[derive(Debug, Deserialize)]
+struct SymbolLine {
+    address: String,
+    memsize: Option<usize>,
+    slot_size: Option<usize>,
+    value: Option<String>,
+
+    // Allocation tracing field
+    generation: Option<usize>,
+}
+
+// All symbols in the dump, reachable or not.
+#[timed]
+pub fn find_symbols(reader: impl BufRead) -> std::io::Result<Vec<Symbol>> {
+    let mut found = Vec::new();
+    scan(reader, &["SYMBOL"], |symbol: SymbolLine| {
+        if let Ok(address) = parse_address(&symbol.address) {
+            found.push(Symbol {
+                address,
+                value: symbol.value.unwrap_or_default(),
+                bytes: symbol.memsize.unwrap_or(0),
+                slot_bytes: slot_bytes(symbol.memsize.unwrap_or(0), symbol.slot_size),
+                generation: symbol.generation,
+            });
+        }
+    })?;
+    Ok(found)
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionLine {
+    address: String,
+    #[serde(rename = "type")]
+    object_type: String,
+    length: Option<usize>,
+    size: Option<usize>,
+    memsize: Option<usize>,
+    slot_size: Option<usize>,
+}
+
+// Least bytes per element: a pointer for each array element, or a hash, key
+// and value for each hash entry
+const ARRAY_ELEMENT_BYTES: usize = 8;
+const HASH_ENTRY_BYTES: usize = 24;
+
+// All arrays and hashes, with their numbers of elements.
+#[timed]
+pub fn find_collections(reader: impl BufRead) -> std::io::Result<Vec<Collection>> {
+    let mut found = Vec::new();
+    scan(reader, &["ARRAY", "HASH"], |collection: CollectionLine| {
+        if let (Ok(address), Some(elements)) = (
+            parse_address(&collection.address),
+            collection.length.or(collection.size),
+        ) {
+            let element_bytes = match collection.object_type.as_str() {
+                "HASH" => HASH_ENTRY_BYTES,
+                _ => ARRAY_ELEMENT_BYTES,
+            };
+            found.push(Collection {
+                address,
+                elements,
+                bytes: collection.memsize.unwrap_or(0),
+                min_bytes: collection.slot_size.unwrap_or(RVALUE_BYTES) + elements * element_bytes,
+            });
+        }
+    })?;
+    Ok(found)
+}
+
+#[derive(Debug, Deserialize)]
+struct ImemoLine {
+    address: String,
+    imemo_type: Option<String>,
+}
+
+// Addresses and subtypes (e.g. "ment" or "iseq") of all IMEMO objects.
+#[timed]
+pub fn find_imemo_types(reader: impl BufRead) -> std::io::Result<Vec<(usize, String)>> {
+    let mut found = Vec::new();
+    scan(reader, &["IMEMO"], |imemo: ImemoLine| {
+        if let (Ok(address), Some(imemo_type)) = (parse_address(&imemo.address), imemo.imemo_type) {
+            found.push((address, imemo_type));
+        }
+    })?;
+    Ok(found)
+}
+
+#[derive(Debug, Deserialize)]
+struct ShapeLine {
+    address: String,
+    memsize: Option<usize>,
+    shape_id: Option<usize>,
+    slot_size: Option<usize>,
+    #[serde(default)]
+    embedded: bool,
+    #[serde(default)]
+    too_complex_shape: bool,
+}
+
+// Size of every slot before Ruby 3.1's variable width allocation
+const RVALUE_BYTES: usize = 40;
+
+// Of an object's bytes, those in its slot. Ruby counts the slot towards
+// memsize, so anything beyond it was malloc'd.
+fn slot_bytes(bytes: usize, slot_size: Option<usize>) -> usize {
+    bytes.min(slot_size.unwrap_or(RVALUE_BYTES))
+}
+
+// Shapes of all plain objects, in dumps from Ruby 3.2 or later.
+#[timed]
+pub fn find_shapes(reader: impl BufRead) -> std::io::Result<Vec<ObjectShape>> {
+    let mut found = Vec::new();
+    scan(reader, &["OBJECT"], |object: ShapeLine| {
+        if let (Ok(address), Some(shape_id)) = (parse_address(&object.address), object.shape_id) {
+            found.push(ObjectShape {
+                address,
+                shape_id,
+                embedded: object.embedded,
+                too_complex: object.too_complex_shape,
+                bytes: object.memsize.unwrap_or(0),
+                slot_bytes: object.slot_size.unwrap_or(RVALUE_BYTES),
+            });
+        }
+    })?;
+    Ok(found)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SlotFlags {
+    #[serde(default)]
+    pinned: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlotLine {
+    address: Option<String>,
+    slot_size: Option<usize>,
+    #[serde(default)]
+    flags: SlotFlags,
+}
+
+// Slots of all objects, e.g. to find which heap pages they're on.
+#[timed]
+pub fn find_slots(reader: impl BufRead) -> std::io::Result<Vec<Slot>> {
+    let mut found = Vec::new();
+    scan(reader, &[], |object: SlotLine| {
+        if let Some(Ok(address)) = object.address.as_deref().map(parse_address) {
+            found.push(Slot {
+                address,
+                bytes: object.slot_size.unwrap_or(RVALUE_BYTES),
+                pinned: object.flags.pinned,
+            });
+        }
+    })?;
+    Ok(found)
 }
 
 #[timed]
 pub fn parse(
-    file: &Path,
+    reader: impl BufRead,
     class_name_only: bool,
+    ignored_roots: &[String],
+    options: Options,
 ) -> std::io::Result<(NodeIndex<usize>, ReferenceGraph)> {
-    let file = File::open(file)?;
-    let mut reader = BufReader::new(file);
+    let (root, graph, _) = parse_with_dangling(reader, class_name_only, ignored_roots, options)?;
+    Ok((root, graph))
+}
+
+// References to addresses that aren't in the dump, by referrer and missing
+// address.
+pub type Dangling = Vec<(NodeIndex<usize>, usize)>;
 
+// Like `parse`, but also returns the dangling references that are otherwise
+// dropped, e.g. because the dump was truncated. References from the
+// categories of roots in `ignored_roots`, e.g. "machine_context", are left
+// out altogether.
+pub fn parse_with_dangling(
+    mut reader: impl BufRead,
+    class_name_only: bool,
+    ignored_roots: &[String],
+    options: Options,
+) -> std::io::Result<(NodeIndex<usize>, ReferenceGraph, Dangling)> {
     let mut graph: ReferenceGraph = Graph::default();
     let mut indices: HashMap<usize, NodeIndex<usize>> = HashMap::new();
     let mut references: HashMap<usize, Vec<usize>> = HashMap::new();
     let mut instances: HashMap<usize, usize> = HashMap::new();
     let mut names: HashMap<usize, String> = HashMap::new();
+    let mut root_categories: HashMap<String, usize> = HashMap::new();
+    let mut root_lines: HashSet<(String, Vec<usize>)> = HashSet::new();
 
     let root = Object::root();
     let root_address = root.address;
@@ -137,22 +510,42 @@ pub fn parse(
 
     let mut line_buffer = vec![];
 
-    while let Ok(bytes_read) = reader.read_until(0x0A, &mut line_buffer) {
-        if bytes_read <= 0 {
-            break;
-        }
-
+    while reader.read_until(0x0A, &mut line_buffer)? > 0 {
         let line = String::from_utf8_lossy(&line_buffer);
 
         let parsed = serde_json::from_str::<Line>(&line)
-            .expect(&line)
-            .parse(class_name_only)
-            .expect(&line);
+            .ok()
+            .and_then(|parsed| parsed.parse(class_name_only, options))
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Not a line of a heap dump: {}", line.trim_end()),
+                )
+            })?;
 
         if parsed.object.is_root() {
-            let refs = references.get_mut(&root_address).unwrap();
+            let name = parsed.root.unwrap_or_else(|| "unknown".to_string());
+            // Every shard of a dump split across several files may repeat the
+            // same roots
+            if ignored_roots.contains(&name)
+                || !root_lines.insert((name.clone(), parsed.references.clone()))
+            {
+                line_buffer.clear();
+                continue;
+            }
+
+            // Each category of roots gets a node of its own under the root, so
+            // memory only reachable from one is attributed to it
+            let next_address = root_categories.len() + 1;
+            let address = *root_categories.entry(name.clone()).or_insert(next_address);
+            if address == next_address {
+                let category = Object::root_category(&name, address);
+                indices.insert(address, graph.add_node(category));
+                references.get_mut(&root_address).unwrap().push(address);
+            }
+            let refs = references.entry(address).or_default();
             refs.extend_from_slice(parsed.references.as_slice());
-        } else {
+        } else if !indices.contains_key(&parsed.object.address) {
             let address = parsed.object.address;
             indices.insert(address, graph.add_node(parsed.object));
 
@@ -170,16 +563,53 @@ pub fn parse(
         line_buffer.clear();
     }
 
-    for (node, successors) in references {
-        let i = &indices[&node];
-        for s in successors {
-            if let Some(j) = indices.get(&s) {
-                graph.add_edge(*i, *j, EDGE_WEIGHT);
+    // Several references from one object to another become a single edge,
+    // weighted by how many there are
+    let mut dangling = Vec::new();
+    for (node, mut successors) in references {
+        let i = indices[&node];
+        successors.sort_unstable();
+        for group in successors.chunk_by(|a, b| a == b) {
+            let j = match indices.get(&group[0]) {
+                Some(j) => *j,
+                None => {
+                    dangling.extend(group.iter().map(|s| (i, *s)));
+                    if !options.partial {
+                        continue;
+                    }
+                    *indices
+                        .entry(group[0])
+                        .or_insert_with(|| graph.add_node(Object::placeholder(group[0])))
+                }
+            };
+            graph.add_edge(i, j, References(group.len()));
+        }
+    }
+
+    // Objects nothing in a partial dump references are presumably referenced
+    // by objects left out of it, rather than garbage
+    if options.partial {
+        let orphans: Vec<NodeIndex<usize>> = graph
+            .node_indices()
+            .filter(|i| !graph[*i].is_root())
+            .filter(|i| {
+                graph
+                    .neighbors_directed(*i, Direction::Incoming)
+                    .next()
+                    .is_none()
+            })
+            .collect();
+        if !orphans.is_empty() {
+            let category = Object::root_category("outside_dump", root_categories.len() + 1);
+            let category = graph.add_node(category);
+            graph.add_edge(root_index, category, References(1));
+            for orphan in orphans {
+                graph.add_edge(category, orphan, References(1));
             }
         }
     }
 
-    for mut obj in graph.node_weights_mut() {
+    for obj in graph.node_weights_mut() {
         if let Some(module) = instances.get(&obj.address) {
             if let Some(name) = names.get(module) {
                 obj.kind = name.to_owned();
@@ -187,5 +617,5 @@ pub fn parse(
         }
     }
 
-    Ok((root_index, graph))
+    Ok((root_index, graph, dangling))
 }
@@ -0,0 +1,169 @@
+use crate::analyze::Analysis;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+// Minimal protobuf encoding of the pprof Profile message; see
+// https://github.com/google/pprof/blob/main/proto/profile.proto
+//
+// Field numbers are those of profile.proto; we only ever write the handful of
+// message types below, so a full protobuf library would be overkill.
+#[derive(Default)]
+struct Message {
+    buf: Vec<u8>,
+}
+
+impl Message {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint(u64::from(field << 3) | u64::from(wire_type));
+    }
+
+    fn uint(&mut self, field: u32, value: u64) {
+        if value != 0 {
+            self.key(field, 0);
+            self.varint(value);
+        }
+    }
+
+    fn bytes(&mut self, field: u32, value: &[u8]) {
+        self.key(field, 2);
+        self.varint(value.len() as u64);
+        self.buf.extend_from_slice(value);
+    }
+
+    fn message(&mut self, field: u32, message: Message) {
+        self.bytes(field, &message.buf);
+    }
+
+    fn packed(&mut self, field: u32, values: &[u64]) {
+        let mut packed = Message::default();
+        for &v in values {
+            packed.varint(v);
+        }
+        self.bytes(field, &packed.buf);
+    }
+}
+
+#[derive(Default)]
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, u64>,
+}
+
+impl StringTable {
+    fn new() -> StringTable {
+        let mut table = StringTable::default();
+        // By convention, the first entry is always the empty string
+        table.index("");
+        table
+    }
+
+    fn index(&mut self, s: &str) -> u64 {
+        if let Some(&i) = self.indices.get(s) {
+            return i;
+        }
+        let i = self.strings.len() as u64;
+        self.strings.push(s.to_string());
+        self.indices.insert(s.to_string(), i);
+        i
+    }
+}
+
+fn value_type(strings: &mut StringTable, name: &str, unit: &str) -> Message {
+    let mut m = Message::default();
+    m.uint(1, strings.index(name));
+    m.uint(2, strings.index(unit));
+    m
+}
+
+// Encodes the dominator tree as an (uncompressed) pprof profile, with one
+// sample per reachable object. Each sample's stack is the object's dominator
+// chain, and its values are one object and the object's own size, so the
+// cumulative values of each frame are the object count and bytes it retains.
+pub fn profile(analysis: &Analysis, class_name_only: bool) -> Vec<u8> {
+    let mut strings = StringTable::new();
+    let mut profile = Message::default();
+
+    let objects = value_type(&mut strings, "objects", "count");
+    profile.message(1, objects);
+    let space = value_type(&mut strings, "space", "bytes");
+    profile.message(1, space);
+
+    // Function & location IDs are shared, since we emit one location per function
+    let mut function_ids: HashMap<String, u64> = HashMap::new();
+    let mut functions: Vec<Message> = Vec::new();
+    let mut stack: Vec<u64> = Vec::new();
+
+    analysis.for_each_dominator_chain(|chain| {
+        for obj in chain.iter().rev() {
+            let name = obj.format(class_name_only);
+            let next_id = function_ids.len() as u64 + 1;
+            let id = *function_ids.entry(name).or_insert_with_key(|name| {
+                let mut function = Message::default();
+                function.uint(1, next_id);
+                function.uint(2, strings.index(name));
+                if let Some(ref location) = obj.location {
+                    function.uint(4, strings.index(&location.file));
+                    function.uint(5, location.line as u64);
+                }
+                functions.push(function);
+                next_id
+            });
+            stack.push(id);
+        }
+
+        let obj = chain[chain.len() - 1];
+        let mut sample = Message::default();
+        sample.packed(1, &stack);
+        sample.packed(2, &[1, obj.bytes as u64]);
+        profile.message(2, sample);
+        stack.clear();
+    });
+
+    for id in 1..=functions.len() as u64 {
+        let mut line = Message::default();
+        line.uint(1, id);
+
+        let mut location = Message::default();
+        location.uint(1, id);
+        location.message(4, line);
+        profile.message(4, location);
+    }
+
+    for function in functions {
+        profile.message(5, function);
+    }
+
+    let period_type = value_type(&mut strings, "space", "bytes");
+    let default_sample_type = strings.index("space");
+
+    for s in strings.strings.iter() {
+        profile.bytes(6, s.as_bytes());
+    }
+
+    profile.message(11, period_type);
+    profile.uint(12, 1);
+    profile.uint(14, default_sample_type);
+
+    profile.buf
+}
+
+pub fn write_pprof(profile: &[u8], filename: &Path) -> io::Result<()> {
+    let file = File::create(filename)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(profile)?;
+    encoder.finish()?;
+    Ok(())
+}
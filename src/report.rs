@@ -0,0 +1,5541 @@
+use crate::analyze::{Analysis, GroupBy, Index, KindFilter, Sort, TreeNode};
+use crate::diff::{Delta, Diff, Regression};
+use crate::object::*;
+use crate::pages::Page;
+use crate::parse::Dangling;
+use crate::trend::{Series, Trend};
+use crate::units::Units;
+use petgraph::Direction;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Markdown,
+    Csv,
+    Yaml,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "markdown" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            "yaml" => Ok(Format::Yaml),
+            _ => Err(format!("Unknown format {:?}", s)),
+        }
+    }
+}
+
+pub const FORMATS: &[&str] = &["table", "json", "markdown", "csv", "yaml"];
+
+#[derive(Debug, Serialize)]
+pub struct Table<R, S = Stats> {
+    pub largest: Vec<R>,
+    #[serde(skip_serializing_if = "is_unit")]
+    pub rest: S,
+}
+
+// Tables without a summary of the rows beyond the top N have `S = ()`
+fn is_unit<S>(_: &S) -> bool {
+    std::mem::size_of::<S>() == 0
+}
+
+// How the bytes in the column before these split between GC heap slots and
+// malloc'd memory
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Split {
+    pub slot_bytes: usize,
+    pub malloc_bytes: usize,
+}
+
+impl From<Stats> for Split {
+    fn from(stats: Stats) -> Split {
+        Split {
+            slot_bytes: stats.slot_bytes,
+            malloc_bytes: stats.malloc_bytes(),
+        }
+    }
+}
+
+impl Split {
+    // E.g. "40 B in slots, 1.0 KiB malloc'd"
+    fn text(self, units: Units) -> String {
+        format!(
+            "{} in slots, {} malloc'd",
+            units.bytes(self.slot_bytes),
+            units.bytes(self.malloc_bytes)
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct KindRow {
+    pub kind: String,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetainerRow {
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    pub self_bytes: usize,
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    pub count: usize,
+    // Length of the shortest reference path from the root
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GrowthRow {
+    pub kind: String,
+    #[serde(flatten)]
+    pub growth: Delta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesRow {
+    pub kind: String,
+    pub retained_bytes: Vec<usize>,
+    pub slot_bytes: Vec<usize>,
+    pub malloc_bytes: Vec<usize>,
+    pub counts: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegressionRow {
+    pub kind: String,
+    pub before_bytes: usize,
+    pub after_bytes: usize,
+    pub growth_bytes: i64,
+    pub growth_slot_bytes: i64,
+    pub growth_malloc_bytes: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferenceRow {
+    pub from: String,
+    pub to: String,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DominatorRow {
+    pub kind: String,
+    pub instances: usize,
+    pub share: f64,
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObjectRow {
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    pub bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollectionRow {
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    pub elements: usize,
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    pub dominator: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SlackRow {
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    pub elements: usize,
+    pub self_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    // Estimated capacity beyond what the elements need
+    pub wasted_bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CycleRow {
+    // The member retaining the most
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    pub members: usize,
+    pub self_bytes: usize,
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchRow {
+    pub address: String,
+    pub value: String,
+    pub self_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    pub retained_bytes: usize,
+    // From the root down to the string's immediate dominator
+    pub dominators: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateRow {
+    pub value: String,
+    pub copies: usize,
+    pub total_bytes: usize,
+    // All but one copy's bytes
+    pub wasted_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FstringRow {
+    pub address: String,
+    pub value: String,
+    pub bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    pub generation: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolRow {
+    pub address: String,
+    pub value: String,
+    pub bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    pub generation: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DanglingRow {
+    pub kind: String,
+    // Objects of the type with dangling references, and how many they have
+    pub objects: usize,
+    pub references: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DanglingReferenceRow {
+    pub address: String,
+    pub label: String,
+    pub missing: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharingRow {
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    // Dominated by the object alone
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    pub reachable_bytes: usize,
+    // Reachable, but also through other objects
+    pub shared_bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RootCategoryRow {
+    pub label: String,
+    // Only reachable through this category
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    pub retained_count: usize,
+    pub reachable_bytes: usize,
+    // Of everything reachable from the root
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkRow {
+    pub from_address: String,
+    pub from: String,
+    pub to_address: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FanInRow {
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    // Distinct objects referencing this one
+    pub referrers: usize,
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KindFanInRow {
+    pub kind: String,
+    // Instances referenced at all, and their referrers between them
+    pub objects: usize,
+    pub referrers: usize,
+    pub max_referrers: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerationRow {
+    // e.g. "20-29", or just "20" for buckets of one generation
+    pub generations: String,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShapeRow {
+    pub shape_id: usize,
+    // Classes of the objects with the shape
+    pub kinds: String,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+// Heap pages whose occupancy falls in a range
+#[derive(Debug, Serialize)]
+pub struct PageRow {
+    pub occupancy: String,
+    pub pages: usize,
+    pub objects: usize,
+    pub free_bytes: usize,
+}
+
+// An object on a sparsely occupied heap page
+#[derive(Debug, Serialize)]
+pub struct HolderRow {
+    pub address: String,
+    pub page: String,
+    pub kind: String,
+    pub label: String,
+    pub pinned: bool,
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+    // Types of the objects on a shortest reference path from the root
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DepthRow {
+    pub depth: usize,
+    pub objects: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldRow {
+    pub field: &'static str,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HopRow {
+    pub address: String,
+    pub kind: String,
+    pub label: String,
+    pub self_bytes: usize,
+    pub retained_bytes: usize,
+    #[serde(flatten)]
+    pub split: Split,
+}
+
+impl Display for KindRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for ReferenceRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)
+    }
+}
+
+impl Display for DominatorRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for ObjectRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for CollectionRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for SlackRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for CycleRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+// Like string labels, the value is cut short
+impl Display for MatchRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "String[{}][{}]", self.address, preview(&self.value))
+    }
+}
+
+impl Display for DuplicateRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "\"{}\"", preview(&self.value))
+    }
+}
+
+impl Display for FstringRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "String[{}][{}]", self.address, preview(&self.value))
+    }
+}
+
+impl Display for SymbolRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Symbol[{}][:{}]", self.address, preview(&self.value))
+    }
+}
+
+impl Display for DanglingRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for DanglingReferenceRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for SharingRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for RootCategoryRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for LinkRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)
+    }
+}
+
+impl Display for FanInRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for KindFanInRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for ShapeRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "shape {} ({})", self.shape_id, self.kinds)
+    }
+}
+
+impl Display for GenerationRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.generations)
+    }
+}
+
+impl Display for PageRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} occupied", self.occupancy)
+    }
+}
+
+impl Display for HolderRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for DepthRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.depth)
+    }
+}
+
+impl Display for FieldRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.field)
+    }
+}
+
+impl Display for HopRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for RetainerRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl Display for GrowthRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for SeriesRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Display for RegressionRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+pub trait Row {
+    // Summary of the rows beyond the top N
+    type Rest: Copy + Default + PartialEq;
+
+    const CSV_HEADER: &'static [&'static str];
+
+    fn csv_record(&self) -> Vec<String>;
+
+    // Record summarizing the rows beyond the top N, labeled "..."
+    fn csv_rest(rest: Self::Rest) -> Vec<String>;
+
+    // Column headings and human-readable cells, for markdown and HTML tables
+    const HEADER: &'static [&'static str];
+
+    // Leading columns that are labels rather than sizes, left-aligned in markdown
+    const LABEL_COLUMNS: usize = 1;
+
+    fn cells(&self, units: Units) -> Vec<String>;
+
+    fn rest_cells(rest: Self::Rest, units: Units) -> Vec<String>;
+
+    // What follows the label in text output, e.g. "1.2 MB (5 objects)"
+    fn sizes(&self, units: Units) -> String;
+
+    fn rest_sizes(rest: Self::Rest, units: Units) -> String;
+
+    // Size of the bar drawn for the row in HTML reports
+    fn bytes(&self) -> usize;
+
+    fn rest_bytes(rest: Self::Rest) -> usize;
+}
+
+// With how the bytes split between GC heap slots and malloc'd memory
+fn sizes(stats: Stats, units: Units) -> String {
+    format!(
+        "{} ({} objects; {})",
+        units.bytes(stats.bytes),
+        stats.count,
+        Split::from(stats).text(units)
+    )
+}
+
+fn growth_sizes(delta: Delta, units: Units) -> String {
+    format!(
+        "{} ({:+} objects; {} in slots, {} malloc'd)",
+        units.signed_bytes(delta.bytes),
+        delta.count,
+        units.signed_bytes(delta.slot_bytes),
+        units.signed_bytes(delta.malloc_bytes())
+    )
+}
+
+impl Row for KindRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] =
+        &["kind", "count", "bytes", "slot_bytes", "malloc_bytes"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.stats.count.to_string(),
+            self.stats.bytes.to_string(),
+            self.stats.slot_bytes.to_string(),
+            self.stats.malloc_bytes().to_string(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            rest.count.to_string(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &["Type", "Bytes", "Slots", "Malloc", "Objects"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            units.bytes(self.stats.bytes),
+            units.bytes(self.stats.slot_bytes),
+            units.bytes(self.stats.malloc_bytes()),
+            self.stats.count.to_string(),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+            rest.count.to_string(),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        sizes(self.stats, units)
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.stats.bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for RetainerRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "kind",
+        "label",
+        "self_bytes",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "count",
+        "depth",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.self_bytes.to_string(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.count.to_string(),
+            self.depth.to_string(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            String::new(),
+            String::new(),
+            "...".to_string(),
+            String::new(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+            rest.count.to_string(),
+            String::new(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Object",
+        "Self bytes",
+        "Retained bytes",
+        "Slots",
+        "Malloc",
+        "Objects",
+        "Depth",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            units.bytes(self.self_bytes),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            self.count.to_string(),
+            self.depth.to_string(),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            String::new(),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+            rest.count.to_string(),
+            String::new(),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} ({} objects; {}) at depth {}",
+            units.bytes(self.retained_bytes),
+            self.count,
+            self.split.text(units),
+            self.depth
+        )
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.retained_bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+fn reference_sizes(stats: Stats, units: Units) -> String {
+    format!(
+        "{} ({} references; {})",
+        units.bytes(stats.bytes),
+        stats.count,
+        Split::from(stats).text(units)
+    )
+}
+
+impl Row for ReferenceRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] =
+        &["from", "to", "count", "bytes", "slot_bytes", "malloc_bytes"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.from.clone(),
+            self.to.clone(),
+            self.stats.count.to_string(),
+            self.stats.bytes.to_string(),
+            self.stats.slot_bytes.to_string(),
+            self.stats.malloc_bytes().to_string(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            String::new(),
+            rest.count.to_string(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] =
+        &["From", "To", "Bytes", "Slots", "Malloc", "References"];
+
+    const LABEL_COLUMNS: usize = 2;
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.from.clone(),
+            self.to.clone(),
+            units.bytes(self.stats.bytes),
+            units.bytes(self.stats.slot_bytes),
+            units.bytes(self.stats.malloc_bytes()),
+            self.stats.count.to_string(),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            String::new(),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+            rest.count.to_string(),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        reference_sizes(self.stats, units)
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        reference_sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.stats.bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for DominatorRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "kind",
+        "instances",
+        "share",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.instances.to_string(),
+            self.share.to_string(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            rest.count.to_string(),
+            String::new(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Dominator",
+        "Instances",
+        "Share",
+        "Retained bytes",
+        "Slots",
+        "Malloc",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.instances.to_string(),
+            format!("{:.1}%", self.share * 100.0),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            rest.count.to_string(),
+            String::new(),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{:.1}% of instances ({}), retaining {} ({})",
+            self.share * 100.0,
+            self.instances,
+            units.bytes(self.retained_bytes),
+            self.split.text(units)
+        )
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        format!(
+            "{} instances, retaining {} ({})",
+            rest.count,
+            units.bytes(rest.bytes),
+            Split::from(rest).text(units)
+        )
+    }
+
+    fn bytes(&self) -> usize {
+        self.retained_bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for ObjectRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "kind",
+        "label",
+        "bytes",
+        "slot_bytes",
+        "malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            String::new(),
+            String::new(),
+            "...".to_string(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &["Object", "Bytes", "Slots", "Malloc"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            units.bytes(self.bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            format!("... ({} more)", rest.count),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!("{} ({})", units.bytes(self.bytes), self.split.text(units))
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for SlackRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "kind",
+        "label",
+        "elements",
+        "self_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "wasted_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.elements.to_string(),
+            self.self_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.wasted_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Collection",
+        "Elements",
+        "Self bytes",
+        "Slots",
+        "Malloc",
+        "Wasted bytes",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            self.elements.to_string(),
+            units.bytes(self.self_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            units.bytes(self.wasted_bytes),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} wasted of {} ({}) for {} elements",
+            units.bytes(self.wasted_bytes),
+            units.bytes(self.self_bytes),
+            self.split.text(units),
+            self.elements
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.wasted_bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for CollectionRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "kind",
+        "label",
+        "elements",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "dominator",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.elements.to_string(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.dominator.clone(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Collection",
+        "Elements",
+        "Retained bytes",
+        "Slots",
+        "Malloc",
+        "Dominator",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            self.elements.to_string(),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            self.dominator.clone(),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} elements, retaining {} ({}), dominated by {}",
+            self.elements,
+            units.bytes(self.retained_bytes),
+            self.split.text(units),
+            self.dominator
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.retained_bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for CycleRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "kind",
+        "label",
+        "members",
+        "self_bytes",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.members.to_string(),
+            self.self_bytes.to_string(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Largest member",
+        "Members",
+        "Self bytes",
+        "Retained bytes",
+        "Slots",
+        "Malloc",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            self.members.to_string(),
+            units.bytes(self.self_bytes),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} members, {} self, retaining {} ({})",
+            self.members,
+            units.bytes(self.self_bytes),
+            units.bytes(self.retained_bytes),
+            self.split.text(units)
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.retained_bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+// The start of a string value, escaped, like in string labels
+fn preview(value: &str) -> String {
+    let escaped = format!("{:?}", value);
+    let inner = &escaped[1..escaped.len() - 1];
+    match inner.char_indices().nth(40) {
+        Some((end, _)) => format!("{}…", &inner[..end]),
+        None => inner.to_string(),
+    }
+}
+
+impl Row for MatchRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "value",
+        "self_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "retained_bytes",
+        "dominators",
+    ];
+
+    // Dominators are separated by semicolons
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.value.clone(),
+            self.self_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.retained_bytes.to_string(),
+            self.dominators.join(";"),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            String::new(),
+            "...".to_string(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+            String::new(),
+            String::new(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "String",
+        "Bytes",
+        "Slots",
+        "Malloc",
+        "Retained bytes",
+        "Dominators",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.to_string(),
+            units.bytes(self.self_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            units.bytes(self.retained_bytes),
+            self.dominators.join(" > "),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            format!("... ({} more)", rest.count),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+            String::new(),
+            String::new(),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} ({}; {} retained), in {}",
+            units.bytes(self.self_bytes),
+            self.split.text(units),
+            units.bytes(self.retained_bytes),
+            self.dominators.join(" > ")
+        )
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.self_bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for DuplicateRow {
+    // Copies and wasted bytes
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "value",
+        "copies",
+        "total_bytes",
+        "wasted_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.value.clone(),
+            self.copies.to_string(),
+            self.total_bytes.to_string(),
+            self.wasted_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            rest.count.to_string(),
+            String::new(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Value",
+        "Copies",
+        "Total bytes",
+        "Wasted bytes",
+        "Slots",
+        "Malloc",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.to_string(),
+            self.copies.to_string(),
+            units.bytes(self.total_bytes),
+            units.bytes(self.wasted_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            rest.count.to_string(),
+            String::new(),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} wasted ({}; {} copies, {} in all)",
+            units.bytes(self.wasted_bytes),
+            self.split.text(units),
+            self.copies,
+            units.bytes(self.total_bytes)
+        )
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        format!(
+            "{} wasted ({}; {} copies)",
+            units.bytes(rest.bytes),
+            Split::from(rest).text(units),
+            rest.count
+        )
+    }
+
+    fn bytes(&self) -> usize {
+        self.wasted_bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for SymbolRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "value",
+        "bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "generation",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.value.clone(),
+            self.bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.generation.map(|g| g.to_string()).unwrap_or_default(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            String::new(),
+            "...".to_string(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+            String::new(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &["Symbol", "Bytes", "Slots", "Malloc", "Generation"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.to_string(),
+            units.bytes(self.bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            self.generation.map(|g| g.to_string()).unwrap_or_default(),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            format!("... ({} more)", rest.count),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+            String::new(),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        match self.generation {
+            Some(generation) => format!(
+                "{} ({}; generation {})",
+                units.bytes(self.bytes),
+                self.split.text(units),
+                generation
+            ),
+            None => format!("{} ({})", units.bytes(self.bytes), self.split.text(units)),
+        }
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for FstringRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "value",
+        "bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "generation",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.value.clone(),
+            self.bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.generation.map(|g| g.to_string()).unwrap_or_default(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            String::new(),
+            "...".to_string(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+            String::new(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &["String", "Bytes", "Slots", "Malloc", "Generation"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.to_string(),
+            units.bytes(self.bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            self.generation.map(|g| g.to_string()).unwrap_or_default(),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            format!("... ({} more)", rest.count),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+            String::new(),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        match self.generation {
+            Some(generation) => format!(
+                "{} ({}; generation {})",
+                units.bytes(self.bytes),
+                self.split.text(units),
+                generation
+            ),
+            None => format!("{} ({})", units.bytes(self.bytes), self.split.text(units)),
+        }
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for DanglingRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &["kind", "objects", "references"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.objects.to_string(),
+            self.references.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &["Type", "Objects", "Dangling references"];
+
+    fn cells(&self, _: Units) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.objects.to_string(),
+            self.references.to_string(),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, _: Units) -> String {
+        format!(
+            "{} dangling references ({} objects)",
+            self.references, self.objects
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.references
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for DanglingReferenceRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &["address", "label", "missing"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.label.clone(),
+            self.missing.clone(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &["Object", "Missing address"];
+
+    const LABEL_COLUMNS: usize = 2;
+
+    fn cells(&self, _: Units) -> Vec<String> {
+        vec![self.label.clone(), self.missing.clone()]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, _: Units) -> String {
+        format!("-> {}", self.missing)
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        0
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for SharingRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "kind",
+        "label",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "reachable_bytes",
+        "shared_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.reachable_bytes.to_string(),
+            self.shared_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Object",
+        "Retained bytes",
+        "Slots",
+        "Malloc",
+        "Reachable bytes",
+        "Shared bytes",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            units.bytes(self.reachable_bytes),
+            units.bytes(self.shared_bytes),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} retained ({}), {} reachable ({} shared)",
+            units.bytes(self.retained_bytes),
+            self.split.text(units),
+            units.bytes(self.reachable_bytes),
+            units.bytes(self.shared_bytes)
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.reachable_bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for LinkRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &["from_address", "from", "to_address", "to"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.from_address.clone(),
+            self.from.clone(),
+            self.to_address.clone(),
+            self.to.clone(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &["Referrer", "Referenced object"];
+
+    const LABEL_COLUMNS: usize = 2;
+
+    fn cells(&self, _: Units) -> Vec<String> {
+        vec![self.from.clone(), self.to.clone()]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, _: Units) -> String {
+        String::new()
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        0
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for FanInRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "kind",
+        "label",
+        "referrers",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.referrers.to_string(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] =
+        &["Object", "Referrers", "Retained bytes", "Slots", "Malloc"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            self.referrers.to_string(),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} referrers, retaining {} ({})",
+            self.referrers,
+            units.bytes(self.retained_bytes),
+            self.split.text(units)
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.referrers
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for KindFanInRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &["kind", "objects", "referrers", "max_referrers"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.objects.to_string(),
+            self.referrers.to_string(),
+            self.max_referrers.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &["Type", "Objects", "Referrers", "Most referrers"];
+
+    fn cells(&self, _: Units) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.objects.to_string(),
+            self.referrers.to_string(),
+            self.max_referrers.to_string(),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, _: Units) -> String {
+        format!(
+            "{} referrers of {} objects (at most {} for one)",
+            self.referrers, self.objects, self.max_referrers
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.referrers
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for RootCategoryRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "label",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "retained_count",
+        "reachable_bytes",
+        "share",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.retained_count.to_string(),
+            self.reachable_bytes.to_string(),
+            format!("{:.4}", self.share),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Category",
+        "Retained bytes",
+        "Slots",
+        "Malloc",
+        "Objects",
+        "Reachable bytes",
+        "Share",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            self.retained_count.to_string(),
+            units.bytes(self.reachable_bytes),
+            format!("{:.1}%", self.share * 100.0),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} ({} objects, {:.1}%; {}), {} reachable",
+            units.bytes(self.retained_bytes),
+            self.retained_count,
+            self.share * 100.0,
+            self.split.text(units),
+            units.bytes(self.reachable_bytes)
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.retained_bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for ShapeRow {
+    type Rest = Stats;
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "shape_id",
+        "kinds",
+        "count",
+        "bytes",
+        "slot_bytes",
+        "malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.shape_id.to_string(),
+            self.kinds.clone(),
+            self.stats.count.to_string(),
+            self.stats.bytes.to_string(),
+            self.stats.slot_bytes.to_string(),
+            self.stats.malloc_bytes().to_string(),
+        ]
+    }
+
+    fn csv_rest(rest: Stats) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            String::new(),
+            rest.count.to_string(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] =
+        &["Shape", "Types", "Objects", "Bytes", "Slots", "Malloc"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.shape_id.to_string(),
+            self.kinds.clone(),
+            self.stats.count.to_string(),
+            units.bytes(self.stats.bytes),
+            units.bytes(self.stats.slot_bytes),
+            units.bytes(self.stats.malloc_bytes()),
+        ]
+    }
+
+    fn rest_cells(rest: Stats, units: Units) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            String::new(),
+            rest.count.to_string(),
+            units.bytes(rest.bytes),
+            units.bytes(rest.slot_bytes),
+            units.bytes(rest.malloc_bytes()),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        sizes(self.stats, units)
+    }
+
+    fn rest_sizes(rest: Stats, units: Units) -> String {
+        sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.stats.bytes
+    }
+
+    fn rest_bytes(rest: Stats) -> usize {
+        rest.bytes
+    }
+}
+
+impl Row for GenerationRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "generations",
+        "count",
+        "bytes",
+        "slot_bytes",
+        "malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.generations.clone(),
+            self.stats.count.to_string(),
+            self.stats.bytes.to_string(),
+            self.stats.slot_bytes.to_string(),
+            self.stats.malloc_bytes().to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &["Generations", "Bytes", "Slots", "Malloc", "Objects"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.generations.clone(),
+            units.bytes(self.stats.bytes),
+            units.bytes(self.stats.slot_bytes),
+            units.bytes(self.stats.malloc_bytes()),
+            self.stats.count.to_string(),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        sizes(self.stats, units)
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.stats.bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for PageRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &["occupancy", "pages", "objects", "free_bytes"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.occupancy.clone(),
+            self.pages.to_string(),
+            self.objects.to_string(),
+            self.free_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &["Occupancy", "Pages", "Objects", "Free"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.occupancy.clone(),
+            self.pages.to_string(),
+            self.objects.to_string(),
+            units.bytes(self.free_bytes),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} pages, {} free",
+            self.pages,
+            units.bytes(self.free_bytes)
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.free_bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for HolderRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "page",
+        "kind",
+        "label",
+        "pinned",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "path",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.page.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.pinned.to_string(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+            self.path.clone(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Object", "Page", "Pinned", "Retained", "Slots", "Malloc", "Path",
+    ];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            self.page.clone(),
+            if self.pinned { "yes" } else { "no" }.to_string(),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+            self.path.clone(),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{}on page {}, retaining {} ({}), via {}",
+            if self.pinned { "pinned " } else { "" },
+            self.page,
+            units.bytes(self.retained_bytes),
+            self.split.text(units),
+            self.path
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.retained_bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for DepthRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &["depth", "objects"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![self.depth.to_string(), self.objects.to_string()]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &["Depth", "Objects"];
+
+    fn cells(&self, _: Units) -> Vec<String> {
+        vec![self.depth.to_string(), self.objects.to_string()]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, _: Units) -> String {
+        format!("{} objects", self.objects)
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.objects
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for FieldRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &["field", "value"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![self.field.to_string(), self.value.clone()]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &["Field", "Value"];
+
+    const LABEL_COLUMNS: usize = 2;
+
+    fn cells(&self, _: Units) -> Vec<String> {
+        vec![self.field.to_string(), self.value.clone()]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, _: Units) -> String {
+        self.value.clone()
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        0
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for HopRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "address",
+        "kind",
+        "label",
+        "self_bytes",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.address.clone(),
+            self.kind.clone(),
+            self.label.clone(),
+            self.self_bytes.to_string(),
+            self.retained_bytes.to_string(),
+            self.split.slot_bytes.to_string(),
+            self.split.malloc_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] = &[
+        "Object",
+        "Type",
+        "Self bytes",
+        "Retained bytes",
+        "Slots",
+        "Malloc",
+    ];
+
+    const LABEL_COLUMNS: usize = 2;
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.label.clone(),
+            self.kind.clone(),
+            units.bytes(self.self_bytes),
+            units.bytes(self.retained_bytes),
+            units.bytes(self.split.slot_bytes),
+            units.bytes(self.split.malloc_bytes),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{}, {} self, {} retained ({})",
+            self.kind,
+            units.bytes(self.self_bytes),
+            units.bytes(self.retained_bytes),
+            self.split.text(units)
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.retained_bytes
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for GrowthRow {
+    type Rest = Delta;
+
+    const CSV_HEADER: &'static [&'static str] =
+        &["kind", "count", "bytes", "slot_bytes", "malloc_bytes"];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.growth.count.to_string(),
+            self.growth.bytes.to_string(),
+            self.growth.slot_bytes.to_string(),
+            self.growth.malloc_bytes().to_string(),
+        ]
+    }
+
+    fn csv_rest(rest: Delta) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            rest.count.to_string(),
+            rest.bytes.to_string(),
+            rest.slot_bytes.to_string(),
+            rest.malloc_bytes().to_string(),
+        ]
+    }
+
+    const HEADER: &'static [&'static str] = &["Type", "Bytes", "Slots", "Malloc", "Objects"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            units.signed_bytes(self.growth.bytes),
+            units.signed_bytes(self.growth.slot_bytes),
+            units.signed_bytes(self.growth.malloc_bytes()),
+            format!("{:+}", self.growth.count),
+        ]
+    }
+
+    fn rest_cells(rest: Delta, units: Units) -> Vec<String> {
+        vec![
+            "...".to_string(),
+            units.signed_bytes(rest.bytes),
+            units.signed_bytes(rest.slot_bytes),
+            units.signed_bytes(rest.malloc_bytes()),
+            format!("{:+}", rest.count),
+        ]
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        growth_sizes(self.growth, units)
+    }
+
+    fn rest_sizes(rest: Delta, units: Units) -> String {
+        growth_sizes(rest, units)
+    }
+
+    fn bytes(&self) -> usize {
+        self.growth.bytes.max(0) as usize
+    }
+
+    fn rest_bytes(rest: Delta) -> usize {
+        rest.bytes.max(0) as usize
+    }
+}
+
+// E.g. "1.0 KiB -> 2.0 KiB"
+fn byte_series(bytes: &[usize], units: Units) -> String {
+    bytes
+        .iter()
+        .map(|b| units.bytes(*b))
+        .collect::<Vec<String>>()
+        .join(" -> ")
+}
+
+impl SeriesRow {
+    // E.g. "1 -> 2"
+    fn count_series(&self) -> String {
+        self.counts
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>()
+            .join(" -> ")
+    }
+}
+
+impl Row for SeriesRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "kind",
+        "retained_bytes",
+        "slot_bytes",
+        "malloc_bytes",
+        "counts",
+    ];
+
+    // Values for each dump are separated by semicolons
+    fn csv_record(&self) -> Vec<String> {
+        let join = |values: &[usize]| {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(";")
+        };
+        vec![
+            self.kind.clone(),
+            join(&self.retained_bytes),
+            join(&self.slot_bytes),
+            join(&self.malloc_bytes),
+            join(&self.counts),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] =
+        &["Type", "Retained bytes", "Slots", "Malloc", "Objects"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            byte_series(&self.retained_bytes, units),
+            byte_series(&self.slot_bytes, units),
+            byte_series(&self.malloc_bytes, units),
+            self.count_series(),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} ({} objects; {} in slots, {} malloc'd)",
+            byte_series(&self.retained_bytes, units),
+            self.count_series(),
+            byte_series(&self.slot_bytes, units),
+            byte_series(&self.malloc_bytes, units)
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.retained_bytes.last().cloned().unwrap_or_default()
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl Row for RegressionRow {
+    type Rest = ();
+
+    const CSV_HEADER: &'static [&'static str] = &[
+        "kind",
+        "before_bytes",
+        "after_bytes",
+        "growth_bytes",
+        "growth_slot_bytes",
+        "growth_malloc_bytes",
+    ];
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            self.before_bytes.to_string(),
+            self.after_bytes.to_string(),
+            self.growth_bytes.to_string(),
+            self.growth_slot_bytes.to_string(),
+            self.growth_malloc_bytes.to_string(),
+        ]
+    }
+
+    fn csv_rest(_: ()) -> Vec<String> {
+        Vec::new()
+    }
+
+    const HEADER: &'static [&'static str] =
+        &["Type", "Before", "After", "Growth", "Slots", "Malloc"];
+
+    fn cells(&self, units: Units) -> Vec<String> {
+        vec![
+            self.kind.clone(),
+            units.bytes(self.before_bytes),
+            units.bytes(self.after_bytes),
+            units.signed_bytes(self.growth_bytes),
+            units.signed_bytes(self.growth_slot_bytes),
+            units.signed_bytes(self.growth_malloc_bytes),
+        ]
+    }
+
+    fn rest_cells(_: (), _: Units) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn sizes(&self, units: Units) -> String {
+        format!(
+            "{} -> {} ({}; {} in slots, {} malloc'd)",
+            units.bytes(self.before_bytes),
+            units.bytes(self.after_bytes),
+            units.signed_bytes(self.growth_bytes),
+            units.signed_bytes(self.growth_slot_bytes),
+            units.signed_bytes(self.growth_malloc_bytes)
+        )
+    }
+
+    fn rest_sizes(_: (), _: Units) -> String {
+        String::new()
+    }
+
+    fn bytes(&self) -> usize {
+        self.growth_bytes.max(0) as usize
+    }
+
+    fn rest_bytes(_: ()) -> usize {
+        0
+    }
+}
+
+impl<R: Row> Table<R, R::Rest> {
+    pub fn has_rest(&self) -> bool {
+        self.rest != R::Rest::default()
+    }
+
+    fn write_csv(&self, filename: &Path) -> csv::Result<()> {
+        let mut writer = csv::Writer::from_path(filename)?;
+        self.write_records(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn write_records<W: Write>(&self, writer: &mut csv::Writer<W>) -> csv::Result<()> {
+        writer.write_record(R::CSV_HEADER)?;
+        for row in self.largest.iter() {
+            writer.write_record(row.csv_record())?;
+        }
+        if self.has_rest() {
+            writer.write_record(R::csv_rest(self.rest))?;
+        }
+        Ok(())
+    }
+}
+
+fn kind_table((largest, rest): (Vec<(Cow<str>, Stats)>, Stats)) -> Table<KindRow> {
+    Table {
+        largest: largest
+            .into_iter()
+            .map(|(kind, stats)| KindRow {
+                kind: kind.into_owned(),
+                stats,
+            })
+            .collect(),
+        rest,
+    }
+}
+
+fn retainer_table(
+    analysis: &Analysis,
+    (largest, rest): (Vec<(Index, Stats)>, Stats),
+) -> Table<RetainerRow> {
+    let graph = analysis.dominated_subgraph();
+    let depths = analysis.reference_depths();
+    Table {
+        largest: largest
+            .into_iter()
+            .map(|(i, stats)| RetainerRow {
+                address: format!("{:#x}", graph[i].address),
+                kind: graph[i].kind.clone(),
+                label: graph[i].to_string(),
+                self_bytes: graph[i].bytes,
+                retained_bytes: stats.bytes,
+                split: stats.into(),
+                count: stats.count,
+                depth: depths[&i],
+            })
+            .collect(),
+        rest,
+    }
+}
+
+fn hop_row(analysis: &Analysis, i: Index) -> HopRow {
+    let obj = &analysis.dominated_subgraph()[i];
+    let retained = analysis.retained_stats(i);
+    HopRow {
+        address: format!("{:#x}", obj.address),
+        kind: obj.kind.clone(),
+        label: obj.to_string(),
+        self_bytes: obj.bytes,
+        retained_bytes: retained.bytes,
+        split: retained.into(),
+    }
+}
+
+fn growth_table((largest, rest): (Vec<(&String, Delta)>, Delta)) -> Table<GrowthRow, Delta> {
+    Table {
+        largest: largest
+            .into_iter()
+            .map(|(kind, growth)| GrowthRow {
+                kind: kind.clone(),
+                growth,
+            })
+            .collect(),
+        rest,
+    }
+}
+
+fn series_table(series: Vec<&Series>) -> Table<SeriesRow, ()> {
+    Table {
+        largest: series
+            .into_iter()
+            .map(|s| SeriesRow {
+                kind: s.kind.clone(),
+                retained_bytes: s.retained.iter().map(|stats| stats.bytes).collect(),
+                slot_bytes: s.retained.iter().map(|stats| stats.slot_bytes).collect(),
+                malloc_bytes: s
+                    .retained
+                    .iter()
+                    .map(|stats| stats.malloc_bytes())
+                    .collect(),
+                counts: s.live.iter().map(|stats| stats.count).collect(),
+            })
+            .collect(),
+        rest: (),
+    }
+}
+
+// Anything the tool prints, which can be rendered in any of the formats.
+pub trait Report: Serialize {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()>;
+}
+
+pub fn write<T: Report, W: Write>(
+    report: &T,
+    mut out: W,
+    format: Format,
+    units: Units,
+) -> io::Result<()> {
+    match format {
+        Format::Table => report.print(&mut TextPrinter::new(out, units)),
+        Format::Markdown => report.print(&mut MarkdownPrinter::new(out, units)),
+        Format::Csv => report.print(&mut CsvPrinter::new(out, units)),
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, report)?;
+            writeln!(out)
+        }
+        Format::Yaml => serde_yaml::to_writer(&mut out, report).map_err(io::Error::other),
+    }
+}
+
+// Which rows of the summary tables to include, and in what order.
+#[derive(Debug, Clone)]
+pub struct Selection {
+    pub top_types: usize,
+    pub top_retainers: usize,
+    pub sort: Sort,
+    pub filter: KindFilter,
+    pub group_by: GroupBy,
+}
+
+// The default report on a single heap dump (or subtree of one).
+#[derive(Debug, Serialize)]
+pub struct SummaryReport {
+    #[serde(skip)]
+    grouping: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub totals: Stats,
+    pub live_by_kind: Table<KindRow>,
+    pub retainers: Table<RetainerRow>,
+    pub retained_by_kind: Table<KindRow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unreachable_by_kind: Option<Table<KindRow>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_dominated_by_kind: Option<Table<KindRow>>,
+}
+
+impl SummaryReport {
+    pub fn new(
+        analysis: &Analysis,
+        subtree_root: Option<usize>,
+        selection: Selection,
+    ) -> SummaryReport {
+        let Selection {
+            top_types,
+            top_retainers,
+            sort,
+            ref filter,
+            group_by,
+        } = selection;
+        let rest_by_kind =
+            kind_table(analysis.unreachable_stats_by_kind(top_types, sort, filter, group_by));
+        let (unreachable_by_kind, not_dominated_by_kind) = match subtree_root {
+            Some(_) => (None, Some(rest_by_kind)),
+            None => (Some(rest_by_kind), None),
+        };
+
+        SummaryReport {
+            grouping: match group_by {
+                GroupBy::Kind => "Object types",
+                GroupBy::Namespace => "Namespaces",
+                GroupBy::File => "Files",
+                GroupBy::Location => "Allocation sites",
+                GroupBy::Method => "Allocating methods",
+                GroupBy::Gem => "Gems",
+            },
+            root: subtree_root.map(|address| format!("{:#x}", address)),
+            totals: analysis.dominated_totals(),
+            live_by_kind: kind_table(
+                analysis.live_stats_by_kind(top_types, sort, filter, group_by),
+            ),
+            retainers: retainer_table(
+                analysis,
+                analysis.dominator_subtree_stats(top_retainers, sort, filter),
+            ),
+            retained_by_kind: kind_table(
+                analysis.retained_stats_by_kind(top_types, sort, filter, group_by),
+            ),
+            unreachable_by_kind,
+            not_dominated_by_kind,
+        }
+    }
+
+    // Writes one CSV file per table into `dir`, returning the paths written.
+    pub fn write_csv(&self, dir: &Path) -> csv::Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+
+        let mut written = Vec::new();
+        let mut filename = |name: &str| {
+            written.push(dir.join(name));
+            dir.join(name)
+        };
+
+        self.live_by_kind.write_csv(&filename("live_by_kind.csv"))?;
+        self.retainers.write_csv(&filename("retainers.csv"))?;
+        self.retained_by_kind
+            .write_csv(&filename("retained_by_kind.csv"))?;
+        if let Some(ref table) = self.unreachable_by_kind {
+            table.write_csv(&filename("unreachable_by_kind.csv"))?;
+        }
+        if let Some(ref table) = self.not_dominated_by_kind {
+            table.write_csv(&filename("not_dominated_by_kind.csv"))?;
+        }
+
+        Ok(written)
+    }
+}
+
+impl Report for SummaryReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading(&format!("{} using the most live memory", self.grouping))?;
+        printer.table(&self.live_by_kind)?;
+
+        printer.heading("Objects retaining the most live memory")?;
+        printer.table(&self.retainers)?;
+
+        printer.heading(&format!("{} retaining the most live memory", self.grouping))?;
+        printer.table(&self.retained_by_kind)?;
+
+        if let Some(ref table) = self.unreachable_by_kind {
+            printer.heading("Objects unreachable from root")?;
+            printer.table(table)?;
+        }
+
+        if let Some(ref table) = self.not_dominated_by_kind {
+            printer.heading(&format!(
+                "Objects reachable from, but not dominated by, {}",
+                self.root.as_ref().unwrap(),
+            ))?;
+            printer.table(table)?;
+        }
+
+        Ok(())
+    }
+}
+
+// References between object types, e.g. how many Hashes point at Strings.
+#[derive(Debug, Serialize)]
+pub struct RefsMatrixReport {
+    pub references: Table<ReferenceRow>,
+}
+
+impl RefsMatrixReport {
+    pub fn new(analysis: &Analysis, selection: Selection) -> RefsMatrixReport {
+        let (largest, rest) = analysis.reference_stats_by_kind(
+            selection.top_types,
+            selection.sort,
+            &selection.filter,
+            selection.group_by,
+        );
+        RefsMatrixReport {
+            references: Table {
+                largest: largest
+                    .into_iter()
+                    .map(|((from, to), stats)| ReferenceRow {
+                        from: from.to_string(),
+                        to: to.to_string(),
+                        stats,
+                    })
+                    .collect(),
+                rest,
+            },
+        }
+    }
+}
+
+impl Report for RefsMatrixReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading("References between object types, by bytes referenced")?;
+        printer.table(&self.references)
+    }
+}
+
+// What dominates the live instances of one object type, e.g. "85% of Foo
+// instances are retained by Bar".
+#[derive(Debug, Serialize)]
+pub struct RetainersOfReport {
+    pub kind: String,
+    pub instances: usize,
+    pub dominators: Table<DominatorRow>,
+}
+
+impl RetainersOfReport {
+    pub fn new(analysis: &Analysis, kind: &str, selection: Selection) -> RetainersOfReport {
+        let (largest, rest) = analysis.dominator_stats_of_kind(
+            kind,
+            selection.top_types,
+            selection.sort,
+            selection.group_by,
+        );
+        let instances = largest.iter().map(|(_, s)| s.count).sum::<usize>() + rest.count;
+        RetainersOfReport {
+            kind: kind.to_string(),
+            instances,
+            dominators: Table {
+                largest: largest
+                    .into_iter()
+                    .map(|(dominator, stats)| DominatorRow {
+                        kind: dominator.to_string(),
+                        instances: stats.count,
+                        share: stats.count as f64 / instances as f64,
+                        retained_bytes: stats.bytes,
+                        split: stats.into(),
+                    })
+                    .collect(),
+                rest,
+            },
+        }
+    }
+}
+
+impl Report for RetainersOfReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let most = self
+            .dominators
+            .largest
+            .iter()
+            .max_by_key(|row| row.instances);
+        match most {
+            Some(top) => printer.text(&format!(
+                "{:.0}% of {} instances are retained by {}",
+                top.share * 100.0,
+                self.kind,
+                top.kind
+            ))?,
+            None => return printer.text(&format!("No live {} instances", self.kind)),
+        }
+
+        printer.heading(&format!(
+            "Dominators of the {} live {} instances",
+            self.instances, self.kind
+        ))?;
+        printer.table(&self.dominators)
+    }
+}
+
+// Everything dominated by one object, i.e. what would be freed if it were
+// released, by type.
+#[derive(Debug, Serialize)]
+pub struct DominatedReport {
+    pub address: String,
+    pub label: String,
+    pub totals: Stats,
+    pub by_kind: Table<KindRow>,
+}
+
+impl DominatedReport {
+    // `analysis` is of the subtree rooted at the object
+    pub fn new(analysis: &Analysis, selection: Selection) -> DominatedReport {
+        let root = &analysis.dominated_subgraph()[analysis.root()];
+        DominatedReport {
+            address: format!("{:#x}", root.address),
+            label: root.to_string(),
+            totals: analysis.dominated_totals(),
+            by_kind: kind_table(analysis.live_stats_by_kind(
+                selection.top_types,
+                selection.sort,
+                &selection.filter,
+                selection.group_by,
+            )),
+        }
+    }
+}
+
+impl Report for DominatedReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "Releasing {} would free {} ({} objects)",
+            self.label,
+            units.bytes(self.totals.bytes),
+            self.totals.count
+        ))?;
+        printer.heading(&format!("Objects dominated by {}", self.address))?;
+        printer.table(&self.by_kind)
+    }
+}
+
+// The largest individual objects, reachable or not, by their own size.
+#[derive(Debug, Serialize)]
+pub struct LargestReport {
+    pub objects: Table<ObjectRow>,
+}
+
+impl LargestReport {
+    pub fn new(graph: &ReferenceGraph, top_n: usize, filter: &KindFilter) -> LargestReport {
+        LargestReport {
+            objects: object_table(
+                graph
+                    .raw_nodes()
+                    .iter()
+                    .map(|n| &n.weight)
+                    .filter(|o| !o.is_root() && filter.matches(&o.kind))
+                    .collect(),
+                top_n,
+            ),
+        }
+    }
+}
+
+// The largest of `objects` by their own size
+fn object_table(mut objects: Vec<&Object>, top_n: usize) -> Table<ObjectRow> {
+    objects.sort_unstable_by_key(|o| (Reverse(o.bytes), o.address));
+    let rest = objects
+        .iter()
+        .skip(top_n)
+        .fold(Stats::default(), |mut acc, o| acc.add(o.stats()));
+    objects.truncate(top_n);
+
+    Table {
+        largest: objects
+            .into_iter()
+            .map(|o| ObjectRow {
+                address: format!("{:#x}", o.address),
+                kind: o.kind.clone(),
+                label: o.to_string(),
+                bytes: o.bytes,
+                split: o.stats().into(),
+            })
+            .collect(),
+        rest,
+    }
+}
+
+// Objects in the dump that aren't reachable from the root, i.e. garbage that
+// hasn't been collected yet.
+#[derive(Debug, Serialize)]
+pub struct UnreachableReport {
+    pub totals: Stats,
+    // Reachable and unreachable objects alike
+    pub heap: Stats,
+    pub by_kind: Table<KindRow>,
+    pub objects: Table<ObjectRow>,
+}
+
+impl UnreachableReport {
+    // `analysis` is of the full graph
+    pub fn new(analysis: &Analysis, selection: Selection) -> UnreachableReport {
+        let unreachable: Vec<&Object> = analysis
+            .rest()
+            .iter()
+            .filter(|o| selection.filter.matches(&o.kind))
+            .collect();
+        let totals = unreachable
+            .iter()
+            .fold(Stats::default(), |mut acc, o| acc.add(o.stats()));
+        let heap = analysis
+            .rest()
+            .iter()
+            .fold(analysis.dominated_totals(), |mut acc, o| acc.add(o.stats()));
+
+        UnreachableReport {
+            totals,
+            heap,
+            by_kind: kind_table(analysis.unreachable_stats_by_kind(
+                selection.top_types,
+                selection.sort,
+                &selection.filter,
+                selection.group_by,
+            )),
+            objects: object_table(unreachable, selection.top_retainers),
+        }
+    }
+}
+
+impl Report for UnreachableReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "{} ({} objects) unreachable from root, {:.1}% of the heap",
+            units.bytes(self.totals.bytes),
+            self.totals.count,
+            self.totals.bytes as f64 / self.heap.bytes.max(1) as f64 * 100.0
+        ))?;
+        printer.heading("Object types unreachable from root")?;
+        printer.table(&self.by_kind)?;
+        printer.heading("Largest objects unreachable from root")?;
+        printer.table(&self.objects)
+    }
+}
+
+impl Report for LargestReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading("Largest objects")?;
+        printer.table(&self.objects)
+    }
+}
+
+// The arrays and hashes with the most elements, and those with the most
+// capacity allocated beyond what their elements need.
+#[derive(Debug, Serialize)]
+pub struct CollectionsReport {
+    pub collections: Table<CollectionRow, ()>,
+    pub wasted: Stats,
+    pub wasted_by_kind: Table<KindRow>,
+    pub most_wasteful: Table<SlackRow, ()>,
+}
+
+impl CollectionsReport {
+    // `found` are the collections as found in the dump
+    pub fn new(analysis: &Analysis, found: Vec<Collection>, top_n: usize) -> CollectionsReport {
+        let graph = analysis.dominated_subgraph();
+        let indices: HashMap<usize, Index> = graph
+            .node_indices()
+            .map(|i| (graph[i].address, i))
+            .collect();
+
+        // Unreachable collections aren't in the analysis
+        let mut collections: Vec<(Index, Collection)> = found
+            .into_iter()
+            .filter_map(|c| indices.get(&c.address).map(|i| (*i, c)))
+            .collect();
+
+        let mut wasted = Stats::default();
+        let mut wasted_by_kind: HashMap<&str, Stats> = HashMap::new();
+        for (i, collection) in collections.iter().filter(|(_, c)| c.wasted_bytes() > 0) {
+            let waste = Stats {
+                count: 1,
+                bytes: collection.wasted_bytes(),
+                ..Stats::default()
+            };
+            wasted = wasted.add(waste);
+            let stats = wasted_by_kind.entry(&graph[*i].kind).or_default();
+            *stats = stats.add(waste);
+        }
+        let mut wasted_by_kind: Vec<(Cow<str>, Stats)> = wasted_by_kind
+            .into_iter()
+            .map(|(kind, stats)| (Cow::Borrowed(kind), stats))
+            .collect();
+        wasted_by_kind
+            .sort_unstable_by(|(a, x), (b, y)| y.bytes.cmp(&x.bytes).then_with(|| a.cmp(b)));
+        let rest = wasted_by_kind
+            .split_off(top_n.min(wasted_by_kind.len()))
+            .iter()
+            .fold(Stats::default(), |mut acc, (_, stats)| acc.add(*stats));
+        let wasted_by_kind = kind_table((wasted_by_kind, rest));
+
+        collections.sort_unstable_by_key(|(i, c)| (Reverse(c.wasted_bytes()), graph[*i].address));
+        let most_wasteful = collections
+            .iter()
+            .take(top_n)
+            .filter(|(_, c)| c.wasted_bytes() > 0)
+            .map(|(i, c)| SlackRow {
+                address: format!("{:#x}", graph[*i].address),
+                kind: graph[*i].kind.clone(),
+                label: graph[*i].to_string(),
+                elements: c.elements,
+                self_bytes: c.bytes,
+                split: graph[*i].stats().into(),
+                wasted_bytes: c.wasted_bytes(),
+            })
+            .collect();
+
+        collections.sort_unstable_by_key(|(i, c)| (Reverse(c.elements), graph[*i].address));
+        collections.truncate(top_n);
+
+        CollectionsReport {
+            wasted,
+            wasted_by_kind,
+            most_wasteful: Table {
+                largest: most_wasteful,
+                rest: (),
+            },
+            collections: Table {
+                largest: collections
+                    .into_iter()
+                    .map(|(i, Collection { elements, .. })| CollectionRow {
+                        address: format!("{:#x}", graph[i].address),
+                        kind: graph[i].kind.clone(),
+                        label: graph[i].to_string(),
+                        elements,
+                        retained_bytes: analysis.retained_stats(i).bytes,
+                        split: analysis.retained_stats(i).into(),
+                        dominator: analysis
+                            .dominator(i)
+                            .map(|d| graph[d].to_string())
+                            .unwrap_or_default(),
+                    })
+                    .collect(),
+                rest: (),
+            },
+        }
+    }
+}
+
+impl Report for CollectionsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading("Arrays and hashes with the most elements")?;
+        printer.table(&self.collections)?;
+        printer.heading("Types of collections wasting the most capacity")?;
+        printer.table(&self.wasted_by_kind)?;
+        printer.heading("Collections wasting the most capacity")?;
+        printer.table(&self.most_wasteful)?;
+
+        let units = printer.units();
+        printer.text(&format!(
+            "Estimated {} of capacity wasted across {} collections",
+            units.bytes(self.wasted.bytes),
+            self.wasted.count
+        ))
+    }
+}
+
+// Reference cycles, with the most members first. Objects in a cycle are
+// only dominated by whatever dominates the whole cycle, which is why they
+// often show up in surprising places in the dominator tree.
+#[derive(Debug, Serialize)]
+pub struct CyclesReport {
+    pub cycle_count: usize,
+    pub object_count: usize,
+    pub cycles: Table<CycleRow, ()>,
+}
+
+impl CyclesReport {
+    pub fn new(analysis: &Analysis, top_n: usize) -> CyclesReport {
+        let graph = analysis.dominated_subgraph();
+        let mut cycles = analysis.cycles();
+        let cycle_count = cycles.len();
+        let object_count = cycles.iter().map(|(members, _)| members.len()).sum();
+        cycles.truncate(top_n);
+
+        CyclesReport {
+            cycle_count,
+            object_count,
+            cycles: Table {
+                largest: cycles
+                    .into_iter()
+                    .map(|(members, retained)| CycleRow {
+                        address: format!("{:#x}", graph[members[0]].address),
+                        kind: graph[members[0]].kind.clone(),
+                        label: graph[members[0]].to_string(),
+                        members: members.len(),
+                        self_bytes: members.iter().map(|i| graph[*i].bytes).sum(),
+                        retained_bytes: retained.bytes,
+                        split: retained.into(),
+                    })
+                    .collect(),
+                rest: (),
+            },
+        }
+    }
+}
+
+impl Report for CyclesReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading("Reference cycles, by number of members")?;
+        printer.table(&self.cycles)?;
+        printer.text(&format!(
+            "{} cycles with {} objects in all",
+            self.cycle_count, self.object_count
+        ))
+    }
+}
+
+// References to addresses that aren't in the dump, which are dropped from the
+// graph. Many of them suggest a truncated or inconsistent dump.
+#[derive(Debug, Serialize)]
+pub struct DanglingReport {
+    pub references: usize,
+    pub objects: usize,
+    pub by_kind: Table<DanglingRow, ()>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dangling: Option<Table<DanglingReferenceRow, ()>>,
+}
+
+impl DanglingReport {
+    // With `list`, every dangling reference is listed as well
+    pub fn new(
+        graph: &ReferenceGraph,
+        mut dangling: Dangling,
+        top_n: usize,
+        list: bool,
+    ) -> DanglingReport {
+        dangling.sort_unstable_by_key(|(i, missing)| (graph[*i].address, *missing));
+
+        let mut referrers: HashMap<&str, HashSet<Index>> = HashMap::new();
+        let mut references: HashMap<&str, usize> = HashMap::new();
+        for (i, _) in &dangling {
+            referrers.entry(&graph[*i].kind).or_default().insert(*i);
+            *references.entry(&graph[*i].kind).or_default() += 1;
+        }
+        let mut by_kind: Vec<DanglingRow> = referrers
+            .into_iter()
+            .map(|(kind, objects)| DanglingRow {
+                kind: kind.to_string(),
+                objects: objects.len(),
+                references: references[kind],
+            })
+            .collect();
+        by_kind.sort_unstable_by(|a, b| {
+            b.references
+                .cmp(&a.references)
+                .then_with(|| a.kind.cmp(&b.kind))
+        });
+        let objects = by_kind.iter().map(|row| row.objects).sum();
+        by_kind.truncate(top_n);
+
+        DanglingReport {
+            references: dangling.len(),
+            objects,
+            by_kind: Table {
+                largest: by_kind,
+                rest: (),
+            },
+            dangling: if list {
+                Some(Table {
+                    largest: dangling
+                        .iter()
+                        .map(|(i, missing)| DanglingReferenceRow {
+                            address: format!("{:#x}", graph[*i].address),
+                            label: graph[*i].to_string(),
+                            missing: format!("{:#x}", missing),
+                        })
+                        .collect(),
+                    rest: (),
+                })
+            } else {
+                None
+            },
+        }
+    }
+}
+
+impl Report for DanglingReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.text(&format!(
+            "{} references from {} objects to addresses not in the dump",
+            self.references, self.objects
+        ))?;
+        if self.references == 0 {
+            return Ok(());
+        }
+
+        printer.heading("Types with the most dangling references")?;
+        printer.table(&self.by_kind)?;
+
+        if let Some(ref dangling) = self.dangling {
+            printer.heading("Dangling references")?;
+            printer.table(dangling)?;
+        }
+        Ok(())
+    }
+}
+
+// String values with more than one copy, by the bytes taken up by all but
+// one of them.
+#[derive(Debug, Serialize)]
+pub struct DuplicatesReport {
+    pub wasted: Stats,
+    pub duplicates: Table<DuplicateRow>,
+}
+
+impl DuplicatesReport {
+    // `strings` are the copies and total bytes of each string value
+    pub fn new(strings: HashMap<String, Stats>, top_n: usize) -> DuplicatesReport {
+        let mut duplicates: Vec<DuplicateRow> = strings
+            .into_iter()
+            .filter(|(_, stats)| stats.count > 1)
+            .map(|(value, stats)| {
+                // The copies are taken to be alike, so all but one of
+                // them split the same way as the total
+                let wasted_slots = stats.slot_bytes - stats.slot_bytes / stats.count;
+                let wasted_bytes = stats.bytes - stats.bytes / stats.count;
+                DuplicateRow {
+                    value,
+                    copies: stats.count,
+                    total_bytes: stats.bytes,
+                    wasted_bytes,
+                    split: Split {
+                        slot_bytes: wasted_slots,
+                        malloc_bytes: wasted_bytes.saturating_sub(wasted_slots),
+                    },
+                }
+            })
+            .collect();
+        duplicates.sort_unstable_by(|a, b| {
+            b.wasted_bytes
+                .cmp(&a.wasted_bytes)
+                .then_with(|| a.value.cmp(&b.value))
+        });
+
+        let waste = |rows: &[DuplicateRow]| {
+            rows.iter().fold(Stats::default(), |mut acc, row| {
+                acc.add(Stats {
+                    count: row.copies,
+                    bytes: row.wasted_bytes,
+                    slot_bytes: row.split.slot_bytes,
+                })
+            })
+        };
+        let wasted = waste(&duplicates);
+        let rest = waste(duplicates.get(top_n..).unwrap_or_default());
+        duplicates.truncate(top_n);
+
+        DuplicatesReport {
+            wasted,
+            duplicates: Table {
+                largest: duplicates,
+                rest,
+            },
+        }
+    }
+}
+
+impl Report for DuplicatesReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading("Duplicated strings, by bytes wasted on extra copies")?;
+        printer.table(&self.duplicates)?;
+
+        let units = printer.units();
+        printer.text(&format!(
+            "Total wasted: {} across {} copies",
+            units.bytes(self.wasted.bytes),
+            self.wasted.count
+        ))
+    }
+}
+
+// How strings are stored, and what freezing duplicated ones could save:
+// every frozen copy of a value can share one interned string.
+#[derive(Debug, Serialize)]
+pub struct StringsReport {
+    pub total: Stats,
+    pub storage: Table<KindRow>,
+    pub freezable: Stats,
+}
+
+impl StringsReport {
+    pub fn new(strings: &[DumpedString]) -> StringsReport {
+        let storage = ["frozen", "not frozen", "embedded", "shared", "own buffer"]
+            .iter()
+            .map(|&category| KindRow {
+                kind: category.to_string(),
+                stats: strings
+                    .iter()
+                    .filter(|s| match category {
+                        "frozen" => s.frozen,
+                        "not frozen" => !s.frozen,
+                        "embedded" => s.embedded,
+                        "shared" => s.shared,
+                        _ => !s.embedded && !s.shared,
+                    })
+                    .fold(Stats::default(), |mut acc, s| acc.add(s.stats())),
+            })
+            .collect();
+
+        // Unfrozen copies of each value, and whether there's a frozen one
+        // they could all share. Shared strings cost little to begin with.
+        let mut values: HashMap<&str, (Stats, bool)> = HashMap::new();
+        for string in strings.iter().filter(|s| !s.shared) {
+            if let Some(ref value) = string.value {
+                let (unfrozen, frozen) = values.entry(value).or_default();
+                if string.frozen {
+                    *frozen = true;
+                } else {
+                    *unfrozen = unfrozen.add(string.stats());
+                }
+            }
+        }
+        let freezable = values
+            .values()
+            .fold(Stats::default(), |mut acc, (unfrozen, frozen)| {
+                acc.add(match (unfrozen.count, frozen) {
+                    (0, _) => Stats::default(),
+                    (_, true) => *unfrozen,
+                    // All but one copy
+                    (count, false) => Stats {
+                        count: count - 1,
+                        bytes: unfrozen.bytes - unfrozen.bytes / count,
+                        slot_bytes: unfrozen.slot_bytes - unfrozen.slot_bytes / count,
+                    },
+                })
+            });
+
+        StringsReport {
+            total: strings
+                .iter()
+                .fold(Stats::default(), |mut acc, s| acc.add(s.stats())),
+            storage: Table {
+                largest: storage,
+                rest: Stats::default(),
+            },
+            freezable,
+        }
+    }
+}
+
+impl Report for StringsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "{} in {} strings, reachable or not",
+            units.bytes(self.total.bytes),
+            self.total.count
+        ))?;
+        printer.heading("Strings by how they're stored")?;
+        printer.table(&self.storage)?;
+        printer.text(&format!(
+            "Freezing duplicated strings could save about {} across {} copies",
+            units.bytes(self.freezable.bytes),
+            self.freezable.count
+        ))
+    }
+}
+
+// All symbols, the largest and most recently created of them, and whether
+// there are suspiciously many: symbols created from user input (e.g. with
+// `to_sym`) are a classic leak.
+#[derive(Debug, Serialize)]
+pub struct SymbolsReport {
+    pub symbols: Stats,
+    pub max_symbols: usize,
+    pub largest: Table<SymbolRow>,
+    // Only when the dump was taken with allocation tracing
+    pub newest: Option<Table<SymbolRow>>,
+}
+
+impl SymbolsReport {
+    // Symbols beyond `max_symbols` are flagged as abnormally many
+    pub fn new(symbols: Vec<Symbol>, top_n: usize, max_symbols: usize) -> SymbolsReport {
+        let total = symbols
+            .iter()
+            .fold(Stats::default(), |mut acc, s| acc.add(s.stats()));
+
+        let table = |mut symbols: Vec<&Symbol>| {
+            let rest = symbols
+                .iter()
+                .skip(top_n)
+                .fold(Stats::default(), |mut acc, s| acc.add(s.stats()));
+            symbols.truncate(top_n);
+            Table {
+                largest: symbols
+                    .into_iter()
+                    .map(|s| SymbolRow {
+                        address: format!("{:#x}", s.address),
+                        value: s.value.clone(),
+                        bytes: s.bytes,
+                        split: s.stats().into(),
+                        generation: s.generation,
+                    })
+                    .collect(),
+                rest,
+            }
+        };
+
+        let mut largest: Vec<&Symbol> = symbols.iter().collect();
+        largest.sort_unstable_by_key(|s| (Reverse(s.bytes), s.address));
+
+        let mut newest: Vec<&Symbol> = symbols.iter().filter(|s| s.generation.is_some()).collect();
+        newest.sort_unstable_by_key(|s| (Reverse(s.generation), s.address));
+
+        SymbolsReport {
+            symbols: total,
+            max_symbols,
+            largest: table(largest),
+            newest: if newest.is_empty() {
+                None
+            } else {
+                Some(table(newest))
+            },
+        }
+    }
+
+    pub fn too_many(&self) -> bool {
+        self.symbols.count > self.max_symbols
+    }
+}
+
+impl Report for SymbolsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "{} symbols using {}",
+            self.symbols.count,
+            units.bytes(self.symbols.bytes)
+        ))?;
+        if self.too_many() {
+            printer.text(&format!(
+                "Warning: more than {} symbols, they may be created dynamically from unbounded input (e.g. with to_sym)",
+                self.max_symbols
+            ))?;
+        }
+
+        printer.heading("Largest symbols")?;
+        printer.table(&self.largest)?;
+
+        if let Some(newest) = &self.newest {
+            printer.heading("Most recently created symbols")?;
+            printer.table(newest)?;
+        }
+        Ok(())
+    }
+}
+
+// Interned strings, the largest and most recently created of them. The VM
+// interns literals, but also strings frozen with -@ or turned into symbols,
+// so the table can grow without bound on user input.
+#[derive(Debug, Serialize)]
+pub struct FstringsReport {
+    pub fstrings: Stats,
+    pub strings: Stats,
+    pub largest: Table<FstringRow>,
+    // Only when the dump was taken with allocation tracing
+    pub newest: Option<Table<FstringRow>>,
+}
+
+impl FstringsReport {
+    pub fn new(strings: &[DumpedString], top_n: usize) -> FstringsReport {
+        let total = |strings: &[&DumpedString]| {
+            strings
+                .iter()
+                .fold(Stats::default(), |mut acc, s| acc.add(s.stats()))
+        };
+        let table = |mut strings: Vec<&DumpedString>| {
+            let rest = total(strings.get(top_n..).unwrap_or_default());
+            strings.truncate(top_n);
+            Table {
+                largest: strings
+                    .into_iter()
+                    .map(|s| FstringRow {
+                        address: format!("{:#x}", s.address),
+                        value: s.value.clone().unwrap_or_default(),
+                        bytes: s.bytes,
+                        split: s.stats().into(),
+                        generation: s.generation,
+                    })
+                    .collect(),
+                rest,
+            }
+        };
+
+        let mut largest: Vec<&DumpedString> = strings.iter().filter(|s| s.fstring).collect();
+        largest.sort_unstable_by_key(|s| (Reverse(s.bytes), s.address));
+        let fstrings = total(&largest);
+
+        let mut newest: Vec<&DumpedString> = largest
+            .iter()
+            .filter(|s| s.generation.is_some())
+            .copied()
+            .collect();
+        newest.sort_unstable_by_key(|s| (Reverse(s.generation), s.address));
+
+        FstringsReport {
+            fstrings,
+            strings: total(&strings.iter().collect::<Vec<_>>()),
+            largest: table(largest),
+            newest: if newest.is_empty() {
+                None
+            } else {
+                Some(table(newest))
+            },
+        }
+    }
+}
+
+impl Report for FstringsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "{} interned strings using {}, {} of all strings",
+            self.fstrings.count,
+            units.bytes(self.fstrings.bytes),
+            percent_of(self.fstrings.bytes, self.strings.bytes)
+        ))?;
+
+        printer.heading("Largest interned strings")?;
+        printer.table(&self.largest)?;
+
+        if let Some(newest) = &self.newest {
+            printer.heading("Most recently interned strings")?;
+            printer.table(newest)?;
+        }
+        Ok(())
+    }
+}
+
+// Strings whose values match a pattern, largest first.
+#[derive(Debug, Serialize)]
+pub struct GrepReport {
+    pub pattern: String,
+    pub matches: Table<MatchRow>,
+}
+
+impl GrepReport {
+    // `found` are the matching strings' addresses and values, as found in the dump
+    pub fn new(
+        analysis: &Analysis,
+        pattern: &str,
+        found: Vec<(usize, String)>,
+        top_n: usize,
+    ) -> GrepReport {
+        let graph = analysis.dominated_subgraph();
+        let indices: HashMap<usize, Index> = graph
+            .node_indices()
+            .map(|i| (graph[i].address, i))
+            .collect();
+
+        // Unreachable strings aren't in the analysis
+        let mut matches: Vec<(Index, String)> = found
+            .into_iter()
+            .filter_map(|(address, value)| indices.get(&address).map(|i| (*i, value)))
+            .collect();
+        matches.sort_unstable_by_key(|(i, _)| (Reverse(graph[*i].bytes), graph[*i].address));
+        let rest = matches
+            .iter()
+            .skip(top_n)
+            .fold(Stats::default(), |mut acc, (i, _)| {
+                acc.add(graph[*i].stats())
+            });
+        matches.truncate(top_n);
+
+        GrepReport {
+            pattern: pattern.to_string(),
+            matches: Table {
+                largest: matches
+                    .into_iter()
+                    .map(|(i, value)| {
+                        let mut dominators = Vec::new();
+                        let mut d = i;
+                        while let Some(next) = analysis.dominator(d) {
+                            dominators.push(graph[next].to_string());
+                            d = next;
+                        }
+                        dominators.reverse();
+                        MatchRow {
+                            address: format!("{:#x}", graph[i].address),
+                            value,
+                            self_bytes: graph[i].bytes,
+                            split: graph[i].stats().into(),
+                            retained_bytes: analysis.retained_stats(i).bytes,
+                            dominators,
+                        }
+                    })
+                    .collect(),
+                rest,
+            },
+        }
+    }
+}
+
+impl Report for GrepReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading(&format!("Live strings matching /{}/", self.pattern))?;
+        printer.table(&self.matches)
+    }
+}
+
+// Everything known about one object.
+#[derive(Debug, Serialize)]
+pub struct ExplainReport {
+    pub address: String,
+    pub kind: String,
+    #[serde(rename = "type")]
+    pub object_type: Option<String>,
+    pub label: String,
+    pub value: Option<String>,
+    pub location: Option<String>,
+    pub generation: Option<usize>,
+    pub self_bytes: usize,
+    pub retained_bytes: usize,
+    pub retained_count: usize,
+    pub dominator: Option<HopRow>,
+    pub incoming_references: usize,
+    pub references: Table<HopRow, ()>,
+}
+
+impl ExplainReport {
+    // `record` is the object's line from the dump, if it could be found
+    pub fn new(
+        analysis: &Analysis,
+        target: Index,
+        record: Option<&serde_json::Value>,
+    ) -> ExplainReport {
+        let graph = analysis.dominated_subgraph();
+        let obj = &graph[target];
+        let retained = analysis.retained_stats(target);
+        let field = |name: &str| {
+            record
+                .and_then(|r| r.get(name))
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string())
+        };
+
+        let mut references: Vec<Index> = graph.neighbors(target).collect();
+        references.sort_unstable_by_key(|i| {
+            (
+                Reverse(analysis.retained_stats(*i).bytes),
+                graph[*i].address,
+            )
+        });
+        references.dedup();
+
+        ExplainReport {
+            address: format!("{:#x}", obj.address),
+            kind: obj.kind.clone(),
+            object_type: field("type"),
+            label: obj.to_string(),
+            value: field("value"),
+            location: obj.location.as_ref().map(|l| match l.method {
+                Some(ref method) => format!("{} in {}", l, method),
+                None => l.to_string(),
+            }),
+            generation: obj.generation,
+            self_bytes: obj.bytes,
+            retained_bytes: retained.bytes,
+            retained_count: retained.count,
+            dominator: analysis.dominator(target).map(|d| hop_row(analysis, d)),
+            incoming_references: graph
+                .neighbors_directed(target, Direction::Incoming)
+                .collect::<HashSet<Index>>()
+                .len(),
+            references: Table {
+                largest: references
+                    .into_iter()
+                    .map(|i| hop_row(analysis, i))
+                    .collect(),
+                rest: (),
+            },
+        }
+    }
+}
+
+impl Report for ExplainReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        let fields = vec![
+            ("Address", Some(self.address.clone())),
+            ("Class", Some(self.kind.clone())),
+            ("Type", self.object_type.clone()),
+            ("Label", Some(self.label.clone())),
+            ("Value", self.value.as_ref().map(|v| format!("{:?}", v))),
+            ("Allocated at", self.location.clone()),
+            ("Generation", self.generation.map(|g| g.to_string())),
+            ("Self bytes", Some(units.bytes(self.self_bytes))),
+            (
+                "Retained bytes",
+                Some(format!(
+                    "{} ({} objects)",
+                    units.bytes(self.retained_bytes),
+                    self.retained_count
+                )),
+            ),
+            (
+                "Dominator",
+                self.dominator.as_ref().map(|d| d.label.clone()),
+            ),
+            (
+                "Incoming references",
+                Some(self.incoming_references.to_string()),
+            ),
+        ];
+        printer.heading(&self.label)?;
+        printer.table(&Table {
+            largest: fields
+                .into_iter()
+                .filter_map(|(field, value)| value.map(|value| FieldRow { field, value }))
+                .collect(),
+            rest: (),
+        })?;
+
+        printer.heading("References")?;
+        printer.table(&self.references)
+    }
+}
+
+// Live objects by the GC generation they were allocated in, oldest first, in
+// buckets of `bucket_size` generations. Only dumps taken with allocation
+// tracing record generations; the objects allocated before it was enabled are
+// counted apart.
+#[derive(Debug, Serialize)]
+pub struct GenerationsReport {
+    pub generations: Table<GenerationRow, ()>,
+    pub untraced: Stats,
+}
+
+impl GenerationsReport {
+    pub fn new(analysis: &Analysis, bucket_size: usize) -> GenerationsReport {
+        let graph = analysis.dominated_subgraph();
+        let bucket_size = bucket_size.max(1);
+
+        let mut buckets: BTreeMap<usize, Stats> = BTreeMap::new();
+        let mut untraced = Stats::default();
+        for obj in graph.raw_nodes().iter().map(|n| &n.weight) {
+            if obj.is_root() {
+                continue;
+            }
+            let stats = match obj.generation {
+                Some(generation) => buckets.entry(generation / bucket_size).or_default(),
+                None => &mut untraced,
+            };
+            *stats = stats.add(obj.stats());
+        }
+
+        GenerationsReport {
+            generations: Table {
+                largest: buckets
+                    .into_iter()
+                    .map(|(bucket, stats)| {
+                        let first = bucket * bucket_size;
+                        GenerationRow {
+                            generations: if bucket_size == 1 {
+                                first.to_string()
+                            } else {
+                                format!("{}-{}", first, first + bucket_size - 1)
+                            },
+                            stats,
+                        }
+                    })
+                    .collect(),
+                rest: (),
+            },
+            untraced,
+        }
+    }
+}
+
+impl Report for GenerationsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.heading("Live objects by the GC generation they were allocated in")?;
+        printer.table(&self.generations)?;
+        printer.text(&format!(
+            "{} ({} objects) with no generation recorded",
+            units.bytes(self.untraced.bytes),
+            self.untraced.count
+        ))
+    }
+}
+
+// IMEMO memory by subtype, e.g. method entries ("ment") or instruction
+// sequences ("iseq"): the VM allocates all sorts of internal objects as
+// IMEMO, so it's often the largest type in a dump.
+#[derive(Debug, Serialize)]
+pub struct ImemoReport {
+    pub live: Table<KindRow>,
+    pub retained: Table<KindRow>,
+}
+
+impl ImemoReport {
+    // `found` are the addresses and subtypes of IMEMO objects, as found in
+    // the dump
+    pub fn new(analysis: &Analysis, found: Vec<(usize, String)>, top_n: usize) -> ImemoReport {
+        let graph = analysis.dominated_subgraph();
+        let addresses: HashMap<usize, String> = found.into_iter().collect();
+        // Unreachable objects aren't in the analysis
+        let subtypes: HashMap<Index, &str> = graph
+            .node_indices()
+            .filter_map(|i| addresses.get(&graph[i].address).map(|t| (i, t.as_str())))
+            .collect();
+
+        let (mut live, mut retained): (HashMap<&str, Stats>, HashMap<&str, Stats>) =
+            Default::default();
+        for (&i, &subtype) in &subtypes {
+            let stats = live.entry(subtype).or_default();
+            *stats = stats.add(graph[i].stats());
+
+            // What nested IMEMOs of the same subtype retain is already counted
+            // towards the outermost one
+            let mut d = i;
+            let mut nested = false;
+            while let Some(next) = analysis.dominator(d) {
+                if subtypes.get(&next) == Some(&subtype) {
+                    nested = true;
+                    break;
+                }
+                d = next;
+            }
+            if !nested {
+                let stats = retained.entry(subtype).or_default();
+                *stats = stats.add(analysis.retained_stats(i));
+            }
+        }
+
+        ImemoReport {
+            live: largest_subtypes(live, top_n),
+            retained: largest_subtypes(retained, top_n),
+        }
+    }
+}
+
+fn largest_subtypes(stats: HashMap<&str, Stats>, top_n: usize) -> Table<KindRow> {
+    let mut largest: Vec<(Cow<str>, Stats)> = stats
+        .into_iter()
+        .map(|(subtype, stats)| (Cow::Borrowed(subtype), stats))
+        .collect();
+    largest.sort_unstable_by(|(a, x), (b, y)| y.bytes.cmp(&x.bytes).then_with(|| a.cmp(b)));
+    let rest = largest
+        .split_off(top_n.min(largest.len()))
+        .iter()
+        .fold(Stats::default(), |mut acc, (_, stats)| acc.add(*stats));
+    kind_table((largest, rest))
+}
+
+impl Report for ImemoReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading("IMEMO subtypes using the most live memory")?;
+        printer.table(&self.live)?;
+        printer.heading("IMEMO subtypes retaining the most live memory")?;
+        printer.table(&self.retained)
+    }
+}
+
+// How live plain objects use shapes (Ruby 3.2+): the shapes most objects
+// have, objects whose shapes got too complex to share, and memory spent on
+// instance variables that don't fit in their objects' slots.
+#[derive(Debug, Serialize)]
+pub struct ShapesReport {
+    pub objects: usize,
+    pub shape_count: usize,
+    pub shapes: Table<ShapeRow>,
+    pub too_complex: Stats,
+    // Bytes outside the objects' slots
+    pub unembedded: Stats,
+}
+
+// Types listed for each shape, beyond which the rest are elided
+const SHAPE_KINDS: usize = 3;
+
+impl ShapesReport {
+    // `found` are the shapes of plain objects, as found in the dump
+    pub fn new(analysis: &Analysis, found: Vec<ObjectShape>, top_n: usize) -> ShapesReport {
+        let graph = analysis.dominated_subgraph();
+        let indices: HashMap<usize, Index> = graph
+            .node_indices()
+            .map(|i| (graph[i].address, i))
+            .collect();
+
+        let mut objects = 0;
+        let mut by_shape: HashMap<usize, (Stats, BTreeSet<&str>)> = HashMap::new();
+        let (mut too_complex, mut unembedded) = (Stats::default(), Stats::default());
+        // Unreachable objects aren't in the analysis
+        for shape in found {
+            let obj = match indices.get(&shape.address) {
+                Some(i) => &graph[*i],
+                None => continue,
+            };
+            objects += 1;
+            let (stats, kinds) = by_shape.entry(shape.shape_id).or_default();
+            *stats = stats.add(obj.stats());
+            kinds.insert(&obj.kind);
+            if shape.too_complex {
+                too_complex = too_complex.add(obj.stats());
+            }
+            if !shape.embedded {
+                unembedded = unembedded.add(Stats {
+                    count: 1,
+                    bytes: shape.bytes.saturating_sub(shape.slot_bytes),
+                    ..Stats::default()
+                });
+            }
+        }
+
+        let shape_count = by_shape.len();
+        let mut shapes: Vec<ShapeRow> = by_shape
+            .into_iter()
+            .map(|(shape_id, (stats, kinds))| {
+                let mut listed: Vec<&str> = kinds.iter().take(SHAPE_KINDS).copied().collect();
+                if kinds.len() > SHAPE_KINDS {
+                    listed.push("...");
+                }
+                ShapeRow {
+                    shape_id,
+                    kinds: listed.join(", "),
+                    stats,
+                }
+            })
+            .collect();
+        shapes.sort_unstable_by_key(|row| (Reverse(row.stats.count), row.shape_id));
+        let rest = shapes
+            .split_off(top_n.min(shapes.len()))
+            .iter()
+            .fold(Stats::default(), |mut acc, row| acc.add(row.stats));
+
+        ShapesReport {
+            objects,
+            shape_count,
+            shapes: Table {
+                largest: shapes,
+                rest,
+            },
+            too_complex,
+            unembedded,
+        }
+    }
+}
+
+impl Report for ShapesReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "{} live objects with {} shapes",
+            self.objects, self.shape_count
+        ))?;
+        printer.heading("Shapes with the most objects")?;
+        printer.table(&self.shapes)?;
+        printer.text(&format!(
+            "{} ({} objects) with too complex shapes, keeping instance variables in hash tables",
+            units.bytes(self.too_complex.bytes),
+            self.too_complex.count
+        ))?;
+        printer.text(&format!(
+            "{} ({} objects) of instance variables outside their objects' slots",
+            units.bytes(self.unembedded.bytes),
+            self.unembedded.count
+        ))
+    }
+}
+
+// How full the heap pages holding the dump's objects are. Free slots on
+// pages that still hold objects count towards RSS without showing up in any
+// object's memsize, and mostly empty pages are what compaction frees.
+#[derive(Debug, Serialize)]
+pub struct PagesReport {
+    pub pages: usize,
+    pub page_bytes: usize,
+    pub objects: usize,
+    pub slots: usize,
+    pub free_bytes: usize,
+    pub occupancy: Table<PageRow, ()>,
+    // Pages less than half full, and the bytes of their free slots
+    pub sparse_pages: usize,
+    pub stranded_bytes: usize,
+}
+
+// Width of each occupancy range, in percent
+const OCCUPANCY_STEP: usize = 10;
+
+impl PagesReport {
+    pub fn new(pages: &[Page]) -> PagesReport {
+        let mut rows: Vec<PageRow> = (0..100)
+            .step_by(OCCUPANCY_STEP)
+            .map(|low| PageRow {
+                occupancy: format!("{}-{}%", low, low + OCCUPANCY_STEP),
+                pages: 0,
+                objects: 0,
+                free_bytes: 0,
+            })
+            .collect();
+        let (mut sparse_pages, mut stranded_bytes) = (0, 0);
+        for page in pages {
+            let bucket = ((page.occupancy() * 100.0) as usize / OCCUPANCY_STEP).min(rows.len() - 1);
+            let row = &mut rows[bucket];
+            row.pages += 1;
+            row.objects += page.objects.len();
+            row.free_bytes += page.free_bytes();
+            if page.occupancy() < 0.5 {
+                sparse_pages += 1;
+                stranded_bytes += page.free_bytes();
+            }
+        }
+
+        PagesReport {
+            pages: pages.len(),
+            page_bytes: pages.iter().map(|page| page.bytes).sum(),
+            objects: pages.iter().map(|page| page.objects.len()).sum(),
+            slots: pages.iter().map(Page::slots).sum(),
+            free_bytes: pages.iter().map(Page::free_bytes).sum(),
+            occupancy: Table {
+                largest: rows,
+                rest: (),
+            },
+            sparse_pages,
+            stranded_bytes,
+        }
+    }
+}
+
+impl Report for PagesReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "{} pages ({}) holding {} objects in {} slots, {} of them free",
+            self.pages,
+            units.bytes(self.page_bytes),
+            self.objects,
+            self.slots,
+            units.bytes(self.free_bytes)
+        ))?;
+        printer.heading("Pages by occupancy")?;
+        printer.table(&self.occupancy)?;
+        printer.text(&format!(
+            "{} stranded in free slots on {} pages less than half full",
+            units.bytes(self.stranded_bytes),
+            self.sparse_pages
+        ))
+    }
+}
+
+// Nearly empty heap pages, and the few objects keeping each of them in use.
+// `GC.compact` can move objects off these pages and free them, unless the
+// objects are pinned.
+#[derive(Debug, Serialize)]
+pub struct CompactionReport {
+    pub max_occupancy: usize,
+    pub pages: usize,
+    pub objects: usize,
+    pub pinned: usize,
+    pub free_bytes: usize,
+    // Pages with no pinned objects, which compaction could empty
+    pub movable_pages: usize,
+    pub movable_bytes: usize,
+    pub kinds: Table<KindRow>,
+    pub holders: Table<HolderRow, ()>,
+}
+
+impl CompactionReport {
+    // Pages count as nearly empty when less than `max_occupancy` percent of
+    // their slots are in use
+    pub fn new(
+        analysis: &Analysis,
+        pages: &[Page],
+        max_occupancy: usize,
+        top_n: usize,
+    ) -> CompactionReport {
+        let graph = analysis.dominated_subgraph();
+        let indices: HashMap<usize, Index> = graph
+            .node_indices()
+            .map(|i| (graph[i].address, i))
+            .collect();
+        let sparse: Vec<&Page> = pages
+            .iter()
+            .filter(|page| page.occupancy() * 100.0 < max_occupancy as f64)
+            .collect();
+        let movable: Vec<&&Page> = sparse.iter().filter(|page| page.pinned() == 0).collect();
+
+        // Unreachable objects will be freed by the next GC anyway
+        let mut held: Vec<(&Page, Slot, Index)> = sparse
+            .iter()
+            .flat_map(|page| page.objects.iter().map(move |slot| (*page, *slot)))
+            .filter_map(|(page, slot)| indices.get(&slot.address).map(|i| (page, slot, *i)))
+            .collect();
+
+        let mut by_kind: HashMap<&str, Stats> = HashMap::new();
+        for (_, _, i) in &held {
+            let stats = by_kind.entry(&graph[*i].kind).or_default();
+            *stats = stats.add(graph[*i].stats());
+        }
+        let mut kinds: Vec<(&str, Stats)> = by_kind.into_iter().collect();
+        kinds.sort_unstable_by_key(|(kind, stats)| (Reverse(stats.count), *kind));
+        let rest = kinds
+            .split_off(top_n.min(kinds.len()))
+            .iter()
+            .fold(Stats::default(), |mut acc, (_, stats)| acc.add(*stats));
+
+        held.sort_unstable_by_key(|(page, slot, _)| {
+            (!slot.pinned, page.objects.len(), page.address, slot.address)
+        });
+        held.truncate(top_n);
+        let parents = analysis.reference_parents();
+        let holders = held
+            .into_iter()
+            .map(|(page, slot, i)| HolderRow {
+                address: format!("{:#x}", graph[i].address),
+                page: format!("{:#x}", page.address),
+                kind: graph[i].kind.clone(),
+                label: graph[i].to_string(),
+                pinned: slot.pinned,
+                retained_bytes: analysis.retained_stats(i).bytes,
+                split: analysis.retained_stats(i).into(),
+                path: if parents.contains_key(&i) {
+                    // From the category of roots, e.g. "root[vm] -> Hash"
+                    let mut path = vec![i];
+                    while let Some(&parent) = parents.get(path.last().unwrap()) {
+                        path.push(parent);
+                    }
+                    path.iter()
+                        .rev()
+                        .skip(1)
+                        .map(|j| match graph[*j] {
+                            ref obj if obj.is_root() => obj.to_string(),
+                            ref obj => obj.kind.clone(),
+                        })
+                        .collect::<Vec<String>>()
+                        .join(" -> ")
+                } else {
+                    String::new()
+                },
+            })
+            .collect();
+
+        CompactionReport {
+            max_occupancy,
+            pages: sparse.len(),
+            objects: sparse.iter().map(|page| page.objects.len()).sum(),
+            pinned: sparse.iter().map(|page| page.pinned()).sum(),
+            free_bytes: sparse.iter().map(|page| page.free_bytes()).sum(),
+            movable_pages: movable.len(),
+            movable_bytes: movable.iter().map(|page| page.bytes).sum(),
+            kinds: Table {
+                largest: kinds
+                    .into_iter()
+                    .map(|(kind, stats)| KindRow {
+                        kind: kind.to_string(),
+                        stats,
+                    })
+                    .collect(),
+                rest,
+            },
+            holders: Table {
+                largest: holders,
+                rest: (),
+            },
+        }
+    }
+}
+
+impl Report for CompactionReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "{} pages less than {}% occupied hold {} objects ({} pinned), with {} of free slots",
+            self.pages,
+            self.max_occupancy,
+            self.objects,
+            self.pinned,
+            units.bytes(self.free_bytes)
+        ))?;
+        printer.text(&format!(
+            "{} of those pages ({}) have no pinned objects, so GC.compact could free them",
+            self.movable_pages,
+            units.bytes(self.movable_bytes)
+        ))?;
+        printer.heading("Types of the objects on those pages")?;
+        printer.table(&self.kinds)?;
+        printer.heading("Objects keeping those pages in use, pinned first")?;
+        printer.table(&self.holders)
+    }
+}
+
+// How much of a forked child process's heap is still shared with its parent
+// through copy-on-write. Objects are shared if the parent had the same object
+// at the same address; pages are copied as soon as any object on them is
+// changed, allocated or freed.
+#[derive(Debug, Serialize)]
+pub struct CowReport {
+    pub shared: Stats,
+    // New or changed since the fork
+    pub private: Stats,
+    pub freed: usize,
+    pub shared_pages: usize,
+    pub shared_bytes: usize,
+    pub copied_pages: usize,
+    pub copied_bytes: usize,
+    // Pages allocated since the fork, which were never shared
+    pub new_pages: usize,
+    pub new_bytes: usize,
+    // Types of the private objects on copied pages, with each page's bytes
+    // split between them
+    pub kinds: Table<KindRow>,
+}
+
+impl CowReport {
+    // `parent` are the fingerprints of the parent's objects by address
+    pub fn new(
+        parent: &HashMap<usize, u64>,
+        parent_pages: &[Page],
+        child: &Analysis,
+        child_pages: &[Page],
+        top_n: usize,
+    ) -> CowReport {
+        let graph = child.dominated_subgraph();
+        let indices: HashMap<usize, Index> = graph
+            .node_indices()
+            .filter(|i| !graph[*i].is_root())
+            .map(|i| (graph[i].address, i))
+            .collect();
+        let is_private = |i: Index| parent.get(&graph[i].address) != Some(&graph[i].fingerprint());
+
+        let (mut shared, mut private) = (Stats::default(), Stats::default());
+        for &i in indices.values() {
+            if is_private(i) {
+                private = private.add(graph[i].stats());
+            } else {
+                shared = shared.add(graph[i].stats());
+            }
+        }
+
+        // Pages the parent had, and which of them had objects freed since
+        let mut freed = 0;
+        let mut with_freed = HashSet::new();
+        for page in parent_pages {
+            for slot in &page.objects {
+                if parent.contains_key(&slot.address) && !indices.contains_key(&slot.address) {
+                    freed += 1;
+                    with_freed.insert(page.address);
+                }
+            }
+        }
+        let inherited: HashSet<usize> = parent_pages.iter().map(|page| page.address).collect();
+
+        let (mut shared_pages, mut shared_bytes) = (0, 0);
+        let (mut copied_pages, mut copied_bytes) = (0, 0);
+        let (mut new_pages, mut new_bytes) = (0, 0);
+        let mut by_kind: HashMap<&str, Stats> = HashMap::new();
+        for page in child_pages {
+            if !inherited.contains(&page.address) {
+                new_pages += 1;
+                new_bytes += page.bytes;
+                continue;
+            }
+            // Unreachable objects are left out of the analysis, as garbage
+            let changed: Vec<Index> = page
+                .objects
+                .iter()
+                .filter_map(|slot| indices.get(&slot.address).copied())
+                .filter(|i| is_private(*i))
+                .collect();
+            if changed.is_empty() && !with_freed.contains(&page.address) {
+                shared_pages += 1;
+                shared_bytes += page.bytes;
+                continue;
+            }
+            copied_pages += 1;
+            copied_bytes += page.bytes;
+            for &i in &changed {
+                let stats = by_kind.entry(&graph[i].kind).or_default();
+                // Copied pages are heap memory, all in slots
+                *stats = stats.add(Stats {
+                    count: 1,
+                    bytes: page.bytes / changed.len(),
+                    slot_bytes: page.bytes / changed.len(),
+                });
+            }
+        }
+
+        let mut kinds: Vec<(&str, Stats)> = by_kind.into_iter().collect();
+        kinds.sort_unstable_by_key(|(kind, stats)| (Reverse(stats.bytes), *kind));
+        let rest = kinds
+            .split_off(top_n.min(kinds.len()))
+            .iter()
+            .fold(Stats::default(), |mut acc, (_, stats)| acc.add(*stats));
+
+        CowReport {
+            shared,
+            private,
+            freed,
+            shared_pages,
+            shared_bytes,
+            copied_pages,
+            copied_bytes,
+            new_pages,
+            new_bytes,
+            kinds: Table {
+                largest: kinds
+                    .into_iter()
+                    .map(|(kind, stats)| KindRow {
+                        kind: kind.to_string(),
+                        stats,
+                    })
+                    .collect(),
+                rest,
+            },
+        }
+    }
+}
+
+impl Report for CowReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.text(&format!(
+            "{} objects ({}) unchanged since the fork, {} ({}) new or changed, and {} freed",
+            self.shared.count,
+            units.bytes(self.shared.bytes),
+            self.private.count,
+            units.bytes(self.private.bytes),
+            self.freed
+        ))?;
+        printer.text(&format!(
+            "{} pages ({}) still shared with the parent, {} ({}) copied, and {} ({}) allocated since the fork",
+            self.shared_pages,
+            units.bytes(self.shared_bytes),
+            self.copied_pages,
+            units.bytes(self.copied_bytes),
+            self.new_pages,
+            units.bytes(self.new_bytes)
+        ))?;
+        printer.heading("Types of the new or changed objects on copied pages")?;
+        printer.table(&self.kinds)
+    }
+}
+
+// What the top retainers retain by themselves, next to everything reachable
+// from them: the difference is memory they share with other objects, which
+// dominator-based retained sizes leave out.
+#[derive(Debug, Serialize)]
+pub struct SharingReport {
+    pub objects: Table<SharingRow, ()>,
+}
+
+impl SharingReport {
+    pub fn new(analysis: &Analysis, selection: Selection) -> SharingReport {
+        let graph = analysis.dominated_subgraph();
+        let (largest, _) = analysis.dominator_subtree_stats(
+            selection.top_retainers,
+            selection.sort,
+            &selection.filter,
+        );
+
+        SharingReport {
+            objects: Table {
+                largest: largest
+                    .into_iter()
+                    .map(|(i, retained)| {
+                        let reachable = analysis.reachable_stats(i);
+                        SharingRow {
+                            address: format!("{:#x}", graph[i].address),
+                            kind: graph[i].kind.clone(),
+                            label: graph[i].to_string(),
+                            retained_bytes: retained.bytes,
+                            split: retained.into(),
+                            reachable_bytes: reachable.bytes,
+                            shared_bytes: reachable.bytes - retained.bytes,
+                        }
+                    })
+                    .collect(),
+                rest: (),
+            },
+        }
+    }
+}
+
+impl Report for SharingReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading("Memory retained by, and shared with, the objects retaining the most")?;
+        printer.table(&self.objects)
+    }
+}
+
+// What each category of GC roots retains by itself, e.g. globals or the VM,
+// which tells application leaks from ones inside the interpreter. Memory
+// reachable through several categories is only retained by the root.
+#[derive(Debug, Serialize)]
+pub struct RootsReport {
+    pub categories: Table<RootCategoryRow, ()>,
+    pub shared: Stats,
+}
+
+impl RootsReport {
+    pub fn new(analysis: &Analysis) -> RootsReport {
+        let graph = analysis.dominated_subgraph();
+        let root = analysis.root();
+        let total = analysis.retained_stats(root);
+
+        let mut categories: Vec<RootCategoryRow> = graph
+            .neighbors(root)
+            .filter(|&i| graph[i].is_root())
+            .map(|i| {
+                let retained = analysis.retained_stats(i);
+                RootCategoryRow {
+                    label: graph[i].to_string(),
+                    retained_bytes: retained.bytes,
+                    split: retained.into(),
+                    // Not counting the category itself
+                    retained_count: retained.count - 1,
+                    reachable_bytes: analysis.reachable_stats(i).bytes,
+                    share: if total.bytes == 0 {
+                        0.0
+                    } else {
+                        retained.bytes as f64 / total.bytes as f64
+                    },
+                }
+            })
+            .collect();
+        categories.sort_unstable_by(|a, b| {
+            b.retained_bytes
+                .cmp(&a.retained_bytes)
+                .then_with(|| a.label.cmp(&b.label))
+        });
+
+        // Neither the root nor the categories are objects
+        let shared = Stats {
+            count: total.count
+                - 1
+                - categories.len()
+                - categories.iter().map(|c| c.retained_count).sum::<usize>(),
+            bytes: total.bytes - categories.iter().map(|c| c.retained_bytes).sum::<usize>(),
+            ..Stats::default()
+        };
+
+        RootsReport {
+            categories: Table {
+                largest: categories,
+                rest: (),
+            },
+            shared,
+        }
+    }
+}
+
+impl Report for RootsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.heading("Memory retained through each category of GC roots")?;
+        printer.table(&self.categories)?;
+        printer.text(&format!(
+            "{} ({} objects) reachable through several categories",
+            units.bytes(self.shared.bytes),
+            self.shared.count
+        ))
+    }
+}
+
+// Kinds whose old instances retaining the most memory through few references
+// are the likeliest leaks.
+#[derive(Debug, Serialize)]
+pub struct SuspectsReport {
+    pub max_referrers: usize,
+    pub suspects: Table<KindRow>,
+}
+
+impl SuspectsReport {
+    pub fn new(analysis: &Analysis, max_referrers: usize, selection: Selection) -> SuspectsReport {
+        SuspectsReport {
+            max_referrers,
+            suspects: kind_table(analysis.suspect_stats_by_kind(
+                selection.top_types,
+                max_referrers,
+                &selection.filter,
+                selection.group_by,
+            )),
+        }
+    }
+}
+
+impl Report for SuspectsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading(&format!(
+            "Object types retaining the most through old objects with few referrers (at most {})",
+            self.max_referrers
+        ))?;
+        printer.table(&self.suspects)
+    }
+}
+
+// What each live Thread retains, e.g. through its locals and thread-local
+// variables, plus what the C stack of the thread that wrote the dump keeps
+// alive through `machine_context` roots.
+#[derive(Debug, Serialize)]
+pub struct ThreadsReport {
+    pub threads: Table<SharingRow, ()>,
+    pub stack: Option<Stats>,
+}
+
+impl ThreadsReport {
+    pub fn new(analysis: &Analysis, selection: Selection) -> ThreadsReport {
+        let graph = analysis.dominated_subgraph();
+
+        let mut threads: Vec<SharingRow> = graph
+            .node_indices()
+            .filter(|&i| graph[i].kind == "Thread")
+            .map(|i| {
+                let retained = analysis.retained_stats(i);
+                let reachable = analysis.reachable_stats(i);
+                SharingRow {
+                    address: format!("{:#x}", graph[i].address),
+                    kind: graph[i].kind.clone(),
+                    label: graph[i].to_string(),
+                    retained_bytes: retained.bytes,
+                    split: retained.into(),
+                    reachable_bytes: reachable.bytes,
+                    shared_bytes: reachable.bytes - retained.bytes,
+                }
+            })
+            .collect();
+        threads.sort_unstable_by(|a, b| {
+            b.retained_bytes
+                .cmp(&a.retained_bytes)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        threads.truncate(selection.top_retainers);
+
+        let stack = graph
+            .neighbors(analysis.root())
+            .find(|&i| graph[i].to_string() == "root[machine_context]")
+            .map(|i| {
+                // Not counting the category itself
+                let retained = analysis.retained_stats(i);
+                Stats {
+                    count: retained.count - 1,
+                    ..retained
+                }
+            });
+
+        ThreadsReport {
+            threads: Table {
+                largest: threads,
+                rest: (),
+            },
+            stack,
+        }
+    }
+}
+
+impl Report for ThreadsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let units = printer.units();
+        printer.heading("Memory retained by each thread")?;
+        printer.table(&self.threads)?;
+        if let Some(stack) = self.stack {
+            printer.text(&format!(
+                "{} ({} objects) retained by the C stack of the thread that wrote the dump",
+                units.bytes(stack.bytes),
+                stack.count
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+// A smallest set of references to remove to make an object unreachable, i.e.
+// the links to break to fix a leak.
+#[derive(Debug, Serialize)]
+pub struct CutReport {
+    pub address: String,
+    pub label: String,
+    pub references: Table<LinkRow, ()>,
+}
+
+impl CutReport {
+    pub fn new(analysis: &Analysis, target: Index) -> CutReport {
+        let graph = analysis.dominated_subgraph();
+        let label = |i: Index| {
+            if graph[i].address == 0 {
+                "the root".to_string()
+            } else {
+                graph[i].to_string()
+            }
+        };
+
+        CutReport {
+            address: format!("{:#x}", graph[target].address),
+            label: graph[target].to_string(),
+            references: Table {
+                largest: analysis
+                    .min_cut(target)
+                    .into_iter()
+                    .map(|(i, j)| LinkRow {
+                        from_address: format!("{:#x}", graph[i].address),
+                        from: label(i),
+                        to_address: format!("{:#x}", graph[j].address),
+                        to: label(j),
+                    })
+                    .collect(),
+                rest: (),
+            },
+        }
+    }
+}
+
+impl Report for CutReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading(&format!(
+            "Removing these {} references would make {} unreachable",
+            self.references.largest.len(),
+            self.label
+        ))?;
+        printer.table(&self.references)
+    }
+}
+
+// The objects referenced from the most places, e.g. global caches and
+// interning tables, and the types whose instances are.
+#[derive(Debug, Serialize)]
+pub struct FanInReport {
+    pub objects: Table<FanInRow, ()>,
+    pub by_kind: Table<KindFanInRow, ()>,
+}
+
+impl FanInReport {
+    pub fn new(analysis: &Analysis, selection: Selection) -> FanInReport {
+        let graph = analysis.dominated_subgraph();
+        let mut counts: Vec<(Index, usize)> = analysis
+            .referrer_counts()
+            .into_iter()
+            .filter(|(i, _)| selection.filter.matches(&graph[*i].kind))
+            .collect();
+        counts.sort_unstable_by_key(|(i, referrers)| (Reverse(*referrers), graph[*i].address));
+
+        let mut by_kind: HashMap<Cow<str>, KindFanInRow> = HashMap::new();
+        for (i, referrers) in &counts {
+            let kind = selection.group_by.key(&graph[*i], analysis.gemfile_lock());
+            let row = by_kind.entry(kind.clone()).or_insert_with(|| KindFanInRow {
+                kind: kind.to_string(),
+                objects: 0,
+                referrers: 0,
+                max_referrers: 0,
+            });
+            row.objects += 1;
+            row.referrers += referrers;
+            row.max_referrers = row.max_referrers.max(*referrers);
+        }
+        let mut by_kind: Vec<KindFanInRow> = by_kind.into_values().collect();
+        by_kind.sort_unstable_by(|a, b| {
+            b.referrers
+                .cmp(&a.referrers)
+                .then_with(|| a.kind.cmp(&b.kind))
+        });
+        by_kind.truncate(selection.top_types);
+        counts.truncate(selection.top_retainers);
+
+        FanInReport {
+            objects: Table {
+                largest: counts
+                    .into_iter()
+                    .map(|(i, referrers)| FanInRow {
+                        address: format!("{:#x}", graph[i].address),
+                        kind: graph[i].kind.clone(),
+                        label: graph[i].to_string(),
+                        referrers,
+                        retained_bytes: analysis.retained_stats(i).bytes,
+                        split: analysis.retained_stats(i).into(),
+                    })
+                    .collect(),
+                rest: (),
+            },
+            by_kind: Table {
+                largest: by_kind,
+                rest: (),
+            },
+        }
+    }
+}
+
+impl Report for FanInReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading("Objects referenced by the most objects")?;
+        printer.table(&self.objects)?;
+
+        printer.heading("Object types referenced by the most objects")?;
+        printer.table(&self.by_kind)
+    }
+}
+
+// Overall shape of the reference graph of live objects.
+#[derive(Debug, Serialize)]
+pub struct MetricsReport {
+    pub objects: usize,
+    pub unreachable_objects: usize,
+    pub references: usize,
+    // Strongly connected components, and those of more than one object
+    pub components: usize,
+    pub cycles: usize,
+    pub most_referenced: HopRow,
+    pub max_in_degree: usize,
+    pub most_referencing: HopRow,
+    pub max_out_degree: usize,
+    // The object furthest from the root, by shortest reference path
+    pub deepest: HopRow,
+    pub max_reference_depth: usize,
+    // Number of objects at each depth of the dominator tree
+    pub dominator_depths: Table<DepthRow, ()>,
+}
+
+impl MetricsReport {
+    // `analysis` is of the full graph
+    pub fn new(analysis: &Analysis) -> MetricsReport {
+        let graph = analysis.dominated_subgraph();
+        let (components, cycles) = analysis.component_counts();
+        let max_by = |degree: &dyn Fn(Index) -> usize| {
+            graph
+                .node_indices()
+                .map(|i| (degree(i), i))
+                .max_by_key(|(d, i)| (*d, Reverse(graph[*i].address)))
+                .unwrap_or((0, analysis.root()))
+        };
+        // Degrees count references, including several between the same objects
+        let degree = |i, direction| {
+            graph
+                .edges_directed(i, direction)
+                .map(|e| e.weight().0)
+                .sum::<usize>()
+        };
+        let (max_in_degree, most_referenced) = max_by(&|i| degree(i, Direction::Incoming));
+        let (max_out_degree, most_referencing) = max_by(&|i| degree(i, Direction::Outgoing));
+        let reference_depths = analysis.reference_depths();
+        let (max_reference_depth, deepest) = max_by(&|i| reference_depths[&i]);
+
+        let mut dominator_depths: HashMap<usize, usize> = HashMap::new();
+        for depth in analysis.dominator_depths().values() {
+            *dominator_depths.entry(*depth).or_default() += 1;
+        }
+        let mut dominator_depths: Vec<DepthRow> = dominator_depths
+            .into_iter()
+            .map(|(depth, objects)| DepthRow { depth, objects })
+            .collect();
+        dominator_depths.sort_unstable_by_key(|row| row.depth);
+
+        MetricsReport {
+            objects: graph.node_count(),
+            unreachable_objects: analysis.rest().len(),
+            references: graph.raw_edges().iter().map(|e| e.weight.0).sum(),
+            components,
+            cycles,
+            most_referenced: hop_row(analysis, most_referenced),
+            max_in_degree,
+            most_referencing: hop_row(analysis, most_referencing),
+            max_out_degree,
+            deepest: hop_row(analysis, deepest),
+            max_reference_depth,
+            dominator_depths: Table {
+                largest: dominator_depths,
+                rest: (),
+            },
+        }
+    }
+}
+
+impl Report for MetricsReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let fields = vec![
+            ("Live objects", self.objects.to_string()),
+            ("Unreachable objects", self.unreachable_objects.to_string()),
+            ("References", self.references.to_string()),
+            ("Strongly connected components", self.components.to_string()),
+            ("Cycles", self.cycles.to_string()),
+            (
+                "Most referenced object",
+                format!(
+                    "{} ({} references)",
+                    self.most_referenced.label, self.max_in_degree
+                ),
+            ),
+            (
+                "Object with the most references",
+                format!(
+                    "{} ({} references)",
+                    self.most_referencing.label, self.max_out_degree
+                ),
+            ),
+            (
+                "Longest retention chain",
+                format!(
+                    "{} ({} references from the root)",
+                    self.deepest.label, self.max_reference_depth
+                ),
+            ),
+        ];
+        printer.heading("Reference graph")?;
+        printer.table(&Table {
+            largest: fields
+                .into_iter()
+                .map(|(field, value)| FieldRow { field, value })
+                .collect(),
+            rest: (),
+        })?;
+
+        printer.heading("Objects at each depth of the dominator tree")?;
+        printer.table(&self.dominator_depths)
+    }
+}
+
+// Reference paths from one object (usually the root) to another, one hop
+// per row.
+#[derive(Debug, Serialize)]
+pub struct PathReport {
+    pub from: String,
+    pub address: String,
+    pub paths: Vec<Vec<HopRow>>,
+}
+
+impl PathReport {
+    pub fn new(analysis: &Analysis, source: Index, target: Index, count: usize) -> PathReport {
+        let graph = analysis.dominated_subgraph();
+        PathReport {
+            from: format!("{:#x}", graph[source].address),
+            address: format!("{:#x}", graph[target].address),
+            paths: analysis
+                .paths_between(source, target, count)
+                .into_iter()
+                .map(|path| path.into_iter().map(|i| hop_row(analysis, i)).collect())
+                .collect(),
+        }
+    }
+}
+
+impl Report for PathReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        // The root's address is 0
+        let from = if self.from == "0x0" {
+            "the root"
+        } else {
+            &self.from
+        };
+        if self.paths.is_empty() {
+            return printer.text(&format!("No path from {} to {}", from, self.address));
+        }
+
+        for (n, path) in self.paths.iter().enumerate() {
+            printer.heading(&format!(
+                "Path {} of {} from {} to {}",
+                n + 1,
+                self.paths.len(),
+                from,
+                self.address
+            ))?;
+            printer.table(&Table {
+                largest: path.clone(),
+                rest: (),
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for TreeNode {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.tree(self)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Totals {
+    pub before: Stats,
+    pub after: Stats,
+    pub growth: Delta,
+}
+
+// Growth between two dumps, by object type or allocation site.
+#[derive(Debug, Serialize)]
+pub struct DiffReport {
+    #[serde(skip)]
+    grouping: &'static str,
+    pub live_growth: Table<GrowthRow, Delta>,
+    pub retained_growth: Table<GrowthRow, Delta>,
+    pub totals: Totals,
+}
+
+impl DiffReport {
+    pub fn new(diff: &Diff, top_n: usize, by_location: bool) -> DiffReport {
+        let (before, after) = diff.totals;
+        DiffReport {
+            grouping: if by_location {
+                "Allocation sites"
+            } else {
+                "Object types"
+            },
+            live_growth: growth_table(diff.live_growth_by_kind(top_n)),
+            retained_growth: growth_table(diff.retained_growth_by_kind(top_n)),
+            totals: Totals {
+                before,
+                after,
+                growth: diff.total_growth(),
+            },
+        }
+    }
+}
+
+impl Report for DiffReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading(&format!(
+            "{} with the most growth in live memory",
+            self.grouping
+        ))?;
+        printer.table(&self.live_growth)?;
+
+        printer.heading(&format!(
+            "{} with the most growth in retained memory",
+            self.grouping
+        ))?;
+        printer.table(&self.retained_growth)?;
+
+        let units = printer.units();
+        printer.text(&format!(
+            "Total live memory: {} -> {} ({}, {:+} objects)",
+            units.bytes(self.totals.before.bytes),
+            units.bytes(self.totals.after.bytes),
+            units.signed_bytes(self.totals.growth.bytes),
+            self.totals.growth.count
+        ))
+    }
+}
+
+// Per-type growth across a series of dumps.
+#[derive(Debug, Serialize)]
+pub struct TrendReport {
+    pub dumps: usize,
+    pub monotonic_growth: Table<SeriesRow, ()>,
+    pub other_growth: Table<SeriesRow, ()>,
+}
+
+impl TrendReport {
+    pub fn new(trend: &Trend, top_n: usize) -> TrendReport {
+        TrendReport {
+            dumps: trend.dumps,
+            monotonic_growth: series_table(trend.monotonic_growth(top_n)),
+            other_growth: series_table(trend.other_growth(top_n)),
+        }
+    }
+}
+
+impl Report for TrendReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        printer.heading(&format!(
+            "Object types growing monotonically across {} dumps (retained memory)",
+            self.dumps
+        ))?;
+        printer.table(&self.monotonic_growth)?;
+
+        printer.heading("Other object types with the most growth (retained memory)")?;
+        printer.table(&self.other_growth)
+    }
+}
+
+// Regressions in retained memory relative to a baseline.
+#[derive(Debug, Serialize)]
+pub struct CheckReport {
+    pub max_growth: f64,
+    pub regressions: Table<RegressionRow, ()>,
+}
+
+impl CheckReport {
+    pub fn new(regressions: &[Regression], max_growth: f64) -> CheckReport {
+        CheckReport {
+            max_growth,
+            regressions: Table {
+                largest: regressions
+                    .iter()
+                    .map(|r| RegressionRow {
+                        kind: r.kind.clone(),
+                        before_bytes: r.before.bytes,
+                        after_bytes: r.after.bytes,
+                        growth_bytes: r.growth().bytes,
+                        growth_slot_bytes: r.growth().slot_bytes,
+                        growth_malloc_bytes: r.growth().malloc_bytes(),
+                    })
+                    .collect(),
+                rest: (),
+            },
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.regressions.largest.is_empty()
+    }
+}
+
+impl Report for CheckReport {
+    fn print<P: Printer>(&self, printer: &mut P) -> io::Result<()> {
+        let percent = self.max_growth * 100.0;
+        if self.passed() {
+            return printer.text(&format!(
+                "Retained memory is within {}% of the baseline",
+                percent
+            ));
+        }
+
+        printer.heading(&format!(
+            "Retained memory grew by more than {}% over the baseline",
+            percent
+        ))?;
+        printer.table(&self.regressions)
+    }
+}
+
+// Renders the sections of a human-readable report.
+pub trait Printer {
+    fn heading(&mut self, title: &str) -> io::Result<()>;
+
+    fn table<R: Row + Display>(&mut self, table: &Table<R, R::Rest>) -> io::Result<()>;
+
+    // A line of text outside any table, such as a total
+    fn text(&mut self, text: &str) -> io::Result<()>;
+
+    fn tree(&mut self, tree: &TreeNode) -> io::Result<()>;
+
+    // How byte counts are printed, for reports formatting their own text
+    fn units(&self) -> Units;
+}
+
+fn describe(node: &TreeNode, units: Units) -> String {
+    format!(
+        "{}: {}",
+        node.label,
+        sizes(
+            Stats {
+                count: node.count,
+                bytes: node.retained_bytes,
+                slot_bytes: node.slot_bytes,
+            },
+            units
+        )
+    )
+}
+
+// Visits the tree depth-first without recursing, since dominator chains can
+// be arbitrarily deep, passing each node's depth and whether it is the last of
+// its siblings.
+fn walk_tree<'a, F>(tree: &'a TreeNode, mut visit: F) -> io::Result<()>
+where
+    F: FnMut(&'a TreeNode, usize, bool) -> io::Result<()>,
+{
+    let mut stack = vec![(tree, 0, true)];
+    while let Some((node, depth, last)) = stack.pop() {
+        visit(node, depth, last)?;
+        for (i, child) in node.children.iter().enumerate().rev() {
+            stack.push((child, depth + 1, i + 1 == node.children.len()));
+        }
+    }
+    Ok(())
+}
+
+pub struct TextPrinter<W: Write> {
+    out: W,
+    units: Units,
+    started: bool,
+}
+
+impl<W: Write> TextPrinter<W> {
+    pub fn new(out: W, units: Units) -> TextPrinter<W> {
+        TextPrinter {
+            out,
+            units,
+            started: false,
+        }
+    }
+
+    // Separates each section from the previous one by a blank line
+    fn section(&mut self) -> io::Result<()> {
+        if self.started {
+            writeln!(self.out)?;
+        }
+        self.started = true;
+        Ok(())
+    }
+}
+
+impl<W: Write> Printer for TextPrinter<W> {
+    fn heading(&mut self, title: &str) -> io::Result<()> {
+        self.section()?;
+        writeln!(self.out, "{}:", title)
+    }
+
+    fn table<R: Row + Display>(&mut self, table: &Table<R, R::Rest>) -> io::Result<()> {
+        if table.largest.is_empty() {
+            return writeln!(self.out, "None");
+        }
+
+        for row in table.largest.iter() {
+            writeln!(self.out, "{}: {}", row, row.sizes(self.units))?;
+        }
+
+        if table.has_rest() {
+            writeln!(self.out, "...: {}", R::rest_sizes(table.rest, self.units))?;
+        }
+
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str) -> io::Result<()> {
+        self.section()?;
+        writeln!(self.out, "{}", text)
+    }
+
+    // Indented like `cargo tree`
+    fn tree(&mut self, tree: &TreeNode) -> io::Result<()> {
+        self.section()?;
+        let out = &mut self.out;
+        let units = self.units;
+
+        // Whether each ancestor of the current node is the last of its siblings
+        let mut last_ancestors: Vec<bool> = Vec::new();
+        walk_tree(tree, |node, depth, last| {
+            last_ancestors.truncate(depth);
+            let mut prefix = String::new();
+            for &ancestor_last in last_ancestors.iter().skip(1) {
+                prefix.push_str(if ancestor_last { "    " } else { "│   " });
+            }
+            if depth > 0 {
+                prefix.push_str(if last { "└── " } else { "├── " });
+            }
+            last_ancestors.push(last);
+            writeln!(out, "{}{}", prefix, describe(node, units))
+        })
+    }
+
+    fn units(&self) -> Units {
+        self.units
+    }
+}
+
+pub struct MarkdownPrinter<W: Write> {
+    out: W,
+    units: Units,
+}
+
+impl<W: Write> MarkdownPrinter<W> {
+    pub fn new(out: W, units: Units) -> MarkdownPrinter<W> {
+        MarkdownPrinter { out, units }
+    }
+}
+
+impl<W: Write> Printer for MarkdownPrinter<W> {
+    fn heading(&mut self, title: &str) -> io::Result<()> {
+        writeln!(self.out, "### {}\n", title)
+    }
+
+    fn table<R: Row + Display>(&mut self, table: &Table<R, R::Rest>) -> io::Result<()> {
+        let header = R::HEADER;
+        writeln!(self.out, "| {} |", header.join(" | "))?;
+        writeln!(
+            self.out,
+            "|{}",
+            header
+                .iter()
+                .enumerate()
+                .map(|(i, _)| if i < R::LABEL_COLUMNS {
+                    " --- |"
+                } else {
+                    " ---: |"
+                })
+                .collect::<String>()
+        )?;
+
+        let write_row = |out: &mut W, cells: Vec<String>| {
+            let cells: Vec<String> = cells.iter().map(|c| escape_markdown(c)).collect();
+            writeln!(out, "| {} |", cells.join(" | "))
+        };
+
+        for row in table.largest.iter() {
+            write_row(&mut self.out, row.cells(self.units))?;
+        }
+        if table.has_rest() {
+            write_row(&mut self.out, R::rest_cells(table.rest, self.units))?;
+        }
+        writeln!(self.out)
+    }
+
+    fn text(&mut self, text: &str) -> io::Result<()> {
+        writeln!(self.out, "{}\n", escape_markdown(text))
+    }
+
+    // As a nested list
+    fn tree(&mut self, tree: &TreeNode) -> io::Result<()> {
+        let out = &mut self.out;
+        let units = self.units;
+        walk_tree(tree, |node, depth, _| {
+            writeln!(
+                out,
+                "{}- {}",
+                "  ".repeat(depth),
+                escape_markdown(&describe(node, units))
+            )
+        })
+    }
+
+    fn units(&self) -> Units {
+        self.units
+    }
+}
+
+// Writes each table as CSV, preceded by its heading as a "#" comment line and
+// separated from the next by a blank line.
+pub struct CsvPrinter<W: Write> {
+    out: W,
+    units: Units,
+    started: bool,
+}
+
+impl<W: Write> CsvPrinter<W> {
+    pub fn new(out: W, units: Units) -> CsvPrinter<W> {
+        CsvPrinter {
+            out,
+            units,
+            started: false,
+        }
+    }
+
+    fn comment(&mut self, text: &str) -> io::Result<()> {
+        if self.started {
+            writeln!(self.out)?;
+        }
+        self.started = true;
+        writeln!(self.out, "# {}", text)
+    }
+}
+
+impl<W: Write> Printer for CsvPrinter<W> {
+    fn heading(&mut self, title: &str) -> io::Result<()> {
+        self.comment(title)
+    }
+
+    fn table<R: Row + Display>(&mut self, table: &Table<R, R::Rest>) -> io::Result<()> {
+        let mut writer = csv::Writer::from_writer(&mut self.out);
+        table.write_records(&mut writer)?;
+        writer.flush()
+    }
+
+    fn text(&mut self, text: &str) -> io::Result<()> {
+        self.comment(text)
+    }
+
+    // Flattened, with each node's depth and the address of its parent
+    fn tree(&mut self, tree: &TreeNode) -> io::Result<()> {
+        let mut writer = csv::Writer::from_writer(&mut self.out);
+        writer.write_record([
+            "depth",
+            "address",
+            "parent",
+            "kind",
+            "label",
+            "self_bytes",
+            "retained_bytes",
+            "slot_bytes",
+            "malloc_bytes",
+            "count",
+        ])?;
+
+        let mut ancestors: Vec<&str> = Vec::new();
+        walk_tree(tree, |node, depth, _| {
+            ancestors.truncate(depth);
+            writer.write_record([
+                depth.to_string(),
+                node.address.clone(),
+                ancestors.last().unwrap_or(&"").to_string(),
+                node.kind.clone(),
+                node.label.clone(),
+                node.self_bytes.to_string(),
+                node.retained_bytes.to_string(),
+                node.slot_bytes.to_string(),
+                node.malloc_bytes.to_string(),
+                node.count.to_string(),
+            ])?;
+            ancestors.push(&node.address);
+            Ok(())
+        })?;
+        writer.flush()
+    }
+
+    fn units(&self) -> Units {
+        self.units
+    }
+}
+
+// Escapes characters which would otherwise break a markdown table cell.
+fn escape_markdown(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('`', "\\`")
+        .replace('*', "\\*")
+        .replace('_', "\\_")
+        .replace('<', "&lt;")
+}
@@ -0,0 +1,100 @@
+use crate::analyze::Analysis;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+const SCHEMA: &str = "https://www.speedscope.app/file-format-schema.json";
+
+// See https://github.com/jlfwong/speedscope/wiki/Importing-from-custom-sources
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedscopeFile {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    pub shared: Shared,
+    profiles: Vec<SampledProfile>,
+    name: String,
+    active_profile_index: usize,
+    exporter: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Shared {
+    pub frames: Vec<Frame>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Frame {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampledProfile {
+    #[serde(rename = "type")]
+    profile_type: &'static str,
+    name: String,
+    unit: &'static str,
+    start_value: usize,
+    end_value: usize,
+    pub samples: Vec<Vec<usize>>,
+    pub weights: Vec<usize>,
+}
+
+// Treats every reachable object as a sample weighted by its own size, with
+// its dominator chain as the stack, so that each frame's total weight is the
+// memory retained by that object.
+pub fn profile(analysis: &Analysis, name: &str, class_name_only: bool) -> SpeedscopeFile {
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut frame_indices: HashMap<String, usize> = HashMap::new();
+    let mut samples = Vec::new();
+    let mut weights = Vec::new();
+
+    analysis.for_each_dominator_chain(|chain| {
+        let stack = chain
+            .iter()
+            .map(|obj| {
+                let name = obj.format(class_name_only);
+                *frame_indices.entry(name).or_insert_with_key(|name| {
+                    frames.push(Frame { name: name.clone() });
+                    frames.len() - 1
+                })
+            })
+            .collect();
+
+        samples.push(stack);
+        weights.push(chain[chain.len() - 1].bytes);
+    });
+
+    SpeedscopeFile {
+        schema: SCHEMA,
+        shared: Shared { frames },
+        profiles: vec![SampledProfile {
+            profile_type: "sampled",
+            name: name.to_string(),
+            unit: "bytes",
+            start_value: 0,
+            end_value: weights.iter().sum(),
+            samples,
+            weights,
+        }],
+        name: name.to_string(),
+        active_profile_index: 0,
+        exporter: format!("reap v{}", env!("CARGO_PKG_VERSION")),
+    }
+}
+
+pub fn write_speedscope(profile: &SpeedscopeFile, filename: &Path) -> io::Result<()> {
+    let file = File::create(filename)?;
+    let mut writer = io::BufWriter::new(file);
+    serde_json::to_writer(&mut writer, profile)?;
+    writer.flush()
+}
+
+impl SpeedscopeFile {
+    pub fn profile(&self) -> &SampledProfile {
+        &self.profiles[0]
+    }
+}
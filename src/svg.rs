@@ -0,0 +1,48 @@
+use crate::object::*;
+use layout::backends::svg::SVGWriter;
+use layout::core::base::Orientation;
+use layout::core::style::StyleAttr;
+use layout::std_shapes::render::get_shape_size;
+use layout::std_shapes::shapes::{Arrow, Element, ShapeKind};
+use layout::topo::layout::VisualGraph;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const FONT_SIZE: usize = 14;
+
+// Lays out & renders a graph as SVG in-process, for machines where graphviz
+// isn't installed. The layout engine is simpler than graphviz's, so large
+// graphs may come out less tidy than with `--dot`.
+pub fn write_svg(graph: &ReferenceGraph, filename: &Path) -> io::Result<()> {
+    let orientation = Orientation::TopToBottom;
+    let mut visual = VisualGraph::new(orientation);
+
+    let handles: Vec<_> = graph
+        .node_indices()
+        .map(|i| {
+            let shape = ShapeKind::new_box(&graph[i].to_string());
+            let size = get_shape_size(orientation, &shape, FONT_SIZE, false);
+            visual.add_node(Element::create(
+                shape,
+                StyleAttr::simple(),
+                orientation,
+                size,
+            ))
+        })
+        .collect();
+
+    for e in graph.raw_edges() {
+        visual.add_edge(
+            Arrow::simple(&e.weight.to_string()),
+            handles[e.source().index()],
+            handles[e.target().index()],
+        );
+    }
+
+    let mut svg = SVGWriter::new();
+    if graph.node_count() > 0 {
+        visual.do_it(false, false, false, &mut svg);
+    }
+    fs::write(filename, svg.finalize())
+}
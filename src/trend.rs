@@ -0,0 +1,93 @@
+use crate::diff::{Delta, Summary};
+use crate::object::*;
+use std::collections::BTreeSet;
+
+// Stats for a single object type across a series of dumps, in dump order.
+#[derive(Debug)]
+pub struct Series {
+    pub kind: String,
+    pub live: Vec<Stats>,
+    pub retained: Vec<Stats>,
+}
+
+impl Series {
+    pub fn retained_growth(&self) -> Delta {
+        match (self.retained.first(), self.retained.last()) {
+            (Some(first), Some(last)) => Delta::between(*first, *last),
+            _ => Delta::default(),
+        }
+    }
+
+    // True if the object count or retained bytes never shrink from one dump
+    // to the next, and grow overall.
+    pub fn is_monotonic(&self) -> bool {
+        let growth = self.retained_growth();
+        let live_growth = match (self.live.first(), self.live.last()) {
+            (Some(first), Some(last)) => Delta::between(*first, *last),
+            _ => Delta::default(),
+        };
+
+        let counts_grow =
+            live_growth.count > 0 && self.live.windows(2).all(|w| w[0].count <= w[1].count);
+        let bytes_grow =
+            growth.bytes > 0 && self.retained.windows(2).all(|w| w[0].bytes <= w[1].bytes);
+
+        counts_grow || bytes_grow
+    }
+}
+
+#[derive(Debug)]
+pub struct Trend {
+    pub dumps: usize,
+    series: Vec<Series>,
+}
+
+impl Trend {
+    pub fn new(summaries: &[Summary]) -> Trend {
+        // The root isn't an object type, and grows with the heap
+        let kinds: BTreeSet<&String> = summaries
+            .iter()
+            .flat_map(|s| s.live.keys().chain(s.retained.keys()))
+            .filter(|kind| !is_root_key(kind))
+            .collect();
+
+        let series = kinds
+            .into_iter()
+            .map(|kind| Series {
+                kind: kind.clone(),
+                live: summaries
+                    .iter()
+                    .map(|s| s.live.get(kind).cloned().unwrap_or_default())
+                    .collect(),
+                retained: summaries
+                    .iter()
+                    .map(|s| s.retained.get(kind).cloned().unwrap_or_default())
+                    .collect(),
+            })
+            .collect();
+
+        Trend {
+            dumps: summaries.len(),
+            series,
+        }
+    }
+
+    // Object types growing monotonically, largest retained growth first.
+    pub fn monotonic_growth(&self, top_n: usize) -> Vec<&Series> {
+        self.largest_growth(top_n, |s| s.is_monotonic())
+    }
+
+    // Object types with positive, but not monotonic, retained growth.
+    pub fn other_growth(&self, top_n: usize) -> Vec<&Series> {
+        self.largest_growth(top_n, |s| {
+            !s.is_monotonic() && s.retained_growth().bytes > 0
+        })
+    }
+
+    fn largest_growth<F: Fn(&Series) -> bool>(&self, top_n: usize, filter: F) -> Vec<&Series> {
+        let mut sorted: Vec<&Series> = self.series.iter().filter(|s| filter(s)).collect();
+        sorted.sort_by_key(|s| -s.retained_growth().bytes);
+        sorted.truncate(top_n);
+        sorted
+    }
+}
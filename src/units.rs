@@ -0,0 +1,51 @@
+const BINARY_UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+// How byte counts are printed in reports: scaled to binary units with a fixed
+// number of decimal places (e.g. "3.4 MiB"), or exactly (e.g. "3565158 B").
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Human { precision: usize },
+    Exact,
+}
+
+impl Default for Units {
+    fn default() -> Units {
+        Units::Human { precision: 1 }
+    }
+}
+
+impl Units {
+    pub fn new(exact: bool, precision: usize) -> Units {
+        if exact {
+            Units::Exact
+        } else {
+            Units::Human { precision }
+        }
+    }
+
+    pub fn bytes(self, bytes: usize) -> String {
+        let precision = match self {
+            Units::Human { precision } => precision,
+            Units::Exact => return format!("{} B", bytes),
+        };
+
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit + 1 < BINARY_UNITS.len() {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            format!("{} B", bytes)
+        } else {
+            format!("{:.*} {}", precision, value, BINARY_UNITS[unit])
+        }
+    }
+
+    // With an explicit sign, e.g. "+1.2 KiB" or "-40 B"
+    pub fn signed_bytes(self, bytes: i64) -> String {
+        let sign = if bytes < 0 { "-" } else { "+" };
+        format!("{}{}", sign, self.bytes(bytes.unsigned_abs() as usize))
+    }
+}
@@ -0,0 +1,133 @@
+use crate::diff::{Delta, Summary};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Finds dumps as they appear in a directory
+pub struct Watcher {
+    dir: PathBuf,
+    // Sizes of files seen still growing, or only once
+    sizes: HashMap<PathBuf, u64>,
+    // Sizes of files already found, which are found again if they change
+    seen: HashMap<PathBuf, u64>,
+    ignored: HashSet<PathBuf>,
+}
+
+// Names of files that could be dumps, e.g. heap.json or heap.json.zst
+fn is_dump(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "json" || ext == "gz" || ext == "zst")
+}
+
+impl Watcher {
+    pub fn new(dir: &Path) -> io::Result<Watcher> {
+        Ok(Watcher {
+            dir: fs::canonicalize(dir)?,
+            sizes: HashMap::new(),
+            seen: HashMap::new(),
+            ignored: HashSet::new(),
+        })
+    }
+
+    // Leaves a file out, e.g. one being written to the same directory
+    pub fn ignore(&mut self, path: &Path) {
+        let dir = match path.parent() {
+            Some(parent) if parent != Path::new("") => fs::canonicalize(parent),
+            _ => fs::canonicalize("."),
+        };
+        if let (Ok(dir), Some(name)) = (dir, path.file_name()) {
+            self.ignored.insert(dir.join(name));
+        }
+    }
+
+    // Files that have appeared (or changed) since the last poll, oldest
+    // first (by name where they're as old), once they've stopped growing. Dumps written under a temporary
+    // name and renamed when complete, as by `capture`, are left until they
+    // are.
+    pub fn poll(&mut self) -> io::Result<Vec<PathBuf>> {
+        let mut files = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if !is_dump(&path) || self.ignored.contains(&path) {
+                continue;
+            }
+            // Files may be removed while listing the others
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) if metadata.is_file() => metadata,
+                _ => continue,
+            };
+            if self.seen.get(&path) != Some(&metadata.len()) {
+                files.push((metadata.modified().ok(), path, metadata.len()));
+            }
+        }
+        files.sort();
+
+        let mut complete = vec![];
+        for (_, path, len) in files {
+            if len > 0 && self.sizes.get(&path) == Some(&len) {
+                self.sizes.remove(&path);
+                self.seen.insert(path.clone(), len);
+                complete.push(path);
+            } else {
+                self.sizes.insert(path, len);
+            }
+        }
+        Ok(complete)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Row {
+    dump: String,
+    kind: String,
+    count: usize,
+    bytes: usize,
+    slot_bytes: usize,
+    malloc_bytes: usize,
+    count_growth: Option<i64>,
+    bytes_growth: Option<i64>,
+    slot_bytes_growth: Option<i64>,
+    malloc_bytes_growth: Option<i64>,
+}
+
+// Appends live memory by object type in a dump, with its growth since the
+// previous one, to a CSV file, so a soak test's dumps can be charted. The
+// growth is left empty for the first dump, with none before it.
+pub fn append_csv(
+    filename: &Path,
+    dump: &Path,
+    summary: &Summary,
+    previous: Option<&Summary>,
+) -> csv::Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filename)?;
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(file.metadata()?.len() == 0)
+        .from_writer(file);
+
+    let mut kinds: Vec<_> = summary.live.iter().collect();
+    kinds.sort_by(|(a, x), (b, y)| y.bytes.cmp(&x.bytes).then(a.cmp(b)));
+    for (kind, stats) in kinds {
+        let growth = previous.map(|previous| {
+            let before = previous.live.get(kind).copied().unwrap_or_default();
+            Delta::between(before, *stats)
+        });
+        writer.serialize(Row {
+            dump: dump.display().to_string(),
+            kind: kind.clone(),
+            count: stats.count,
+            bytes: stats.bytes,
+            slot_bytes: stats.slot_bytes,
+            malloc_bytes: stats.malloc_bytes(),
+            count_growth: growth.map(|growth| growth.count),
+            bytes_growth: growth.map(|growth| growth.bytes),
+            slot_bytes_growth: growth.map(|growth| growth.slot_bytes),
+            malloc_bytes_growth: growth.map(|growth| growth.malloc_bytes()),
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
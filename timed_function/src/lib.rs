@@ -53,7 +53,7 @@ pub fn timed(_: TokenStream, item: TokenStream) -> TokenStream {
             let start = Instant::now();
             let result = { #body };
             let elapsed = start.elapsed();
-            println!("{}: {}.{}s", #label, elapsed.as_secs(), elapsed.subsec_millis());
+            eprintln!("{}: {}.{}s", #label, elapsed.as_secs(), elapsed.subsec_millis());
 
             result
         }